@@ -1,5 +1,5 @@
 #[allow(unused_imports)]
-use group02::network::config::{build_network_from_setup, compute_accuracy, compute_max_output_spike, network_setup_from_file};
+use group02::network::config::{build_network_from_setup, network_setup_from_file};
 #[allow(unused_imports)]
 use group02::resilience::components::ComponentType; #[allow(unused_imports)]
 use group02::resilience::fault_models::FaultType;
@@ -21,14 +21,7 @@ fn main() {
 
     // INITIAL PROCESSING
     // *******************
-    let mut vec_max = Vec::new();
-    for input_spikes in input_spike_train.iter() {
-        let output_spikes = snn.process_input(&input_spikes, None);
-        let max = compute_max_output_spike(output_spikes);
-        vec_max.push(max);
-    }
-
-    let acc = compute_accuracy(vec_max, &targets);
+    let acc = snn.process_batch(&input_spike_train, &targets, None, None).accuracy;
     println!("Accuracy = {}%", acc);
 
     // SIMULATION
@@ -0,0 +1,83 @@
+use std::thread;
+use crate::network::config::{compute_decision_accuracy, compute_max_output_spike};
+use crate::network::decoder::{Decision, Decoder};
+use crate::network::snn::SNN;
+use crate::resilience::fault_models::InjectedFault;
+
+// Batch inference
+// ---------------
+// The input spike trains of a batch are split in consecutive chunks, one for each worker thread:
+// the workers share the network, which is only read by the inferences, and each sample is processed
+// with its own inference state (see InferenceState).
+// Each sample starts from a new state, with the weights of the network: the weights learned with STDP on a sample
+// are not used for the following ones, so the result doesn't depend on the number of workers,
+// and the network itself (and the state of its inferences with process_input) is not modified by the batch.
+
+// Result of the processing of a batch of input spike trains (in the order of the inputs)
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchResult {
+    pub output_spikes: Vec<Vec<Vec<u8>>>, // output spikes of each input (one row for each output neuron)
+    pub predictions: Vec<Decision>,       // class predicted by the network for each input
+    pub accuracy: f64,                    // percentage of the predictions matching the targets
+}
+
+impl SNN
+{
+    /**
+        It processes the input spike trains of the batch concurrently (one worker for each available core)
+        - @param targets: class of each input
+        - @param injected_fault: fault injected during the processing of each input, if any
+        - @param decoder: decoder of the predictions (None => output neuron with the most spikes, as compute_max_output_spike)
+     */
+    pub fn process_batch(&self, input_spike_train: &[Vec<Vec<u8>>], targets: &Vec<u8>, injected_fault: Option<InjectedFault>, decoder: Option<Decoder>) -> BatchResult {
+        let num_workers = thread::available_parallelism().map_or(1, |n| n.get());
+        self.process_batch_with_workers(input_spike_train, targets, injected_fault, decoder, num_workers)
+    }
+
+    /**
+        It processes the input spike trains of the batch as process_batch, with the given number of worker threads
+        (at most one for each input)
+     */
+    pub fn process_batch_with_workers(&self, input_spike_train: &[Vec<Vec<u8>>], targets: &Vec<u8>, injected_fault: Option<InjectedFault>, decoder: Option<Decoder>, num_workers: usize) -> BatchResult {
+        if num_workers == 0 {
+            panic!("Error: The number of workers of a batch should be at least 1");
+        }
+        if input_spike_train.len() != targets.len() {
+            panic!("Error: The number of targets is not consistent with the number of inputs of the batch");
+        }
+        if input_spike_train.is_empty() {
+            return BatchResult { output_spikes: Vec::new(), predictions: Vec::new(), accuracy: 0.0 };
+        }
+
+        // each worker processes a chunk of consecutive inputs, each one with its own inference state
+        let chunk_size = input_spike_train.len().div_ceil(num_workers);
+        let results = thread::scope(|scope| {
+            let handles = input_spike_train.chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk.iter()
+                            .map(|input_spikes| self.process_sample(input_spikes, injected_fault, decoder))
+                            .collect::<Vec<(Vec<Vec<u8>>, Decision)>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect::<Vec<(Vec<Vec<u8>>, Decision)>>()
+        });
+
+        let (output_spikes, predictions): (Vec<Vec<Vec<u8>>>, Vec<Decision>) = results.into_iter().unzip();
+        let accuracy = compute_decision_accuracy(predictions.clone(), targets);
+        BatchResult { output_spikes, predictions, accuracy }
+    }
+
+    // Output spikes and prediction of the network for a single input (processed with a new inference state)
+    fn process_sample(&self, input_spikes: &Vec<Vec<u8>>, injected_fault: Option<InjectedFault>, decoder: Option<Decoder>) -> (Vec<Vec<u8>>, Decision) {
+        let mut state = self.new_inference_state();
+        let output_spikes = self.process_input_with_state(&mut state, input_spikes, injected_fault);
+        let membrane_potentials = state.get_output_membrane_potentials();
+        let prediction = match decoder {
+            Some(decoder) => decoder.decode(&output_spikes, &membrane_potentials),
+            None => Decision::Class(compute_max_output_spike(output_spikes.clone())),
+        };
+        (output_spikes, prediction)
+    }
+}
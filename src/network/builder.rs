@@ -1,7 +1,14 @@
-use std::sync::{Arc, Mutex};
-use crate::network::layer::Layer;
+use std::fmt::Debug;
+use crate::network::layer::{Layer, MAX_DELAY};
 use crate::network::neuron::neuron::Neuron;
 use crate::network::snn::SNN;
+use crate::network::network_layer::NetworkLayer;
+use crate::network::storage_format::StorageFormat;
+use crate::network::stdp::StdpParameters;
+use crate::network::convolution::Convolution;
+use crate::network::pooling::Pooling;
+use crate::network::skip_connection::SkipConnection;
+use crate::network::pruning::PruningCriterion;
 
 // SNNBuilder and Building a Spiking Neural Network
 // ------------------------------------------------
@@ -15,9 +22,15 @@ use crate::network::snn::SNN;
 /**
      (the network) providing an interface
     to specify all the parameters that describe it. The user can specify:
-    - Layers
+    - Layers (fully connected, convolutional or pooling)
+    - Skip connections with earlier layers (directed acyclic graph)
     - Neurons (with the relative parameters) of each layer
     - Extra-weights and intra-weights
+    - Delays of the connections with the previous layer
+    - Winner-take-all layers
+    - STDP learning of the extra-weights
+    - Sparse storage of the weights
+    - Pruning of the weights
  **/
 
 #[derive(Clone)]
@@ -26,7 +39,16 @@ pub struct BuilderParameters<N: Neuron> { //struct that contains all the paramet
     neurons: Vec<Vec<N>>,               // neurons in each layer
     extra_weights: Vec<Vec<Vec<f64>>>,  // weights of the connections between each neuron and the neurons in the previous layer
     intra_weights: Vec<Vec<Vec<f64>>>,  // weights of the connections between each neuron and the neurons in the same layer
+    delays: Vec<Vec<Vec<u64>>>,         // delays (in time steps) of the connections between each neuron and the neurons in the previous layer
+    winner_take_all: Vec<Option<usize>>, // number of winners of each k-winner-take-all layer (None for the other layers)
+    stdp: Vec<Option<StdpParameters>>,  // STDP rule of each layer learning its extra weights (None for the other layers)
+    convolutions: Vec<Option<Convolution>>, // shared kernels of each convolutional layer (None for the other layers)
+    poolings: Vec<Option<Pooling>>,     // pooling of each pooling layer (None for the other layers)
+    skip_connections: Vec<Vec<SkipConnection>>, // connections of each layer with the earlier layers
     num_layers: usize,                  // number of layers
+    weight_format: StorageFormat,       // format in which the weights of all the layers are stored
+    sparse_weights: bool,               // true if the extra and intra weights of all the layers are stored as sparse matrices
+    pruning: Option<PruningCriterion>,  // criterion used to prune the extra and intra weights of all the layers, if any
 }
 
 impl<N: Neuron + Clone> BuilderParameters<N> {
@@ -40,6 +62,33 @@ impl<N: Neuron + Clone> BuilderParameters<N> {
     pub fn get_intra_weights(&self) -> Vec<Vec<Vec<f64>>> {
         self.intra_weights.clone()
     }
+    pub fn get_delays(&self) -> Vec<Vec<Vec<u64>>> {
+        self.delays.clone()
+    }
+    pub fn get_winner_take_all(&self) -> Vec<Option<usize>> {
+        self.winner_take_all.clone()
+    }
+    pub fn get_stdp(&self) -> Vec<Option<StdpParameters>> {
+        self.stdp.clone()
+    }
+    pub fn get_convolutions(&self) -> Vec<Option<Convolution>> {
+        self.convolutions.clone()
+    }
+    pub fn get_poolings(&self) -> Vec<Option<Pooling>> {
+        self.poolings.clone()
+    }
+    pub fn get_skip_connections(&self) -> Vec<Vec<SkipConnection>> {
+        self.skip_connections.clone()
+    }
+    pub fn get_weight_format(&self) -> StorageFormat {
+        self.weight_format
+    }
+    pub fn get_sparse_weights(&self) -> bool {
+        self.sparse_weights
+    }
+    pub fn get_pruning(&self) -> Option<PruningCriterion> {
+        self.pruning
+    }
 }
 
 #[derive(Clone)]
@@ -47,7 +96,7 @@ pub struct SNNBuilder<N: Neuron> {
     parameters: BuilderParameters<N>
 }
 
-impl<N: Neuron + Clone + Send> SNNBuilder<N> {
+impl<N: Neuron + Clone + Send + Sync + Debug + 'static> SNNBuilder<N> {
     pub fn new(input_length: usize) -> Self {
         SNNBuilder {
             parameters: BuilderParameters {
@@ -55,7 +104,16 @@ impl<N: Neuron + Clone + Send> SNNBuilder<N> {
                 neurons: Vec::new(),
                 extra_weights: Vec::new(),
                 intra_weights: Vec::new(),
-                num_layers: 0
+                delays: Vec::new(),
+                winner_take_all: Vec::new(),
+                stdp: Vec::new(),
+                convolutions: Vec::new(),
+                poolings: Vec::new(),
+                skip_connections: Vec::new(),
+                num_layers: 0,
+                weight_format: StorageFormat::F64,
+                sparse_weights: false,
+                pruning: None
             }
         }
     }
@@ -105,6 +163,43 @@ impl<N: Neuron + Clone + Send> SNNBuilder<N> {
         }
     }
 
+    fn check_delays(&self, delays: &[Vec<u64>]) {
+        let extra_weights = &self.parameters.extra_weights[self.parameters.num_layers - 1];
+        if extra_weights.len() != delays.len() {
+            panic!("Error: The number of neurons should be equal to the number of rows of the delays matrix");
+        }
+        for (row, weights) in delays.iter().zip(extra_weights) {
+            if row.len() != weights.len() {
+                panic!("Error: The number of columns of the delays matrix should be equal to the number of columns of the extra_weights matrix");
+            }
+            for d in row {
+                if *d > MAX_DELAY {
+                    panic!("Error: The delay of a connection should be at most {} time steps", MAX_DELAY);
+                }
+            }
+        }
+    }
+
+    fn check_input_volume(&self, output_length: usize, input_length: usize, neurons_len: usize) {
+        if output_length != neurons_len {
+            panic!("Error: The number of neurons should be equal to the size of the output volume of the layer");
+        }
+        if self.parameters.num_layers == 0 {
+            if self.parameters.input_length != input_length {
+                panic!("Error: The size of the input volume of the layer should be equal to the dimension of the input layer if no layer exists");
+            }
+        }
+        else if input_length != self.parameters.neurons[self.parameters.num_layers - 1].len() {
+            panic!("Error: The size of the input volume of the layer should be equal to the number of neurons in the previous layer");
+        }
+    }
+
+    // true if the last added layer has no extra weights (convolutional and pooling layers)
+    fn last_layer_without_extra_weights(&self) -> bool {
+        let last = self.parameters.num_layers - 1;
+        self.parameters.convolutions[last].is_some() || self.parameters.poolings[last].is_some()
+    }
+
     /**
         This method receives all the data for building a layer (neurons and intra and extra layer weights)
         and checks its consistency (at run-time)
@@ -115,33 +210,279 @@ impl<N: Neuron + Clone + Send> SNNBuilder<N> {
         // extra weights consistency check
         self.check_extra_weights(&extra_weights, neurons.len());
 
+        // by default the spikes reach the layer without delay
+        let num_neurons = neurons.len();
+        let num_inputs = extra_weights.first().map_or(0, |w| w.len());
+
         // add parameters of the new layer
         let mut parameters = self.parameters;
         parameters.num_layers += 1;
         parameters.neurons.push(neurons);
         parameters.extra_weights.push(extra_weights);
         parameters.intra_weights.push(intra_weights);
+        parameters.delays.push(vec![vec![0; num_inputs]; num_neurons]);
+        parameters.winner_take_all.push(None);
+        parameters.stdp.push(None);
+        parameters.convolutions.push(None);
+        parameters.poolings.push(None);
+        parameters.skip_connections.push(Vec::new());
+
+        Self {
+            parameters
+        }
+    }
+
+    /**
+        This method receives all the data for building a convolutional layer (neurons, convolution and intra layer weights)
+        and checks its consistency (at run-time): the neurons are the output volume of the convolution,
+        whose input volume has the size of the previous layer (or of the input)
+     **/
+    pub fn add_conv_layer(self, neurons: Vec<N>, convolution: Convolution, intra_weights: Vec<Vec<f64>>) -> Self {
+        // intra weights consistency check
+        self.check_intra_weights(&intra_weights, neurons.len());
+        // convolution consistency check
+        self.check_input_volume(convolution.get_output_length(), convolution.get_input_length(), neurons.len());
+
+        // the shared kernels replace the extra weights
+        let num_neurons = neurons.len();
+
+        // add parameters of the new layer
+        let mut parameters = self.parameters;
+        parameters.num_layers += 1;
+        parameters.neurons.push(neurons);
+        parameters.extra_weights.push(vec![Vec::new(); num_neurons]);
+        parameters.intra_weights.push(intra_weights);
+        parameters.delays.push(vec![Vec::new(); num_neurons]);
+        parameters.winner_take_all.push(None);
+        parameters.stdp.push(None);
+        parameters.convolutions.push(Some(convolution));
+        parameters.poolings.push(None);
+        parameters.skip_connections.push(Vec::new());
+
+        Self {
+            parameters
+        }
+    }
+
+    /**
+        This method receives all the data for building a pooling layer (neurons and pooling) and checks its consistency (at run-time):
+        the neurons are the output volume of the pooling, whose input volume has the size of the previous layer (or of the input).
+        The neurons of a pooling layer have no intra layer connections
+     **/
+    pub fn add_pool_layer(self, neurons: Vec<N>, pooling: Pooling) -> Self {
+        // pooling consistency check
+        self.check_input_volume(pooling.get_output_length(), pooling.get_input_length(), neurons.len());
+
+        // the pooled spikes replace the weighted sum of the extra weights
+        let num_neurons = neurons.len();
+
+        // add parameters of the new layer
+        let mut parameters = self.parameters;
+        parameters.num_layers += 1;
+        parameters.neurons.push(neurons);
+        parameters.extra_weights.push(vec![Vec::new(); num_neurons]);
+        parameters.intra_weights.push(vec![vec![0.0; num_neurons]; num_neurons]);
+        parameters.delays.push(vec![Vec::new(); num_neurons]);
+        parameters.winner_take_all.push(None);
+        parameters.stdp.push(None);
+        parameters.convolutions.push(None);
+        parameters.poolings.push(Some(pooling));
+        parameters.skip_connections.push(Vec::new());
+
+        Self {
+            parameters
+        }
+    }
+
+    /**
+        This method sets the delays (in time steps) of the connections between the last added layer
+        and the previous one (or the input): the matrix has the same dimensions as its extra_weights
+        and each delay is at most MAX_DELAY
+     **/
+    pub fn with_delays(self, delays: Vec<Vec<u64>>) -> Self {
+        if self.parameters.num_layers == 0 {
+            panic!("Error: The delays must be set after adding the layer");
+        }
+        if self.last_layer_without_extra_weights() {
+            panic!("Error: The connections of a convolutional or pooling layer can't be delayed");
+        }
+        // delays consistency check
+        self.check_delays(&delays);
+
+        let mut parameters = self.parameters;
+        parameters.delays[parameters.num_layers - 1] = delays;
+
+        Self {
+            parameters
+        }
+    }
+
+    /**
+        This method connects the last added layer with an earlier layer (skip connection), given the index
+        of the source layer (before the previous one) and the weights matrix of the connection,
+        with one row for each neuron of the last layer and one column for each neuron of the source layer
+     **/
+    pub fn with_skip_connection(self, source: usize, weights: Vec<Vec<f64>>) -> Self {
+        if self.parameters.num_layers == 0 {
+            panic!("Error: The skip connections must be set after adding the layer");
+        }
+        let last = self.parameters.num_layers - 1;
+        if source + 1 >= last {
+            panic!("Error: The source of a skip connection should be a layer before the previous one");
+        }
+        if self.parameters.skip_connections[last].iter().any(|s| s.get_source() == source) {
+            panic!("Error: The layer already has a skip connection with the layer {}", source);
+        }
+        // weights consistency check
+        if weights.len() != self.parameters.neurons[last].len() {
+            panic!("Error: The number of neurons should be equal to the number of rows of the weights matrix of the skip connection");
+        }
+        if weights.iter().any(|row| row.len() != self.parameters.neurons[source].len()) {
+            panic!("Error: The number of neurons in the source layer should be equal to the number of columns of the weights matrix of the skip connection");
+        }
+
+        let mut parameters = self.parameters;
+        parameters.skip_connections[last].push(SkipConnection::new(source, weights));
+
+        Self {
+            parameters
+        }
+    }
+
+    /**
+        This method makes the last added layer a k-winner-take-all layer:
+        in each time instant at most k neurons of the layer can spike
+     **/
+    pub fn with_winner_take_all(self, k: usize) -> Self {
+        if self.parameters.num_layers == 0 {
+            panic!("Error: The winner-take-all mode must be set after adding the layer");
+        }
+        if k == 0 || k > self.parameters.neurons[self.parameters.num_layers - 1].len() {
+            panic!("Error: The number of winners should be between 1 and the number of neurons of the layer");
+        }
+
+        let mut parameters = self.parameters;
+        parameters.winner_take_all[parameters.num_layers - 1] = Some(k);
+
+        Self {
+            parameters
+        }
+    }
+
+    /**
+        This method enables the STDP learning of the extra_weights of the last added layer
+        (the weights are updated while the SNN processes the input)
+     **/
+    pub fn with_stdp(self, stdp: StdpParameters) -> Self {
+        if self.parameters.num_layers == 0 {
+            panic!("Error: The STDP rule must be set after adding the layer");
+        }
+        if self.last_layer_without_extra_weights() {
+            panic!("Error: Only the extra weights of a fully connected layer can be learned with the STDP rule");
+        }
+
+        let mut parameters = self.parameters;
+        parameters.stdp[parameters.num_layers - 1] = Some(stdp);
+
+        Self {
+            parameters
+        }
+    }
+
+    /**
+        This method sets the format used to store the weights of all the layers
+        (by default the weights are stored as f64)
+     **/
+    pub fn with_weight_format(self, format: StorageFormat) -> Self {
+        let mut parameters = self.parameters;
+        parameters.weight_format = format;
+
+        Self {
+            parameters
+        }
+    }
+
+    /**
+        This method stores the extra and intra weights of all the layers as sparse (CSR) matrices:
+        only the non-zero weights are kept (after the rounding to the weight format), so that each time step
+        visits only the existing synapses and the faults in the weights are injected only in them.
+        The weights of the layers learning with the STDP rule can't be sparse.
+     **/
+    pub fn with_sparse_weights(self) -> Self {
+        if self.parameters.stdp.iter().any(|stdp| stdp.is_some()) {
+            panic!("Error: The weights of a layer learning with the STDP rule should be stored as dense matrices");
+        }
+
+        let mut parameters = self.parameters;
+        parameters.sparse_weights = true;
+
+        Self {
+            parameters
+        }
+    }
+
+    /**
+        This method prunes the extra and intra weights of all the layers when the SNN is built:
+        the weights selected by the criterion (by magnitude or to reach a target sparsity) are set to 0,
+        before they are rounded to the weight format and stored as sparse matrices (if requested)
+     **/
+    pub fn with_pruning(self, criterion: PruningCriterion) -> Self {
+        let mut parameters = self.parameters;
+        parameters.pruning = Some(criterion);
 
         Self {
             parameters
         }
     }
 
+    /**
+        This method builds the SNN from the information collected by the SNNBuilder
+    */
+    pub fn build(self) -> SNN {
+        SNN::new(self.build_layers())
+    }
+
     /**
         This method builds each layer of the SNN from the information collected
-        by the SNNBuilder (neurons and weights)
+        by the SNNBuilder (neurons and weights).
+        The layers built by builders with different neuron models can be chained in the same SNN
+        (`SNN::new`), starting each builder with the number of neurons of the last layer of the previous one
+        (the skip connections refer to the indices of the layers in the builder, so they are valid only in the first builder)
     */
-    pub fn build(self) -> SNN<N> {
+    pub fn build_layers(self) -> Vec<Box<dyn NetworkLayer>> {
         if self.parameters.num_layers == 0 {
             panic!("Error: The SNN must have at least one layer");
         }
 
         // Creation of each layer
         let mut layers = Vec::new();
-        for (weights, neurons) in self.parameters.extra_weights.into_iter().zip(self.parameters.intra_weights).zip(self.parameters.neurons) {
-            let layer = Layer::new(neurons, weights.0, weights.1);
-            layers.push(Arc::new(Mutex::new(layer)));
+        for (((((((weights, neurons), delays), winner_take_all), stdp), convolution), pooling), skip_connections) in self.parameters.extra_weights.into_iter().zip(self.parameters.intra_weights).zip(self.parameters.neurons).zip(self.parameters.delays).zip(self.parameters.winner_take_all).zip(self.parameters.stdp).zip(self.parameters.convolutions).zip(self.parameters.poolings).zip(self.parameters.skip_connections) {
+            let mut layer = Layer::new(neurons, weights.0, weights.1);
+            layer.set_delays(delays);
+            if let Some(convolution) = convolution {
+                layer.set_convolution(convolution);
+            }
+            if let Some(pooling) = pooling {
+                layer.set_pooling(pooling);
+            }
+            for skip_connection in skip_connections {
+                layer.add_skip_connection(skip_connection);
+            }
+            if let Some(k) = winner_take_all {
+                layer.set_winner_take_all(k);
+            }
+            if let Some(stdp) = stdp {
+                layer.set_stdp(stdp);
+            }
+            if let Some(criterion) = self.parameters.pruning {
+                layer.prune_weights(criterion);
+            }
+            layer.set_weight_format(self.parameters.weight_format);
+            if self.parameters.sparse_weights {
+                layer.set_sparse_weights();
+            }
+            layers.push(Box::new(layer) as Box<dyn NetworkLayer>);
         }
-        SNN::new(layers)
+        layers
     }
 }
\ No newline at end of file
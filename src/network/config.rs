@@ -4,7 +4,17 @@ extern crate toml;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Read};
 use crate::network::builder::SNNBuilder;
-use crate::network::neuron::lif::Lif;
+use crate::network::neuron::lif::{Lif, ResetMode};
+use crate::network::neuron::izhikevich::Izhikevich;
+use crate::network::neuron::adex::{AdEx, AdExParameters};
+use crate::network::neuron::cuba_lif::CubaLif;
+use crate::network::neuron::fixed_lif::FixedLif;
+use crate::network::neuron::stochastic_lif::StochasticLif;
+use crate::network::neuron::adaptive_lif::AdaptiveLif;
+use crate::network::storage_format::StorageFormat;
+use crate::network::stdp::StdpParameters;
+use crate::network::neuron::neuron_model::NeuronModel;
+use crate::network::decoder::Decision;
 use std::io::Write;
 
 // NetworkSetup and Parsing from Config File
@@ -20,25 +30,74 @@ pub struct NetworkSetup {
     pub input_layer: usize,
     pub hidden_layers: Vec<usize>,
     pub output_length: usize,
+    pub winner_take_all: Option<usize>,
     pub extra_weights: Vec<String>,
     pub intra_weights: Vec<String>,
+    pub delays: Vec<Option<LayerParameter>>,
     pub resting_potential: f64,
     pub reset_potential: f64,
     pub threshold: f64,
     pub beta: f64,
     pub tau: f64,
     pub dt: f64,
+    pub refractory_period: u64,
+    pub reset_mode: ResetMode,
     pub spike_length: usize,
     pub batch_size: usize,
     pub input_spike_train: String,
-    pub target_file: String
+    pub target_file: String,
+    pub neuron_model: String,
+    pub izhikevich_parameters: Option<IzhikevichParameters>,
+    pub adex_parameters: Option<AdExParameters>,
+    pub synaptic_tau: Option<f64>,
+    pub stochastic_parameters: Option<StochasticParameters>,
+    pub stdp_parameters: Option<StdpParameters>,
+    pub lif_layer_parameters: Vec<LifLayerParameters>,
+    pub storage_format: StorageFormat
 }
 
-impl NetworkSetup {
-    fn new(input_layer: usize, hidden_layers: Vec<usize>, output_length: usize, extra_weights: Vec<String>, intra_weights: Vec<String>, resting_potential: f64, reset_potential: f64, threshold: f64, beta: f64, tau: f64, dt: f64, spike_length: usize, batch_size: usize, input_spike_train: String, target_file: String) -> Self{
-        NetworkSetup {input_layer, hidden_layers, output_length, extra_weights, intra_weights, resting_potential, reset_potential, threshold, beta, tau, dt, spike_length, batch_size, input_spike_train, target_file}
-    }
+// Value of a LIF parameter for a layer, read from the optional [LIF_layer_parameters] section:
+// the same value for all the neurons of the layer or a file with one value for each neuron
+#[derive(Debug, Clone)]
+pub enum LayerParameter {
+    Scalar(f64),
+    File(String)
+}
 
+// LIF parameters of a layer (None => the value in [LIF_neuron_parameters] is used)
+// If threshold_increment is given (and not 0), the layer is made of adaptive threshold LIF neurons
+#[derive(Debug, Clone, Default)]
+pub struct LifLayerParameters {
+    pub reset_potential: Option<LayerParameter>,
+    pub resting_potential: Option<LayerParameter>,
+    pub threshold: Option<LayerParameter>,
+    pub beta: Option<LayerParameter>,
+    pub tau: Option<LayerParameter>,
+    pub dt: Option<LayerParameter>,
+    pub threshold_increment: Option<LayerParameter>,
+    pub threshold_tau: Option<LayerParameter>
+}
+
+// Parameters of the Izhikevich neurons, read from the optional [Izhikevich_neuron_parameters] section
+#[derive(Debug, Clone, Copy)]
+pub struct IzhikevichParameters {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub threshold: f64,
+    pub dt: f64
+}
+
+// Parameters of the stochastic LIF neurons, read from the optional [StochasticLIF_neuron_parameters] section
+// (the neuron i of the network uses the seed seed + i, so that each neuron has its own random sequence)
+#[derive(Debug, Clone, Copy)]
+pub struct StochasticParameters {
+    pub temperature: f64,
+    pub seed: u64
+}
+
+impl NetworkSetup {
     /**
      * This function updates the config file with the new parameters specified by the user in the GUI
      */
@@ -122,6 +181,14 @@ pub fn network_setup_from_file() -> Result<NetworkSetup, &'static str> {
         .map(|n| n.as_integer().unwrap() as usize)
         .collect::<Vec<usize>>();
     let output_length = config["output_layer"]["neurons"].as_integer().unwrap() as usize;
+    // optional k-winner-take-all output layer (at most k output neurons spike in each time instant)
+    let winner_take_all = match config["output_layer"].get("winner_take_all") {
+        Some(k) => match k.as_integer() {
+            Some(k) if k >= 1 && k as usize <= output_length => Some(k as usize),
+            _ => return Err("Error: the number of winners of the output layer must be between 1 and the number of output neurons")
+        },
+        None => None
+    };
 
     // WEIGHT FILES -> check if length = hidden_layers_length
     let weight_files = config["weight_files"].as_table().unwrap();
@@ -143,6 +210,9 @@ pub fn network_setup_from_file() -> Result<NetworkSetup, &'static str> {
     }else{
         intra_weights = Vec::new();
     }
+    // optional delays of the connections with the previous layer (one value for each hidden and output layer):
+    // the same delay for all the connections of the layer or a file with one delay for each connection
+    let delays = get_layer_parameters(weight_files, "delays", hidden_layers_length.len() + 1)?;
 
     // NEURON PARAMETERS
     let lif_params = config["LIF_neuron_parameters"].as_table().unwrap();
@@ -160,6 +230,161 @@ pub fn network_setup_from_file() -> Result<NetworkSetup, &'static str> {
         tau = lif_params["tau"].as_float().unwrap() as f64;
         beta = (-dt / tau).exp() as f64;
     }
+    // optional absolute refractory period (in time steps)
+    let refractory_period = match lif_params.get("refractory_period") {
+        Some(r) => r.as_integer().unwrap() as u64,
+        None => 0
+    };
+    // optional reset mechanism ("value" by default)
+    let reset_mode = match lif_params.get("reset_mode").map(|r| r.as_str().unwrap()) {
+        Some("value") | None => ResetMode::ToValue,
+        Some("subtract") => ResetMode::Subtract,
+        Some("none") => ResetMode::None,
+        Some(_) => return Err("Error: unknown reset mode")
+    };
+    // optional per-layer LIF parameters (one value for each hidden and output layer)
+    let mut lif_layer_parameters = Vec::new();
+    if let Some(layer_params) = config.get("LIF_layer_parameters").and_then(|p| p.as_table()) {
+        let num_layers = hidden_layers_length.len() + 1;
+        let reset_potential = get_layer_parameters(layer_params, "reset_potential", num_layers)?;
+        let resting_potential = get_layer_parameters(layer_params, "resting_potential", num_layers)?;
+        let threshold = get_layer_parameters(layer_params, "threshold", num_layers)?;
+        let beta = get_layer_parameters(layer_params, "beta", num_layers)?;
+        let tau = get_layer_parameters(layer_params, "tau", num_layers)?;
+        let dt = get_layer_parameters(layer_params, "dt", num_layers)?;
+        // adaptive thresholds (homeostasis): the increment requires the time constant of the decay
+        let threshold_increment = get_layer_parameters(layer_params, "threshold_increment", num_layers)?;
+        let threshold_tau = get_layer_parameters(layer_params, "threshold_tau", num_layers)?;
+        if layer_params.contains_key("threshold_increment") && !layer_params.contains_key("threshold_tau") {
+            return Err("Error: the adaptive thresholds require threshold_tau in the [LIF_layer_parameters] section");
+        }
+        for i in 0..num_layers {
+            lif_layer_parameters.push(LifLayerParameters {
+                reset_potential: reset_potential[i].clone(),
+                resting_potential: resting_potential[i].clone(),
+                threshold: threshold[i].clone(),
+                beta: beta[i].clone(),
+                tau: tau[i].clone(),
+                dt: dt[i].clone(),
+                threshold_increment: threshold_increment[i].clone(),
+                threshold_tau: threshold_tau[i].clone()
+            });
+        }
+    }
+
+    // STORAGE FORMAT (optional, the weights and the neurons are stored as f64 by default)
+    let storage_format = match config.get("storage_format") {
+        Some(f) => StorageFormat::from_name(f["format"].as_str().unwrap())?,
+        None => StorageFormat::F64
+    };
+
+    // NEURON MODEL (optional, LIF by default)
+    let neuron_model = match config.get("neuron_model") {
+        Some(m) => m["model"].as_str().unwrap().to_string(),
+        None => String::from("LIF")
+    };
+    let mut izhikevich_parameters = None;
+    let mut adex_parameters = None;
+    let mut synaptic_tau = None;
+    let mut stochastic_parameters = None;
+    match neuron_model.as_str() {
+        "LIF" => {},
+        "Izhikevich" => {
+            let izh_params = match config.get("Izhikevich_neuron_parameters").and_then(|p| p.as_table()) {
+                Some(p) => p,
+                None => return Err("Error: the Izhikevich model requires the [Izhikevich_neuron_parameters] section")
+            };
+            izhikevich_parameters = Some(IzhikevichParameters {
+                a: izh_params["a"].as_float().unwrap(),
+                b: izh_params["b"].as_float().unwrap(),
+                c: izh_params["c"].as_float().unwrap(),
+                d: izh_params["d"].as_float().unwrap(),
+                threshold: izh_params["threshold"].as_float().unwrap(),
+                dt: izh_params["dt"].as_float().unwrap()
+            });
+        },
+        "AdEx" => {
+            let adex_params = match config.get("AdEx_neuron_parameters").and_then(|p| p.as_table()) {
+                Some(p) => p,
+                None => return Err("Error: the AdEx model requires the [AdEx_neuron_parameters] section")
+            };
+            adex_parameters = Some(AdExParameters {
+                reset_potential: adex_params["reset_potential"].as_float().unwrap(),
+                resting_potential: adex_params["resting_potential"].as_float().unwrap(),
+                threshold: adex_params["threshold"].as_float().unwrap(),
+                peak_potential: adex_params["peak_potential"].as_float().unwrap(),
+                slope_factor: adex_params["slope_factor"].as_float().unwrap(),
+                tau: adex_params["tau"].as_float().unwrap(),
+                adaptation_coupling: adex_params["adaptation_coupling"].as_float().unwrap(),
+                adaptation_increment: adex_params["adaptation_increment"].as_float().unwrap(),
+                tau_adaptation: adex_params["tau_adaptation"].as_float().unwrap(),
+                dt: adex_params["dt"].as_float().unwrap()
+            });
+        },
+        "CubaLIF" => {
+            // the membrane uses the LIF parameters, the synaptic current decays with alpha (or synaptic_tau)
+            let cuba_params = match config.get("CubaLIF_neuron_parameters").and_then(|p| p.as_table()) {
+                Some(p) => p,
+                None => return Err("Error: the CubaLIF model requires the [CubaLIF_neuron_parameters] section")
+            };
+            synaptic_tau = match cuba_params.get("alpha") {
+                Some(alpha) => Some(-dt / alpha.as_float().unwrap().ln()),
+                None => Some(cuba_params["synaptic_tau"].as_float().unwrap())
+            };
+        },
+        "FixedLIF" => {
+            // the LIF parameters are quantized in the fixed-point format of the [storage_format] section
+            if !matches!(storage_format, StorageFormat::Fixed(_)) {
+                return Err("Error: the FixedLIF model requires a fixed-point (Qm.n) storage format");
+            }
+        },
+        "StochasticLIF" => {
+            // the membrane uses the LIF parameters, the firing is driven by the temperature
+            let stochastic_params = match config.get("StochasticLIF_neuron_parameters").and_then(|p| p.as_table()) {
+                Some(p) => p,
+                None => return Err("Error: the StochasticLIF model requires the [StochasticLIF_neuron_parameters] section")
+            };
+            let temperature = stochastic_params["temperature"].as_float().unwrap();
+            if temperature <= 0.0 {
+                return Err("Error: the temperature of the StochasticLIF model must be positive");
+            }
+            stochastic_parameters = Some(StochasticParameters {
+                temperature,
+                seed: stochastic_params["seed"].as_integer().unwrap() as u64
+            });
+        },
+        _ => return Err("Error: unknown neuron model")
+    }
+
+    // the memory areas of the Izhikevich, AdEx and stochastic LIF neurons are stored only as f64
+    if !storage_format_supported(neuron_model.as_str(), storage_format) {
+        return Err("Error: the Izhikevich, AdEx and StochasticLIF models support only the f64 storage format");
+    }
+
+    // the adaptive thresholds are available only for the LIF model
+    if neuron_model != "LIF" && lif_layer_parameters.iter().any(|p| p.threshold_increment.is_some()) {
+        return Err("Error: the adaptive thresholds (threshold_increment) are available only for the LIF model");
+    }
+
+    // the fixed-point and stochastic LIF neurons have no refractory period and are always reset to the reset potential
+    if (neuron_model == "FixedLIF" || neuron_model == "StochasticLIF") && (refractory_period != 0 || reset_mode != ResetMode::ToValue) {
+        return Err("Error: the FixedLIF and StochasticLIF models don't support the refractory period and the reset modes other than value");
+    }
+
+    // STDP LEARNING (optional, the extra weights of all the layers are learned during the processing)
+    let stdp_parameters = match config.get("STDP_parameters").and_then(|p| p.as_table()) {
+        Some(stdp_params) => {
+            let tau_plus = stdp_params["tau_plus"].as_float().unwrap();
+            let tau_minus = stdp_params["tau_minus"].as_float().unwrap();
+            let w_min = stdp_params["w_min"].as_float().unwrap();
+            let w_max = stdp_params["w_max"].as_float().unwrap();
+            if tau_plus <= 0.0 || tau_minus <= 0.0 || w_min > w_max {
+                return Err("Error: the STDP time constants must be positive and w_min must not be greater than w_max");
+            }
+            Some(StdpParameters::new(stdp_params["a_plus"].as_float().unwrap(), stdp_params["a_minus"].as_float().unwrap(), tau_plus, tau_minus, w_min, w_max))
+        },
+        None => None
+    };
 
     // INPUT SPIKES PARAMETERS
     let spike_length = config["input_spike_train"]["spike_length"].as_integer().unwrap() as usize;
@@ -169,15 +394,74 @@ pub fn network_setup_from_file() -> Result<NetworkSetup, &'static str> {
     // TARGET FILE FOR ACCURACY
     let target_file = config["accuracy"]["target_file"].to_string();
 
-    Ok(NetworkSetup::new(input_length, hidden_layers_length.clone(), output_length.clone(), extra_weights.clone(), intra_weights.clone(), resting_potential, reset_potential, threshold, beta, tau, dt, spike_length.clone(), batch_size, input_spike_train.clone(), target_file.clone()))
+    Ok(NetworkSetup {
+        input_layer: input_length,
+        hidden_layers: hidden_layers_length,
+        output_length,
+        winner_take_all,
+        extra_weights,
+        intra_weights,
+        delays,
+        resting_potential,
+        reset_potential,
+        threshold,
+        beta,
+        tau,
+        dt,
+        refractory_period,
+        reset_mode,
+        spike_length,
+        batch_size,
+        input_spike_train,
+        target_file,
+        neuron_model,
+        izhikevich_parameters,
+        adex_parameters,
+        synaptic_tau,
+        stochastic_parameters,
+        stdp_parameters,
+        lif_layer_parameters,
+        storage_format
+    })
     // Now you can use the extracted parameters to build your SNN and perform operations as needed.
 
 }
 
+/**
+    This function reads a per-layer parameter (of the [LIF_layer_parameters] section or the delays),
+    that must have one value (number or file path) for each layer
+**/
+fn get_layer_parameters(layer_params: &toml::value::Table, name: &str, num_layers: usize) -> Result<Vec<Option<LayerParameter>>, &'static str> {
+    let values = match layer_params.get(name) {
+        Some(v) => match v.as_array() {
+            Some(values) => values,
+            None => return Err("Error: the per-layer parameters must be arrays with one value for each layer")
+        },
+        None => return Ok(vec![None; num_layers])
+    };
+    if values.len() != num_layers {
+        return Err("Error: the per-layer parameters must have one value for each layer");
+    }
+    let mut layer_parameters = Vec::new();
+    for v in values.iter() {
+        match v {
+            toml::Value::Float(f) => layer_parameters.push(Some(LayerParameter::Scalar(*f))),
+            toml::Value::Integer(i) => layer_parameters.push(Some(LayerParameter::Scalar(*i as f64))),
+            toml::Value::String(s) => layer_parameters.push(Some(LayerParameter::File(s.clone()))),
+            _ => return Err("Error: a per-layer parameter must be a number or a file path")
+        }
+    }
+    Ok(layer_parameters)
+}
+
 /**
     This function builds the SNN, the input spike trains and the targets starting from the struct NetworkSetup
 **/
-pub fn build_network_from_setup(n: NetworkSetup) -> (SNN<Lif>, Vec<Vec<Vec<u8>>>, Vec<u8>) {
+pub fn build_network_from_setup(n: NetworkSetup) -> (SNN, Vec<Vec<Vec<u8>>>, Vec<u8>) {
+
+    if !storage_format_supported(n.neuron_model.as_str(), n.storage_format) {
+        panic!("Error: the Izhikevich, AdEx and StochasticLIF models support only the f64 storage format");
+    }
 
     // Collect in one vector all the info about layers' dimensions
     let mut layers_dim = Vec::new();
@@ -188,8 +472,15 @@ pub fn build_network_from_setup(n: NetworkSetup) -> (SNN<Lif>, Vec<Vec<Vec<u8>>>
 
     // Building neurons
     let mut vec_neurons = Vec::new();
-    for l in layers_dim.iter() {
-        vec_neurons.push(get_neurons(*l, n.reset_potential, n.resting_potential, n.threshold, n.tau, n.dt));
+    for (i, l) in layers_dim.iter().enumerate() {
+        match n.neuron_model.as_str() {
+            "Izhikevich" => vec_neurons.push(get_izhikevich_neurons(*l, n.izhikevich_parameters.unwrap())),
+            "AdEx" => vec_neurons.push(get_adex_neurons(*l, n.adex_parameters.unwrap())),
+            "CubaLIF" => vec_neurons.push(get_cuba_neurons(*l, &n, i)),
+            "FixedLIF" => vec_neurons.push(get_fixed_neurons(*l, &n, i)),
+            "StochasticLIF" => vec_neurons.push(get_stochastic_neurons(*l, &n, i)),
+            _ => vec_neurons.push(get_neurons(*l, &n, i)),
+        }
     }
 
     // Getting extra_weights from files
@@ -216,8 +507,21 @@ pub fn build_network_from_setup(n: NetworkSetup) -> (SNN<Lif>, Vec<Vec<Vec<u8>>>
 
     //Building the SNN
     let mut snn_builder = SNNBuilder::new(n.input_layer);
-    for (w, n) in vec_extra_weights.iter().zip(vec_intra_weights.iter()).zip(vec_neurons.iter()) {
-        snn_builder = snn_builder.add_layer(n.to_vec(), w.0.to_vec(), w.1.to_vec());
+    if n.storage_format != StorageFormat::F64 {
+        snn_builder = snn_builder.with_weight_format(n.storage_format);
+    }
+    for (i, (w, neurons)) in vec_extra_weights.iter().zip(vec_intra_weights.iter()).zip(vec_neurons.iter()).enumerate() {
+        snn_builder = snn_builder.add_layer(neurons.to_vec(), w.0.to_vec(), w.1.to_vec());
+        // Getting the delays of the layer (if any)
+        if let Some(Some(delays)) = n.delays.get(i) {
+            snn_builder = snn_builder.with_delays(get_delays(delays, w.0[0].len(), neurons.len()));
+        }
+        if let Some(stdp) = n.stdp_parameters {
+            snn_builder = snn_builder.with_stdp(stdp);
+        }
+    }
+    if let Some(k) = n.winner_take_all {
+        snn_builder = snn_builder.with_winner_take_all(k);
     }
     let snn = snn_builder.build();
 
@@ -230,11 +534,116 @@ pub fn build_network_from_setup(n: NetworkSetup) -> (SNN<Lif>, Vec<Vec<Vec<u8>>>
     (snn, input_spike_train, targets)
 }
 
-fn get_neurons(num_neurons: usize, reset_potential: f64, resting_potential: f64, threshold: f64, tau: f64, dt: f64) -> Vec<Lif> {
-    // Building the vector of Lif with the parameters received as arguments
+// The LIF, adaptive LIF, CuBa LIF and fixed-point LIF neurons can store their memory areas in any format
+fn storage_format_supported(neuron_model: &str, storage_format: StorageFormat) -> bool {
+    storage_format == StorageFormat::F64 || !matches!(neuron_model, "Izhikevich" | "AdEx" | "StochasticLIF")
+}
+
+fn get_neurons(num_neurons: usize, n: &NetworkSetup, layer_index: usize) -> Vec<NeuronModel> {
+    // Building the vector of Lif with the LIF parameters of the layer
+    // => the neurons with a threshold increment (not 0) are adaptive threshold LIF neurons
+    let p = n.lif_layer_parameters.get(layer_index).cloned().unwrap_or_default();
+    let threshold_increments = get_neuron_parameters(p.threshold_increment, 0.0, num_neurons);
+    let threshold_taus = get_neuron_parameters(p.threshold_tau, f64::INFINITY, num_neurons);
+    get_lifs(num_neurons, n, layer_index).into_iter().enumerate().map(|(i, mut lif)| {
+        if threshold_increments[i] != 0.0 {
+            let mut adaptive = AdaptiveLif::new(lif, threshold_increments[i], threshold_taus[i]);
+            adaptive.set_storage_format(n.storage_format);
+            NeuronModel::from(adaptive)
+        }
+        else {
+            lif.set_storage_format(n.storage_format);
+            NeuronModel::from(lif)
+        }
+    }).collect()
+}
+
+fn get_cuba_neurons(num_neurons: usize, n: &NetworkSetup, layer_index: usize) -> Vec<NeuronModel> {
+    // Building the vector of CuBa LIF neurons, whose membrane uses the LIF parameters of the layer
+    get_lifs(num_neurons, n, layer_index).into_iter().map(|lif| {
+        let mut cuba = CubaLif::new(lif, n.synaptic_tau.unwrap());
+        cuba.set_storage_format(n.storage_format);
+        NeuronModel::from(cuba)
+    }).collect()
+}
+
+fn get_fixed_neurons(num_neurons: usize, n: &NetworkSetup, layer_index: usize) -> Vec<NeuronModel> {
+    // Building the vector of fixed-point LIF neurons, quantizing the LIF parameters of the layer
+    let format = match n.storage_format {
+        StorageFormat::Fixed(q) => q,
+        _ => panic!("Error: the FixedLIF model requires a fixed-point (Qm.n) storage format")
+    };
+    get_lifs(num_neurons, n, layer_index).iter().map(|lif| NeuronModel::from(FixedLif::from_lif(lif, format))).collect()
+}
+
+fn get_stochastic_neurons(num_neurons: usize, n: &NetworkSetup, layer_index: usize) -> Vec<NeuronModel> {
+    // Building the vector of stochastic LIF neurons, whose membrane uses the LIF parameters of the layer
+    let p = n.stochastic_parameters.unwrap();
+    // index of the first neuron of the layer in the network
+    let first_neuron: usize = n.hidden_layers.iter().take(layer_index).sum();
+    get_lifs(num_neurons, n, layer_index).iter().enumerate().map(|(i, lif)| {
+        NeuronModel::from(StochasticLif::from_lif(lif, p.temperature, p.seed + (first_neuron + i) as u64))
+    }).collect()
+}
+
+fn get_lifs(num_neurons: usize, n: &NetworkSetup, layer_index: usize) -> Vec<Lif> {
+    // Values of the parameters for each neuron of the layer
+    // => the values in [LIF_layer_parameters] override the ones in [LIF_neuron_parameters]
+    let p = n.lif_layer_parameters.get(layer_index).cloned().unwrap_or_default();
+    let reset_potentials = get_neuron_parameters(p.reset_potential, n.reset_potential, num_neurons);
+    let resting_potentials = get_neuron_parameters(p.resting_potential, n.resting_potential, num_neurons);
+    let thresholds = get_neuron_parameters(p.threshold, n.threshold, num_neurons);
+    let dts = get_neuron_parameters(p.dt, n.dt, num_neurons);
+    // if beta is specified, tau = (-dt / beta.ln())
+    let taus = match p.beta {
+        Some(beta) => get_neuron_parameters(Some(beta), n.beta, num_neurons).iter().zip(dts.iter()).map(|(beta, dt)| -dt / beta.ln()).collect(),
+        None => get_neuron_parameters(p.tau, n.tau, num_neurons)
+    };
+
+    let mut neurons = Vec::new();
+    for i in 0..num_neurons {
+        let mut lif = Lif::new(reset_potentials[i], resting_potentials[i], thresholds[i], taus[i], dts[i]);
+        lif.set_refractory_period(n.refractory_period);
+        lif.set_reset_mode(n.reset_mode);
+        neurons.push(lif);
+    }
+    neurons
+}
+
+fn get_neuron_parameters(parameter: Option<LayerParameter>, default: f64, num_neurons: usize) -> Vec<f64> {
+    match parameter {
+        Some(LayerParameter::Scalar(value)) => vec![value; num_neurons],
+        Some(LayerParameter::File(filename)) => {
+            // Reading one value for each neuron of the layer (separated by spaces or new lines)
+            let mut f = File::open(&filename).expect("Error: The LIF parameter file doesn't exist");
+            let mut content = String::new();
+            f.read_to_string(&mut content).expect("Error: Failed to read the LIF parameter file");
+            let values = content.split_whitespace()
+                .map(|v| v.parse::<f64>().expect("Cannot convert to f64"))
+                .collect::<Vec<f64>>();
+            if values.len() != num_neurons {
+                panic!("Error: The LIF parameter file {} contains {} values, but the layer has {} neurons", filename, values.len(), num_neurons);
+            }
+            values
+        },
+        None => vec![default; num_neurons]
+    }
+}
+
+fn get_izhikevich_neurons(num_neurons: usize, p: IzhikevichParameters) -> Vec<NeuronModel> {
+    // Building the vector of Izhikevich neurons with the parameters read from the config file
+    let mut neurons = Vec::new();
+    for _ in 0..num_neurons {
+        neurons.push(NeuronModel::from(Izhikevich::new(p.a, p.b, p.c, p.d, p.threshold, p.dt)));
+    }
+    neurons
+}
+
+fn get_adex_neurons(num_neurons: usize, p: AdExParameters) -> Vec<NeuronModel> {
+    // Building the vector of AdEx neurons with the parameters read from the config file
     let mut neurons = Vec::new();
     for _ in 0..num_neurons {
-        neurons.push(Lif::new(reset_potential, resting_potential, threshold, tau, dt));
+        neurons.push(NeuronModel::from(AdEx::new(p)));
     }
     neurons
 }
@@ -259,6 +668,25 @@ fn get_extra_weights(filename: &str, input_length: usize, num_neurons: usize) ->
     extra_weights
 }
 
+fn get_delays(parameter: &LayerParameter, input_length: usize, num_neurons: usize) -> Vec<Vec<u64>> {
+    let to_delay = |d: f64| {
+        if d < 0.0 || d.fract() != 0.0 {
+            panic!("Error: The delay of a connection must be a non-negative integer number of time steps");
+        }
+        d as u64
+    };
+    match parameter {
+        LayerParameter::Scalar(d) => vec![vec![to_delay(*d); input_length]; num_neurons],
+        LayerParameter::File(filename) => {
+            // The delays file has the same layout as the extra weight file (one row for each neuron)
+            get_extra_weights(filename, input_length, num_neurons)
+                .iter()
+                .map(|row| row.iter().map(|d| to_delay(*d)).collect())
+                .collect()
+        }
+    }
+}
+
 fn get_intra_weights(num_neurons: usize, filename: &str) -> Vec<Vec<f64>> {
     let mut intra_weights = vec![vec![0f64; num_neurons]; num_neurons];
     if filename.eq("") { // The intra weights are not stored in a file but are all set to the value 0.0
@@ -278,13 +706,10 @@ fn get_intra_weights(num_neurons: usize, filename: &str) -> Vec<Vec<f64>> {
         let reader = BufReader::new(f);
         for (i,line) in reader.lines().enumerate() {
             // Each line is a String -> I have to split it and convert to f64
-            let mut j = 0;
-            for w in line.unwrap().split(" ") {
-                if w != "" {
-                    if i != j {
-                        intra_weights[i][j] = w.parse::<f64>().expect("Cannot convert to f64");
-                        j+=1;
-                    }
+            // => each line is a row of the matrix: the weight of the reflexive link (j == i) is ignored
+            for (j, w) in line.unwrap().split(" ").filter(|w| *w != "").enumerate() {
+                if i != j {
+                    intra_weights[i][j] = w.parse::<f64>().expect("Cannot convert to f64");
                 }
             }
         }
@@ -349,6 +774,18 @@ pub fn compute_accuracy(vec_max: Vec<u8>, targets: &Vec<u8>) -> f64 {
     ((matching * 100) / targets.len()) as f64
 }
 
+/**
+    Compare the decisions of the network with the targets: an input without a decision is never correct
+ **/
+pub fn compute_decision_accuracy(decisions: Vec<Decision>, targets: &Vec<u8>) -> f64 {
+    let matching = decisions.iter().zip(targets).filter(|&(d, t)| *d == Decision::Class(*t)).count();
+    ((matching * 100) / targets.len()) as f64
+}
+
+/**
+    Output neuron with the highest number of spikes (class 0 if all the counts are zero or tied:
+    `Decoder::SpikeCount` reports these outputs as no decision)
+ **/
 pub fn compute_max_output_spike(output_spikes: Vec<Vec<u8>>) -> u8 {
     let mut vec_sum = Vec::new();
     for o in output_spikes {
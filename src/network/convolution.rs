@@ -0,0 +1,146 @@
+use crate::network::layer::accumulate;
+use crate::network::storage_format::StorageFormat;
+use crate::resilience::components::ComponentType;
+use crate::resilience::fault_models::InjectedFault;
+
+// Convolutional connections of a layer with the previous one
+// ------------------------------------------------------------
+// The neurons of the previous layer (or the input) are arranged as a (channels, height, width) volume and
+// each neuron of the convolutional layer is connected to a kernel_size x kernel_size window of every input channel.
+// The kernels are shared by all the output positions of the same output channel, so that:
+// - the layer stores out_channels x in_channels x kernel_size x kernel_size weights instead of a dense matrix
+// - a fault in a kernel weight spreads across all the output positions of its output channel
+// Both the input and the output volumes are flattened in channel-major order (channel, row, column),
+// as the tensors of PyTorch/snnTorch.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Convolution {
+    in_channels: usize,     // number of channels of the input volume
+    in_height: usize,       // height of the input volume
+    in_width: usize,        // width of the input volume
+    out_channels: usize,    // number of kernels (channels of the output volume)
+    kernel_size: usize,     // side of the square kernels
+    stride: usize,          // step between two consecutive positions of the kernels
+    padding: usize,         // zeros added around the input volume
+    pub kernel_weights: Vec<f64>, // shared weights, flattened as [out_channel][in_channel][row][column]
+}
+
+impl Convolution {
+    /**
+        It creates the convolutional connections given the shape of the input volume (channels, height, width)
+        and the kernels, indexed as kernels[out_channel][in_channel][row][column]
+     */
+    pub fn new(input_shape: (usize, usize, usize), kernels: Vec<Vec<Vec<Vec<f64>>>>, stride: usize, padding: usize) -> Self {
+        let (in_channels, in_height, in_width) = input_shape;
+        let out_channels = kernels.len();
+        let kernel_size = kernels.first().and_then(|k| k.first()).map_or(0, |k| k.len());
+
+        if out_channels == 0 || kernel_size == 0 {
+            panic!("Error: The convolutional layer should have at least one non-empty kernel");
+        }
+        if stride == 0 {
+            panic!("Error: The stride of the convolutional layer should be at least 1");
+        }
+        for kernel in kernels.iter() {
+            if kernel.len() != in_channels || kernel.iter().any(|k| k.len() != kernel_size || k.iter().any(|row| row.len() != kernel_size)) {
+                panic!("Error: Each kernel should have one kernel_size x kernel_size matrix for each input channel");
+            }
+        }
+        if kernel_size > in_height + 2 * padding || kernel_size > in_width + 2 * padding {
+            panic!("Error: The kernels should not be larger than the padded input");
+        }
+
+        Convolution {
+            in_channels,
+            in_height,
+            in_width,
+            out_channels,
+            kernel_size,
+            stride,
+            padding,
+            kernel_weights: kernels.into_iter().flatten().flatten().flatten().collect(),
+        }
+    }
+
+    // Getters
+    pub fn get_input_shape(&self) -> (usize, usize, usize) {
+        (self.in_channels, self.in_height, self.in_width)
+    }
+
+    pub fn get_output_shape(&self) -> (usize, usize, usize) {
+        let out_height = (self.in_height + 2 * self.padding - self.kernel_size) / self.stride + 1;
+        let out_width = (self.in_width + 2 * self.padding - self.kernel_size) / self.stride + 1;
+        (self.out_channels, out_height, out_width)
+    }
+
+    pub fn get_input_length(&self) -> usize {
+        self.in_channels * self.in_height * self.in_width
+    }
+
+    pub fn get_output_length(&self) -> usize {
+        let (channels, height, width) = self.get_output_shape();
+        channels * height * width
+    }
+
+    pub fn get_kernel_size(&self) -> usize {
+        self.kernel_size
+    }
+
+    pub fn get_stride(&self) -> usize {
+        self.stride
+    }
+
+    pub fn get_padding(&self) -> usize {
+        self.padding
+    }
+
+    pub fn get_kernel_weights(&self) -> &Vec<f64> {
+        &self.kernel_weights
+    }
+
+    // Weight of the kernel of the output channel co, for the input channel ci, at the given row and column
+    pub fn get_kernel_weight(&self, co: usize, ci: usize, row: usize, column: usize) -> f64 {
+        self.kernel_weights[self.get_kernel_weight_index(co, ci, row, column)]
+    }
+
+    fn get_kernel_weight_index(&self, co: usize, ci: usize, row: usize, column: usize) -> usize {
+        ((co * self.in_channels + ci) * self.kernel_size + row) * self.kernel_size + column
+    }
+
+    /**
+        It computes the weighted sum of the input spikes received by the neuron at the given index of the output volume,
+        summing the weights as the dense layers do (in the weight format of the layer).
+        If the fault targets one of the kernel weights, the fault is applied each time the weight is read.
+     */
+    pub fn weighted_sum(&self, neuron_index: usize, input_spikes: &[u8], weight_format: StorageFormat, fault: Option<InjectedFault>, timestamp: u64) -> f64 {
+        let (_, out_height, out_width) = self.get_output_shape();
+        let co = neuron_index / (out_height * out_width);
+        let out_row = (neuron_index / out_width) % out_height;
+        let out_column = neuron_index % out_width;
+
+        let mut sum = 0.0;
+        for ci in 0..self.in_channels {
+            for row in 0..self.kernel_size {
+                // position in the input volume (the padding is made of zeros => no contribution)
+                let in_row = (out_row * self.stride + row) as isize - self.padding as isize;
+                if in_row < 0 || in_row as usize >= self.in_height {
+                    continue;
+                }
+                for column in 0..self.kernel_size {
+                    let in_column = (out_column * self.stride + column) as isize - self.padding as isize;
+                    if in_column < 0 || in_column as usize >= self.in_width {
+                        continue;
+                    }
+                    let spike = input_spikes[(ci * self.in_height + in_row as usize) * self.in_width + in_column as usize];
+                    let index = self.get_kernel_weight_index(co, ci, row, column);
+                    let weight = match fault {
+                        Some(f) if f.component_type == ComponentType::KernelWeight && f.component_index == index => f.apply_fault_in_format(self.kernel_weights[index], weight_format, timestamp),
+                        _ => self.kernel_weights[index],
+                    };
+                    sum = accumulate(sum, weight, spike, weight_format);
+                }
+            }
+        }
+        sum
+    }
+}
@@ -0,0 +1,86 @@
+use crate::network::snn::SNN;
+use crate::resilience::fault_models::InjectedFault;
+
+// Decoding of the output of a network
+// -----------------------------------
+// The class predicted by the network is decoded from the output of the last layer with one of the strategies:
+// - SpikeCount: the output neuron with the highest number of spikes
+// - FirstSpike: the output neuron that spikes first (time-to-first-spike)
+// - MembranePotential: the output neuron with the highest membrane potential at the end of the input
+// - Population(size): the output neurons are grouped in consecutive populations of the given size (one for each class),
+//   and the population with the highest number of spikes wins
+// When the output doesn't select a single class (no spikes or a tie) the decoder returns NoDecision,
+// so that a fault silencing the output layer is counted as a failure, whatever the target.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Class(u8),  // class predicted by the network
+    NoDecision, // the output doesn't select a single class
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decoder {
+    SpikeCount,
+    FirstSpike,
+    MembranePotential,
+    Population(usize), // number of output neurons of each class
+}
+
+impl Decoder {
+    /**
+        It decodes the class predicted by the network
+        - @param output_spikes: spikes of each output neuron (one row for each neuron, one column for each time instant)
+        - @param membrane_potentials: membrane potential of each output neuron at the end of the input
+     */
+    pub fn decode(&self, output_spikes: &[Vec<u8>], membrane_potentials: &[f64]) -> Decision {
+        let spike_counts = || output_spikes.iter().map(|spikes| spikes.iter().map(|s| *s as f64).sum::<f64>());
+        match *self {
+            Decoder::SpikeCount => decide(&spike_counts().collect::<Vec<f64>>(), true),
+            Decoder::FirstSpike => {
+                // the earlier the first spike, the higher the score (no score if the neuron never spikes)
+                let first_spikes = output_spikes.iter()
+                    .map(|spikes| spikes.iter().position(|s| *s == 1).map_or(f64::NEG_INFINITY, |t| -(t as f64)))
+                    .collect::<Vec<f64>>();
+                decide(&first_spikes, false)
+            },
+            Decoder::MembranePotential => decide(membrane_potentials, false),
+            Decoder::Population(size) => {
+                if size == 0 || !output_spikes.len().is_multiple_of(size) {
+                    panic!("Error: The number of output neurons should be a multiple of the size of the populations");
+                }
+                let counts = spike_counts().collect::<Vec<f64>>();
+                decide(&counts.chunks(size).map(|population| population.iter().sum()).collect::<Vec<f64>>(), true)
+            },
+        }
+    }
+}
+
+// The class with the highest score, if it is the only one (and, if required, if its score is positive)
+fn decide(scores: &[f64], positive: bool) -> Decision {
+    let max = scores.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let winners = scores.iter().enumerate().filter(|(_, s)| **s == max).map(|(i, _)| i).collect::<Vec<usize>>();
+    if winners.len() != 1 || max == f64::NEG_INFINITY || (positive && max <= 0.0) {
+        return Decision::NoDecision;
+    }
+    Decision::Class(winners[0] as u8)
+}
+
+impl SNN
+{
+    /**
+        It processes the input spikes and decodes the class predicted by the network
+        (the membrane potentials are read from the output layer at the end of the input)
+     */
+    pub fn decode(&self, input_spikes: &Vec<Vec<u8>>, injected_fault: Option<InjectedFault>, decoder: Decoder) -> Decision {
+        let (output_spikes, membrane_potentials) = self.process_input_with_potentials(input_spikes, injected_fault);
+        decoder.decode(&output_spikes, &membrane_potentials)
+    }
+
+    /**
+        Accuracy of the network on the input spike trains with the given decoder:
+        the inputs without a decision count as errors
+     */
+    pub fn get_decoded_accuracy(&self, input_spike_train: &[Vec<Vec<u8>>], targets: &Vec<u8>, decoder: Decoder) -> f64 {
+        self.process_batch(input_spike_train, targets, None, Some(decoder)).accuracy
+    }
+}
@@ -0,0 +1,108 @@
+// Fixed-point representation of the values stored in the network
+// ---------------------------------------------------------------
+// This module models the fixed-point arithmetic of the accelerators where the SNN is deployed.
+// - `QFormat` describes a signed Qm.n format: m integer bits (sign bit included) and n fractional bits.
+// - `Fixed` is a value in a given format, stored as a two's complement integer on m+n bits:
+//    - conversions from f64 are rounded to the nearest representable value
+//    - arithmetic operations saturate to the minimum/maximum representable value, as in hardware.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QFormat {
+    integer_bits: u32,      // number of integer bits (sign bit included)
+    fractional_bits: u32,   // number of fractional bits
+}
+
+impl QFormat {
+    pub fn new(integer_bits: u32, fractional_bits: u32) -> Self {
+        // the values must be exactly representable as f64 when they are exchanged with the rest of the network
+        if integer_bits == 0 || integer_bits + fractional_bits > 32 {
+            panic!("Error: The fixed-point format must have at least 1 integer bit and at most 32 bits");
+        }
+        QFormat { integer_bits, fractional_bits }
+    }
+
+    /**
+        It parses a format written as "Qm.n" (e.g. "Q8.8" or "Q16.16")
+     **/
+    pub fn from_name(name: &str) -> Result<Self, &'static str> {
+        let bits = match name.strip_prefix('Q').and_then(|b| b.split_once('.')) {
+            Some(bits) => bits,
+            None => return Err("Error: the fixed-point format must be written as Qm.n")
+        };
+        match (bits.0.parse::<u32>(), bits.1.parse::<u32>()) {
+            (Ok(m), Ok(n)) if m > 0 && m + n <= 32 => Ok(QFormat::new(m, n)),
+            _ => Err("Error: the fixed-point format must have at least 1 integer bit and at most 32 bits")
+        }
+    }
+
+    // Getters
+    pub fn get_integer_bits(&self) -> u32 { self.integer_bits }
+    pub fn get_fractional_bits(&self) -> u32 { self.fractional_bits }
+    pub fn get_width(&self) -> usize { (self.integer_bits + self.fractional_bits) as usize }
+
+    fn max_raw(&self) -> i64 { (1i64 << (self.get_width() - 1)) - 1 }
+    fn min_raw(&self) -> i64 { -(1i64 << (self.get_width() - 1)) }
+    fn scale(&self) -> f64 { (1u64 << self.fractional_bits) as f64 }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fixed {
+    raw: i64,           // integer value (the represented value is raw / 2^n)
+    format: QFormat,    // format of the value
+}
+
+impl Fixed {
+    // Conversion from f64, rounding to the nearest value and saturating
+    pub fn from_f64(value: f64, format: QFormat) -> Self {
+        let scaled = (value * format.scale()).round();
+        let raw = if scaled.is_nan() {
+            0
+        } else if scaled >= format.max_raw() as f64 {
+            format.max_raw()
+        } else if scaled <= format.min_raw() as f64 {
+            format.min_raw()
+        } else {
+            scaled as i64
+        };
+        Fixed { raw, format }
+    }
+
+    // Conversion from the integer value, saturating
+    pub fn from_raw(raw: i64, format: QFormat) -> Self {
+        Fixed { raw: raw.clamp(format.min_raw(), format.max_raw()), format }
+    }
+
+    // Conversion from the two's complement encoding on m+n bits (sign extension)
+    pub fn from_bits(bits: u64, format: QFormat) -> Self {
+        let unused_bits = 64 - format.get_width();
+        Fixed { raw: ((bits << unused_bits) as i64) >> unused_bits, format }
+    }
+
+    pub fn to_f64(&self) -> f64 { self.raw as f64 / self.format.scale() }
+
+    // Getters
+    pub fn get_raw(&self) -> i64 { self.raw }
+    pub fn get_format(&self) -> QFormat { self.format }
+
+    // Two's complement encoding on m+n bits: the bit indices correspond to the hardware bits
+    pub fn get_bits(&self) -> u64 {
+        (self.raw as u64) & (u64::MAX >> (64 - self.format.get_width()))
+    }
+
+    // Saturating arithmetic (the operands are expected to share the same format)
+    pub fn saturating_add(self, other: Fixed) -> Fixed {
+        Fixed::from_raw(self.raw + other.raw, self.format)
+    }
+
+    pub fn saturating_sub(self, other: Fixed) -> Fixed {
+        Fixed::from_raw(self.raw - other.raw, self.format)
+    }
+
+    pub fn saturating_mul(self, other: Fixed) -> Fixed {
+        // the product has 2n fractional bits => round to the nearest value with n fractional bits
+        let n = self.format.fractional_bits;
+        let product = self.raw * other.raw;
+        let raw = if n == 0 { product } else { (product + (1i64 << (n - 1))) >> n };
+        Fixed::from_raw(raw, self.format)
+    }
+}
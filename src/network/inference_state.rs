@@ -0,0 +1,45 @@
+use crate::network::network_layer::NetworkLayerState;
+
+/**
+    State of the layers of an SNN during an inference, owned by the caller of the inference (see SNN::process_input_with_state):
+    the layers of the network are only read, so that several inferences can share the same network concurrently,
+    each one with its own state.
+    The state is reset at the beginning of each inference, except for the extra weights learned with the STDP rule,
+    which are kept for the following inferences with the same state.
+ */
+#[derive(Debug)]
+pub struct InferenceState
+{
+    layers: Vec<Box<dyn NetworkLayerState>>,
+}
+
+impl Clone for InferenceState {
+    fn clone(&self) -> Self {
+        InferenceState { layers: self.layers.iter().map(|layer| layer.clone_state()).collect() }
+    }
+}
+
+impl InferenceState
+{
+    pub(crate) fn new(layers: Vec<Box<dyn NetworkLayerState>>) -> Self {
+        InferenceState { layers }
+    }
+
+    // Getters
+    pub fn get_num_layers(&self) -> usize {
+        self.layers.len()
+    }
+
+    pub fn get_layer(&self, index: usize) -> &dyn NetworkLayerState {
+        self.layers[index].as_ref()
+    }
+
+    pub(crate) fn get_layers_mut(&mut self) -> &mut Vec<Box<dyn NetworkLayerState>> {
+        &mut self.layers
+    }
+
+    // Membrane potential of each output neuron at the end of the last processed input
+    pub fn get_output_membrane_potentials(&self) -> Vec<f64> {
+        self.layers.last().map_or(Vec::new(), |layer| layer.get_membrane_potentials())
+    }
+}
@@ -1,21 +1,75 @@
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::Arc;
 use std::sync::mpsc::{Sender, Receiver};
 use crate::network::neuron::neuron::Neuron;
 use crate::network::event::spike_event::SpikeEvent;
 use crate::resilience::components::{ComponentType, ComponentCategory};
 use crate::resilience::fault_models::{InjectedFault, ApplyFault};
+use crate::network::fixed_point::Fixed;
+use crate::network::storage_format::StorageFormat;
+use crate::network::stdp::StdpParameters;
+use crate::network::convolution::Convolution;
+use crate::network::pooling::Pooling;
+use crate::network::skip_connection::SkipConnection;
+use crate::network::sparse::CsrMatrix;
+use crate::network::pruning::PruningCriterion;
+use crate::network::network_layer::{NetworkLayer, NetworkLayerState};
+use crate::resilience::components::FaultableComponent;
 
 
-#[derive(Debug)]
-pub struct Layer<N> 
-where N: Neuron + Clone + Send + 'static
+/**
+  Weights and connections of a layer, which don't change during an inference: they are shared read-only by the copies
+  of the layer and copied only when a copy modifies them (a static fault in a connection or the pruning),
+  so that cloning a network doesn't copy its weight matrices.
+ */
+#[derive(Debug, Clone)]
+pub struct LayerWeights {
+    pub(crate) extra_weights: Vec<Vec<f64>>,       // weights of the connections between each neuron and the neurons in the previous layer
+    pub(crate) intra_weights: Vec<Vec<f64>>,       // weights of the connections between each neuron and the neurons in the same layer
+    pub(crate) delays: Vec<Vec<u64>>,              // delays (in time steps) of the connections between each neuron and the neurons in the previous layer
+    pub(crate) convolution: Option<Convolution>,   // shared kernels replacing the extra weights in a convolutional layer, if any
+    pub(crate) skip_connections: Vec<SkipConnection>, // connections with the earlier layers of the network (sorted by source layer)
+    pub(crate) sparse_extra_weights: Option<CsrMatrix>, // extra weights stored as a sparse matrix (replacing the dense one), if any
+    pub(crate) sparse_intra_weights: Option<CsrMatrix>, // intra weights stored as a sparse matrix (replacing the dense one), if any
+}
+
+/**
+  State of a layer during an inference, owned by the inference (see InferenceState) instead of the layer,
+  so that the inferences only read the layer and can share it without locking it.
+  It's reset at the beginning of each inference, except for the extra weights learned with the STDP rule,
+  which are kept for the following inferences with the same state.
+ */
+#[derive(Debug, Clone)]
+pub struct LayerState<N>
+where N: Neuron + Clone + Send + Sync + 'static
 {
-    pub neurons: Vec<N>,                    // neurons in a layer
-    pub extra_weights: Vec<Vec<f64>>,   // weights of the connections between each neuron and the neurons in the previous layer
-    pub intra_weights: Vec<Vec<f64>>,   // weights of the connections between each neuron and the neurons in the same layer
+    neurons: Vec<N>,                    // neurons processing the inference (copied from the neurons of the layer)
     prev_output: Vec<u8>,               // output vector (0/1) in a layer at time instant t-1: used to decrease the v_mem according to the intra_weights
+    delay_buffers: Vec<Vec<u64>>,       // spikes in flight on each delayed connection: bit k = spike arriving k time steps after buffer_time
+    buffer_time: u64,                   // time instant of the last shift of the delay buffers
+    last_pre_spikes: Vec<Vec<Option<u64>>>, // time instant of the last spike arrived on each connection with the previous layer
+    last_post_spikes: Vec<Option<u64>>, // time instant of the last spike of each neuron
+    learned_extra_weights: Option<Vec<Vec<f64>>>, // extra weights learned with the STDP rule (starting from the extra weights of the layer), if any
 }
 
-impl <N: Neuron + Clone + Send + 'static> Layer<N> {
+#[derive(Debug, Clone)]
+pub struct Layer<N> 
+where N: Neuron + Clone + Send + Sync + 'static
+{
+    neurons: Arc<Vec<N>>,               // neurons of the layer in their initial state (constants of the neuron model), shared by the copies of the layer
+    weights: Arc<LayerWeights>,         // weights and connections, shared by the copies of the layer
+    weight_format: StorageFormat,       // format in which the weights are stored (f64 by default)
+    max_delay: u64,                     // maximum delay of the connections (0 => the input spikes arrive directly)
+    winner_take_all: Option<usize>,     // maximum number of neurons that can spike in the same time instant (k-WTA), if any
+    stdp: Option<StdpParameters>,       // STDP rule updating the extra weights during the processing, if any
+    pooling: Option<Pooling>,           // pooling units replacing the extra weights in a pooling layer, if any
+}
+
+// Maximum delay of a connection: the delay buffers are 64-bit shift registers
+pub const MAX_DELAY: u64 = 63;
+
+impl <N: Neuron + Clone + Send + Sync + 'static> Layer<N> {
 
   pub fn new(
     neurons: Vec<N>,
@@ -26,6 +80,7 @@ impl <N: Neuron + Clone + Send + 'static> Layer<N> {
     let num_n = neurons.len();
     let num_n_prev = extra_weights.len();
     let num_n_same = intra_weights.len();
+    let num_n_in = extra_weights.first().map_or(0, |w| w.len());
 
     // check the number of neurons for the layer is consistent with the number of rows in the weights matrices
     if num_n_prev != num_n || num_n_same != num_n 
@@ -33,12 +88,167 @@ impl <N: Neuron + Clone + Send + 'static> Layer<N> {
       panic!("The number of neurons in the layer is not consistent with the number of rows in the weights matrices.");
     }
 
-    Layer { 
-      neurons, 
-      extra_weights, 
-      intra_weights, 
-      prev_output: vec![0; num_n] 
+    let weights = LayerWeights {
+      extra_weights,
+      intra_weights,
+      delays: vec![vec![0; num_n_in]; num_n],
+      convolution: None,
+      skip_connections: Vec::new(),
+      sparse_extra_weights: None,
+      sparse_intra_weights: None
+    };
+    Layer {
+      neurons: Arc::new(neurons),
+      weights: Arc::new(weights),
+      weight_format: StorageFormat::F64,
+      max_delay: 0,
+      winner_take_all: None,
+      stdp: None,
+      pooling: None
+    }
+  }
+
+  // Weights and connections of the layer, shared with its copies
+  pub fn get_weights(&self) -> &Arc<LayerWeights> {
+    &self.weights
+  }
+
+  /**
+    Weights and connections of the layer to be modified (copied first if they are shared with other copies of the layer)
+   */
+  pub(crate) fn get_weights_mut(&mut self) -> &mut LayerWeights {
+    Arc::make_mut(&mut self.weights)
+  }
+
+  /**
+    Neurons of the layer to be modified (copied first if they are shared with other copies of the layer):
+    the weights of the layer are not copied
+   */
+  pub(crate) fn get_neurons_mut(&mut self) -> &mut Vec<N> {
+    Arc::make_mut(&mut self.neurons)
+  }
+
+  /**
+    It makes the layer a convolutional layer: the neurons are the output volume of the convolution
+    and the spikes of the previous layer are weighted by the shared kernels instead of the extra weights
+   */
+  pub fn set_convolution(&mut self, convolution: Convolution) {
+    if convolution.get_output_length() != self.neurons.len() {
+      panic!("Error: The number of neurons of the convolutional layer should be equal to the size of the output volume of the convolution");
+    }
+    self.get_weights_mut().convolution = Some(convolution);
+  }
+
+  /**
+    It makes the layer a pooling layer: the neurons are the output volume of the pooling
+    and each neuron receives the pooled spikes of its window instead of the weighted sum of the extra weights
+   */
+  pub fn set_pooling(&mut self, pooling: Pooling) {
+    if pooling.get_output_length() != self.neurons.len() {
+      panic!("Error: The number of neurons of the pooling layer should be equal to the size of the output volume of the pooling");
+    }
+    self.pooling = Some(pooling);
+  }
+
+  /**
+    It connects the layer with an earlier layer of the network (skip connection): the spikes of the source layer
+    are weighted by the weights of the connection and added to the weighted sum of each neuron.
+    The input spike events of the layer are the concatenation of the spikes of the previous layer
+    and of the source layers of the skip connections, in the order of the source layers.
+   */
+  pub fn add_skip_connection(&mut self, skip_connection: SkipConnection) {
+    if skip_connection.get_weights().len() != self.neurons.len() {
+      panic!("Error: The number of neurons in the layer should be equal to the number of rows of the weights matrix of the skip connection");
+    }
+    if self.weights.skip_connections.iter().any(|s| s.get_source() == skip_connection.get_source()) {
+      panic!("Error: The layer already has a skip connection with the layer {}", skip_connection.get_source());
+    }
+    let weights = self.get_weights_mut();
+    weights.skip_connections.push(skip_connection);
+    weights.skip_connections.sort_by_key(|s| s.get_source());
+  }
+
+  /**
+    It sets the delays (in time steps) of the connections with the previous layer:
+    a spike produced at time t by the neuron j of the previous layer reaches the neuron i at time t + delays[i][j]
+   */
+  pub fn set_delays(&mut self, delays: Vec<Vec<u64>>) {
+    if delays.len() != self.weights.extra_weights.len() || delays.iter().zip(self.weights.extra_weights.iter()).any(|(d, w)| d.len() != w.len()) {
+      panic!("Error: The delays matrix should have the same dimensions as the extra_weights matrix");
+    }
+    if delays.iter().flatten().any(|d| *d > MAX_DELAY) {
+      panic!("Error: The delay of a connection should be at most {} time steps", MAX_DELAY);
+    }
+    self.max_delay = delays.iter().flatten().copied().max().unwrap_or(0);
+    self.get_weights_mut().delays = delays;
+  }
+
+  /**
+    It makes the layer a k-winner-take-all layer: in each time instant at most k of the neurons that
+    reach the threshold can spike (the ones with the largest weighted input), while the others are inhibited
+   */
+  pub fn set_winner_take_all(&mut self, k: usize) {
+    if k == 0 {
+      panic!("Error: The number of winners of a winner-take-all layer should be at least 1");
+    }
+    self.winner_take_all = Some(k);
+  }
+
+  /**
+    It enables the STDP learning of the extra weights: the weights are updated in each time instant
+    in which a spike reaches or leaves the layer, and the learned weights are kept in the state of the inference
+    for the next inferences with the same state (the weights of the layer are not modified)
+   */
+  pub fn set_stdp(&mut self, stdp: StdpParameters) {
+    if self.weights.sparse_extra_weights.is_some() {
+      panic!("Error: The STDP rule can't be enabled in a layer with sparse weights");
+    }
+    self.stdp = Some(stdp);
+  }
+
+  /**
+    It stores the extra and intra weights as sparse (CSR) matrices: only the non-zero weights are kept,
+    so that each time step visits only the existing synapses and the faults can target only them.
+    The dense matrices are left with empty rows (the sparse ones can be read with get_sparse_extra_weights
+    and get_sparse_intra_weights).
+   */
+  pub fn set_sparse_weights(&mut self) {
+    if self.stdp.is_some() {
+      panic!("Error: The weights of a layer learning with the STDP rule should be stored as dense matrices");
+    }
+    if self.weights.sparse_extra_weights.is_some() {
+      return;
     }
+    let num_n = self.neurons.len();
+    let weights = self.get_weights_mut();
+    weights.sparse_extra_weights = Some(CsrMatrix::from_dense(&weights.extra_weights));
+    weights.sparse_intra_weights = Some(CsrMatrix::from_dense(&weights.intra_weights));
+    weights.extra_weights = vec![Vec::new(); num_n];
+    weights.intra_weights = vec![Vec::new(); num_n];
+  }
+
+  /**
+    It stores the weights of the layer in the given format:
+    the weights are rounded to the nearest representable value, and the weighted sums
+    are computed in the same format (saturating additions for the fixed-point formats).
+   */
+  pub fn set_weight_format(&mut self, format: StorageFormat) {
+    let weights = self.get_weights_mut();
+    for w in weights.extra_weights.iter_mut().chain(weights.intra_weights.iter_mut()).flatten() {
+      *w = format.quantize(*w);
+    }
+    if let Some(convolution) = weights.convolution.as_mut() {
+      for w in convolution.kernel_weights.iter_mut() {
+        *w = format.quantize(*w);
+      }
+    }
+    for w in weights.skip_connections.iter_mut().flat_map(|s| s.weights.iter_mut()).flatten() {
+      *w = format.quantize(*w);
+    }
+    for w in weights.sparse_extra_weights.iter_mut().chain(weights.sparse_intra_weights.iter_mut()).flat_map(|m| m.values.iter_mut()) {
+      *w = format.quantize(*w);
+    }
+    self.weight_format = format;
   }
 
   // Getters
@@ -46,31 +256,163 @@ impl <N: Neuron + Clone + Send + 'static> Layer<N> {
     self.neurons.len()
   }
 
-  pub fn get_neurons(&self) -> &Vec<N> {
+  // Neurons of the layer in their initial state, shared with the copies of the layer
+  pub fn get_neurons(&self) -> &Arc<Vec<N>> {
     &self.neurons
   }
 
   pub fn get_extra_weights(&self) -> &Vec<Vec<f64>> {
-    &self.extra_weights
+    &self.weights.extra_weights
   }
 
   pub fn get_intra_weights(&self) -> &Vec<Vec<f64>> {
-    &self.intra_weights
+    &self.weights.intra_weights
   }
 
-  pub fn get_prev_output(&self) -> &Vec<u8> {
-    &self.prev_output
+  pub fn get_weight_format(&self) -> StorageFormat {
+    self.weight_format
   }
 
+  pub fn get_delays(&self) -> &Vec<Vec<u64>> {
+    &self.weights.delays
+  }
+
+  pub fn get_max_delay(&self) -> u64 {
+    self.max_delay
+  }
+
+  pub fn get_winner_take_all(&self) -> Option<usize> {
+    self.winner_take_all
+  }
+
+  pub fn get_stdp(&self) -> Option<StdpParameters> {
+    self.stdp
+  }
+
+  pub fn get_convolution(&self) -> Option<&Convolution> {
+    self.weights.convolution.as_ref()
+  }
+
+  pub fn get_convolution_mut(&mut self) -> Option<&mut Convolution> {
+    self.get_weights_mut().convolution.as_mut()
+  }
+
+  pub fn get_pooling(&self) -> Option<&Pooling> {
+    self.pooling.as_ref()
+  }
+
+  pub fn get_skip_connections(&self) -> &Vec<SkipConnection> {
+    &self.weights.skip_connections
+  }
+
+  pub fn get_sparse_extra_weights(&self) -> Option<&CsrMatrix> {
+    self.weights.sparse_extra_weights.as_ref()
+  }
+
+  pub fn get_sparse_intra_weights(&self) -> Option<&CsrMatrix> {
+    self.weights.sparse_intra_weights.as_ref()
+  }
+
+  pub fn get_sparse_extra_weights_mut(&mut self) -> Option<&mut CsrMatrix> {
+    self.get_weights_mut().sparse_extra_weights.as_mut()
+  }
+
+  pub fn get_sparse_intra_weights_mut(&mut self) -> Option<&mut CsrMatrix> {
+    self.get_weights_mut().sparse_intra_weights.as_mut()
+  }
+
+  /**
+    Index (among the components of type SkipWeight) of the weight of the skip connection with the given source layer
+    between the neuron i of the layer and the neuron j of the source layer: the weights of the skip connections
+    are numbered row by row, in the order of the source layers
+   */
+  pub fn get_skip_weight_index(&self, source: usize, i: usize, j: usize) -> usize {
+    let mut offset = 0;
+    for skip_connection in self.weights.skip_connections.iter() {
+      if skip_connection.get_source() == source {
+        return offset + i * skip_connection.get_input_length() + j;
+      }
+      offset += skip_connection.get_num_weights();
+    }
+    panic!("Error: The layer has no skip connection with the layer {}", source);
+  }
+
+  // Weight of the skip connections with the given index (as numbered by get_skip_weight_index)
+  pub fn get_skip_weight_mut(&mut self, mut index: usize) -> &mut f64 {
+    for skip_connection in self.get_weights_mut().skip_connections.iter_mut() {
+      let input_length = skip_connection.get_input_length();
+      if index < skip_connection.get_num_weights() {
+        return &mut skip_connection.weights[index / input_length][index % input_length];
+      }
+      index -= skip_connection.get_num_weights();
+    }
+    panic!("Error: The index of the skip weight is out of range");
+  }
+
+  // Extra weights as a dense matrix (rebuilt from the sparse one if the weights are sparse)
+  pub fn get_dense_extra_weights(&self) -> Vec<Vec<f64>> {
+    match &self.weights.sparse_extra_weights {
+      Some(sparse_extra_weights) => sparse_extra_weights.to_dense(),
+      None => self.weights.extra_weights.clone(),
+    }
+  }
+
+  // Intra weights as a dense matrix (rebuilt from the sparse one if the weights are sparse)
+  pub fn get_dense_intra_weights(&self) -> Vec<Vec<f64>> {
+    match &self.weights.sparse_intra_weights {
+      Some(sparse_intra_weights) => sparse_intra_weights.to_dense(),
+      None => self.weights.intra_weights.clone(),
+    }
+  }
+
+  /**
+    It prunes the extra and intra weights with the given criterion: the removed weights are set to 0
+    and, if the weights are sparse, they are no longer stored (the pruned synapses can't be faulted).
+    - @return the number of non-zero weights removed
+   */
+  pub fn prune_weights(&mut self, criterion: PruningCriterion) -> usize {
+    let mut extra_weights = self.get_dense_extra_weights();
+    let mut intra_weights = self.get_dense_intra_weights();
+    let num_pruned = criterion.prune(&mut extra_weights) + criterion.prune(&mut intra_weights);
+    let weights = self.get_weights_mut();
+    if weights.sparse_extra_weights.is_some() {
+      weights.sparse_extra_weights = Some(CsrMatrix::from_dense(&extra_weights));
+      weights.sparse_intra_weights = Some(CsrMatrix::from_dense(&intra_weights));
+    }
+    else {
+      weights.extra_weights = extra_weights;
+      weights.intra_weights = intra_weights;
+    }
+    num_pruned
+  }
+
+  // Number of neurons of the previous layer (or of the input) connected to the layer
+  pub fn get_input_length(&self) -> usize {
+    match (&self.weights.convolution, &self.pooling, &self.weights.sparse_extra_weights) {
+      (Some(convolution), _, _) => convolution.get_input_length(),
+      (_, Some(pooling), _) => pooling.get_input_length(),
+      (_, _, Some(sparse_extra_weights)) => sparse_extra_weights.get_num_cols(),
+      _ => self.weights.extra_weights.first().map_or(0, |w| w.len()),
+    }
+  }
+
+  // Number of stored extra weights (only the existing synapses if the weights are sparse)
   pub fn get_tot_num_extra_weights(&self) -> usize {
-    let num_rows = self.extra_weights.len();
-    let num_cols = self.extra_weights[0].len();
+    if let Some(sparse_extra_weights) = &self.weights.sparse_extra_weights {
+      return sparse_extra_weights.get_num_values();
+    }
+    let num_rows = self.weights.extra_weights.len();
+    let num_cols = self.weights.extra_weights[0].len();
     num_rows * num_cols
   }
 
+  // Number of stored intra weights (only the existing synapses if the weights are sparse)
   pub fn get_tot_num_intra_weights(&self) -> usize {
-    let num_rows = self.intra_weights.len();
-    let num_cols = self.intra_weights[0].len();
+    if let Some(sparse_intra_weights) = &self.weights.sparse_intra_weights {
+      return sparse_intra_weights.get_num_values();
+    }
+    let num_rows = self.weights.intra_weights.len();
+    let num_cols = self.weights.intra_weights[0].len();
     num_rows * num_cols
   }
 
@@ -81,18 +423,53 @@ impl <N: Neuron + Clone + Send + 'static> Layer<N> {
     match component_type {
       // select one weight from the corresponding weights matrix
       ComponentType::Extra => self.get_tot_num_extra_weights(),
+      ComponentType::DelayBuffer => self.weights.delays.iter().map(|d| d.len()).sum(),
       ComponentType::Intra => self.get_tot_num_intra_weights(),
-      // else select one neuron from the neuron vector
+      ComponentType::KernelWeight => self.weights.convolution.as_ref().map_or(0, |c| c.get_kernel_weights().len()),
+      ComponentType::SkipWeight => self.weights.skip_connections.iter().map(|s| s.get_num_weights()).sum(),
+      // else select one neuron (or its pooling unit) from the neuron vector
       _ => self.get_num_neurons(),
     }
   }
 
+  // New state of an inference, with the neurons of the layer in their initial state
+  pub fn new_state(&self) -> LayerState<N> {
+    LayerState {
+      neurons: self.neurons.to_vec(),
+      prev_output: Vec::new(),
+      delay_buffers: Vec::new(),
+      buffer_time: 0,
+      last_pre_spikes: Vec::new(),
+      last_post_spikes: Vec::new(),
+      learned_extra_weights: None
+    }
+  }
+
   // Setters
-  fn initialize (&mut self) {
-    self.prev_output.clear();
-    self.prev_output = vec![0; self.neurons.len()];
-    
-    for neuron in self.neurons.iter_mut() {
+  fn initialize (&self, state: &mut LayerState<N>) {
+    let num_n = self.neurons.len();
+    state.prev_output.clear();
+    state.prev_output = vec![0; num_n];
+
+    // no spikes in flight at the beginning of the inference
+    state.delay_buffers = self.weights.delays.iter().map(|d| vec![0; d.len()]).collect();
+    state.buffer_time = 0;
+
+    // the STDP rule pairs only the spikes of the same inference,
+    // and it starts learning from the extra weights of the layer (or from the ones learned in the previous inferences)
+    let num_n_in = self.weights.extra_weights.first().map_or(0, |w| w.len());
+    state.last_pre_spikes = vec![vec![None; num_n_in]; num_n];
+    state.last_post_spikes = vec![None; num_n];
+    if self.stdp.is_none() {
+      state.learned_extra_weights = None;
+    }
+    else if state.learned_extra_weights.is_none() {
+      state.learned_extra_weights = Some(self.weights.extra_weights.clone());
+    }
+
+    // the neurons of the inference start from the neurons of the layer (with the static faults, if any)
+    state.neurons.clone_from(&self.neurons);
+    for neuron in state.neurons.iter_mut() {
       neuron.initialize();
     }
   }
@@ -101,108 +478,471 @@ impl <N: Neuron + Clone + Send + 'static> Layer<N> {
     It processes the input spikes coming from the previous layer
     according to the model of the neurons in the layer, and returns 
     the output spikes to the next layer.
+    - @param state: the state of the layer in the inference (the layer itself is only read)
     - @param input_rc: the channel to receive the input spike event from the previous layer
     - @param output_tx: the channel to send the output spike event to the next layer
+    - @param num_time_steps: duration of the input (the delayed spikes arriving later are discarded)
    */
-  pub fn process_input(&mut self, input_rc: Receiver<SpikeEvent>, output_tx: Sender<SpikeEvent>, fault: Option<InjectedFault>) {
+  pub fn process_input(&self, state: &mut LayerState<N>, input_rc: Receiver<SpikeEvent>, output_tx: Sender<SpikeEvent>, fault: Option<InjectedFault>, num_time_steps: u64) {
     
     // reset the neurons in the layer to reuse the SNN
     // for future inferences without building a new one
-    self.initialize();
+    self.initialize(state);
 
     // listen to the input spikes from the previous layer
     // until an Err is received (the channel is closed)
     while let Ok(input) = input_rc.recv() {
 
-      let timestamp = input.get_t();
-      let input_spikes = input.get_spikes();
-      let mut output_spikes = Vec::<u8>::with_capacity(self.neurons.len());
-
-      // if all the spikes in the output vector are 0
-      // then there is no need to send the output spikes to the next layer
-      let mut all_zero = true;
-
-      let extra_len = self.extra_weights[0].len();
-      let intra_len = self.intra_weights[0].len();
-
-      // for each neuron in the layer compute the membrane potential
-      // and check if it spikes
-      // -----------------------------------------------------------
-      for (i, neuron) in self.neurons.iter_mut().enumerate() {
-
-        // compute the sum of the weights of the connections between the neuron 
-        // and the neurons in the previous layer 
-        // ---> we consider the input spikes
-        let mut extra_weights_sum = 0.0;
-        for (j, weight) in self.extra_weights[i].iter().enumerate() {
-          
-          // If the fault targets the extra weight selected => apply the fault
+      // the delayed spikes arriving before the input event are processed first
+      self.process_delayed_spikes(state, input.get_t(), &output_tx, fault);
+      self.process_time_step(state, input.get_t(), input.get_spikes(), &output_tx, fault);
+    }
+
+    // the delayed spikes still in flight are processed until the end of the input
+    self.process_delayed_spikes(state, num_time_steps, &output_tx, fault);
+
+    // the state of the neurons is brought to the last time instant of the input (non-spiking readout)
+    if num_time_steps > 0 {
+      for neuron in state.neurons.iter_mut() {
+        neuron.complete_input(num_time_steps - 1);
+      }
+    }
+  }
+
+  /**
+    It processes the time instants before the given one in which a delayed spike
+    reaches a neuron of the layer, even if the layer doesn't receive an input spike event.
+   */
+  fn process_delayed_spikes(&self, state: &mut LayerState<N>, until: u64, output_tx: &Sender<SpikeEvent>, fault: Option<InjectedFault>) {
+    let input_length = self.get_input_length() + self.weights.skip_connections.iter().map(|s| s.get_input_length()).sum::<usize>();
+    let no_input_spikes = vec![0; input_length];
+    while let Some(t) = self.get_next_delayed_arrival(state) {
+      if t >= until {
+        break;
+      }
+      self.process_time_step(state, t, &no_input_spikes, output_tx, fault);
+    }
+  }
+
+  // Time instant of the next arrival of a spike in flight (None if there are no spikes in flight)
+  fn get_next_delayed_arrival(&self, state: &LayerState<N>) -> Option<u64> {
+    if self.max_delay == 0 {
+      return None;
+    }
+    state.delay_buffers.iter().flatten()
+      .filter(|buffer| **buffer != 0)
+      .map(|buffer| state.buffer_time + buffer.trailing_zeros() as u64)
+      .min()
+  }
+
+  /**
+    It shifts the delay buffers to the time instant t, stores the input spikes in the slots
+    corresponding to the delays of the connections and returns the spikes arriving at time t
+    on each connection (None if the layer has no delays, so that the input spikes arrive directly).
+    The faults in the delay buffers corrupt the spikes in flight in the time instants processed by the layer.
+   */
+  fn shift_delay_buffers(&self, state: &mut LayerState<N>, t: u64, input_spikes: &[u8], fault: Option<InjectedFault>) -> Option<Vec<Vec<u8>>> {
+    if self.max_delay == 0 {
+      return None;
+    }
+
+    let shift = t - state.buffer_time;
+    state.buffer_time = t;
+    let extra_len = self.weights.delays[0].len();
+    let mut arrived_spikes = vec![vec![0u8; extra_len]; self.weights.delays.len()];
+
+    for (i, (buffers, delays)) in state.delay_buffers.iter_mut().zip(self.weights.delays.iter()).enumerate() {
+      for (j, (buffer, delay)) in buffers.iter_mut().zip(delays.iter()).enumerate() {
+        let mut b = if shift >= 64 { 0 } else { *buffer >> shift };
+        if input_spikes[j] == 1 {
+          b |= 1 << delay;
+        }
+        match fault {
+          Some(f) if f.component_type == ComponentType::DelayBuffer && f.component_index == i*extra_len + j => b = f.apply_fault(b, t),
+          _ => {}
+        }
+        arrived_spikes[i][j] = (b & 1) as u8;
+        *buffer = b & !1;
+      }
+    }
+
+    Some(arrived_spikes)
+  }
+
+  /**
+    It processes the spikes reaching the layer at the given time instant
+    and sends the output spikes to the next layer.
+   */
+  fn process_time_step(&self, state: &mut LayerState<N>, timestamp: u64, input_spikes: &[u8], output_tx: &Sender<SpikeEvent>, fault: Option<InjectedFault>) {
+
+    let delayed_spikes = self.shift_delay_buffers(state, timestamp, input_spikes, fault);
+    let mut output_spikes = Vec::<u8>::with_capacity(state.neurons.len());
+    let mut weighted_inputs = Vec::<f64>::with_capacity(state.neurons.len());
+
+    let weight_format = self.weight_format;
+    let input_length = self.get_input_length();
+
+    // a layer learning with the STDP rule uses the extra weights learned in the state of the inference
+    let extra_weights = state.learned_extra_weights.as_ref().unwrap_or(&self.weights.extra_weights);
+
+    // in a winner-take-all layer the state of each neuron before the time instant is kept,
+    // so that the neurons losing the arbitration can be processed again without spiking
+    let mut previous_neurons = Vec::<N>::new();
+
+    // for each neuron in the layer compute the membrane potential
+    // and check if it spikes
+    // -----------------------------------------------------------
+    for (i, neuron) in state.neurons.iter_mut().enumerate() {
+
+      // compute the sum of the weights of the connections between the neuron 
+      // and the neurons in the previous layer 
+      // ---> we consider the input spikes
+      // ---> in a convolutional layer the input spikes are weighted by the shared kernels
+      // ---> in a pooling layer the input spikes are pooled in the window of the neuron
+      // ---> with sparse weights only the existing synapses are visited
+      let mut extra_weights_sum = match (&self.weights.convolution, &self.pooling) {
+        (Some(convolution), _) => convolution.weighted_sum(i, input_spikes, weight_format, fault, timestamp),
+        (_, Some(pooling)) => pooling.pool(i, input_spikes, weight_format, fault, timestamp),
+        _ => 0.0,
+      };
+      for (index, j, weight) in row_weights(extra_weights, self.weights.sparse_extra_weights.as_ref(), i) {
+
+        // spike arriving on the connection (after its delay)
+        let input_spike = match &delayed_spikes {
+          Some(spikes) => spikes[i][j],
+          None => input_spikes[j],
+        };
+        
+        // If the fault targets the extra weight selected => apply the fault
+        if fault.is_some()
+          && fault.unwrap().component_type == ComponentType::Extra 
+          && fault.unwrap().component_index == index
+        {
+          let faulted_weight = apply_weight_fault(fault.unwrap(), weight, weight_format, timestamp);
+          extra_weights_sum = accumulate(extra_weights_sum, faulted_weight, input_spike, weight_format);
+        }
+        else {
+          extra_weights_sum = accumulate(extra_weights_sum, weight, input_spike, weight_format);
+        }
+      }
+
+      // add the sums of the weights of the skip connections between the neuron
+      // and the neurons of the earlier layers
+      // ---> we consider the input spikes following the ones of the previous layer
+      let mut offset = input_length;
+      let mut skip_index = 0;
+      for skip_connection in self.weights.skip_connections.iter() {
+        let skip_len = skip_connection.get_input_length();
+        for (j, weight) in skip_connection.weights[i].iter().enumerate() {
+          let weight = match fault {
+            Some(f) if f.component_type == ComponentType::SkipWeight && f.component_index == skip_index + i*skip_len + j => apply_weight_fault(f, *weight, weight_format, timestamp),
+            _ => *weight,
+          };
+          extra_weights_sum = accumulate(extra_weights_sum, weight, input_spikes[offset + j], weight_format);
+        }
+        offset += skip_len;
+        skip_index += skip_connection.get_num_weights();
+      }
+
+      // compute the sum of the weights of the connections between the neuron
+      // and the neurons in the same layer
+      // ---> we consider the output spikes of the previous time instant
+      // - !!! ATTENTION to not consider the reflexive links from a neuron to itself !!!
+      let mut intra_weights_sum = 0.0;
+      for (index, j, weight) in row_weights(&self.weights.intra_weights, self.weights.sparse_intra_weights.as_ref(), i) {
+        if i != j {
           if fault.is_some()
-            && fault.unwrap().component_type == ComponentType::Extra 
-            && fault.unwrap().component_index == (i*extra_len + j)
+            && fault.unwrap().component_type == ComponentType::Intra 
+            && fault.unwrap().component_index == index
           {
-            extra_weights_sum += fault.unwrap().apply_fault(*weight, timestamp) * input_spikes[j] as f64;
+            let faulted_weight = apply_weight_fault(fault.unwrap(), weight, weight_format, timestamp);
+            intra_weights_sum = accumulate(intra_weights_sum, faulted_weight, state.prev_output[j], weight_format);
           }
           else {
-            extra_weights_sum += weight * input_spikes[j] as f64;
+            intra_weights_sum = accumulate(intra_weights_sum, weight, state.prev_output[j], weight_format);
           }
+
         }
+      }
 
-        // compute the sum of the weights of the connections between the neuron
-        // and the neurons in the same layer
-        // ---> we consider the output spikes of the previous time instant
-        // - !!! ATTENTION to not consider the reflexive links from a neuron to itself !!!
-        let mut intra_weights_sum = 0.0;
-        for (j, weight) in self.intra_weights[i].iter().enumerate() {
-          if i != j {
-            if fault.is_some()
-              && fault.unwrap().component_type == ComponentType::Intra 
-              && fault.unwrap().component_index == (i*intra_len + j)
-            {
-              intra_weights_sum += fault.unwrap().apply_fault(*weight, timestamp) * self.prev_output[j] as f64;
-            }
-            else {
-              intra_weights_sum += weight * self.prev_output[j] as f64;
-            }
+      let weights_sum = accumulate(extra_weights_sum, intra_weights_sum, 1, weight_format);
 
-          }
+      // compute the membrane potential and check if it spikes
+      // and update the output spikes vector
+      if self.winner_take_all.is_some() {
+        previous_neurons.push(neuron.clone());
+      }
+      let spike = neuron.process_input(timestamp, weights_sum, neuron_fault(fault, i));
+      output_spikes.push(spike);
+      weighted_inputs.push(weights_sum);
+    }
+
+    // in a winner-take-all layer only the winners of the arbitration spike:
+    // the candidates that lose the arbitration go back to their previous state and integrate the input
+    // as inhibited neurons, so that they keep their membrane potential (without the reset of the spike)
+    if let Some(k) = self.winner_take_all {
+      let candidate_spikes = output_spikes.clone();
+      arbitrate(&mut output_spikes, &weighted_inputs, k, timestamp, fault);
+      for (i, previous_neuron) in previous_neurons.into_iter().enumerate() {
+        if candidate_spikes[i] == 1 && output_spikes[i] == 0 {
+          let neuron = &mut state.neurons[i];
+          *neuron = previous_neuron;
+          neuron.process_inhibited_input(timestamp, weighted_inputs[i], neuron_fault(fault, i));
         }
+      }
+    }
 
-        let weights_sum = extra_weights_sum + intra_weights_sum;
+    // learning of the extra weights from the spikes of this time instant
+    if self.stdp.is_some() {
+      self.update_weights(state, timestamp, input_spikes, &delayed_spikes, &output_spikes, fault);
+    }
 
-        // compute the membrane potential and check if it spikes
-        // and update the output spikes vector
-        let spike;
-        if fault.is_some()
-          && fault.unwrap().component_category != ComponentCategory::Connection
-          && fault.unwrap().component_index == i
-        { // the fault still has to be injected in this neuron
-          spike = neuron.process_input(timestamp, weights_sum, fault);
-        } 
-        else 
-        { // there is no fault to be injected in this neuron
-          spike = neuron.process_input(timestamp, weights_sum, None);
+    // if all the spikes in the output vector are 0
+    // then there is no need to send the output spikes to the next layer
+    let all_zero = output_spikes.iter().all(|spike| *spike == 0);
+
+    // update the output vector of the previous time instant
+    // for the next iteration
+    state.prev_output = output_spikes.clone();
+
+    // if at least one spike in the input vector is 1
+    // then the output spikes are sent to the next layer
+    if !all_zero{
+
+      let output = SpikeEvent::new(timestamp, output_spikes);
+      output_tx.send(output).unwrap();
+    } 
+  }
+
+
+  /**
+    It updates the extra weights with the pair-based STDP rule, given the spikes of the time instant:
+    - each spike arriving on a connection depresses the weight according to the last spike of the neuron
+    - each spike of a neuron potentiates the weights according to the last spike arrived on each connection
+
+    The updated weights are bounded and stored in the weight format of the layer.
+    A fault in an extra weight is applied both when the weight is read and when it is written back,
+    so that the learning can't remove a permanent fault from the memory.
+   */
+  fn update_weights(&self, state: &mut LayerState<N>, timestamp: u64, input_spikes: &[u8], delayed_spikes: &Option<Vec<Vec<u8>>>, output_spikes: &[u8], fault: Option<InjectedFault>) {
+    let stdp = self.stdp.unwrap();
+    let extra_len = self.weights.extra_weights[0].len();
+    let weight_format = self.weight_format;
+
+    // the learned weights are stored in the state of the inference (the weights of the layer are only read)
+    let learned_extra_weights = state.learned_extra_weights.as_mut().expect("Error: The STDP rule can't learn without the extra weights in the state of the inference");
+    for (i, weights) in learned_extra_weights.iter_mut().enumerate() {
+      for (j, weight) in weights.iter_mut().enumerate() {
+        let input_spike = match delayed_spikes {
+          Some(spikes) => spikes[i][j],
+          None => input_spikes[j],
+        };
+        if input_spike == 0 && output_spikes[i] == 0 {
+          continue;
         }
-        output_spikes.push(spike);
 
-        // update the flag to send the output spikes to the next layer
-        if all_zero && spike == 1u8 {
-          all_zero = false;
+        let faulted = matches!(fault, Some(f) if f.component_type == ComponentType::Extra && f.component_index == i*extra_len + j);
+        let mut w = if faulted { apply_weight_fault(fault.unwrap(), *weight, weight_format, timestamp) } else { *weight };
+
+        // post-synaptic spike before the pre-synaptic one => depression
+        if input_spike == 1 {
+          if let Some(t_post) = state.last_post_spikes[i] {
+            w += stdp.depression(timestamp - t_post);
+          }
+          state.last_pre_spikes[i][j] = Some(timestamp);
         }
+        // pre-synaptic spike before (or together with) the post-synaptic one => potentiation
+        if output_spikes[i] == 1 {
+          if let Some(t_pre) = state.last_pre_spikes[i][j] {
+            w += stdp.potentiation(timestamp - t_pre);
+          }
+        }
+
+        w = weight_format.quantize(stdp.bound(w));
+        *weight = if faulted { apply_weight_fault(fault.unwrap(), w, weight_format, timestamp) } else { w };
       }
+    }
 
-      // update the output vector of the previous time instant
-      // for the next iteration
-      self.prev_output = output_spikes.clone();
+    for (last_post_spike, spike) in state.last_post_spikes.iter_mut().zip(output_spikes) {
+      if *spike == 1 {
+        *last_post_spike = Some(timestamp);
+      }
+    }
+  }
+}
 
-      // if at least one spike in the input vector is 1
-      // then the output spikes are sent to the next layer
-      if !all_zero{
+impl <N: Neuron + Clone + Send + Sync + 'static> LayerState<N> {
 
-        let output = SpikeEvent::new(timestamp, output_spikes);
-        output_tx.send(output).unwrap();
-      } 
+  // Neurons of the inference (in the state reached at the end of the input)
+  pub fn get_neurons(&self) -> &Vec<N> {
+    &self.neurons
+  }
+
+  pub fn get_prev_output(&self) -> &Vec<u8> {
+    &self.prev_output
+  }
+
+  // Extra weights learned with the STDP rule (None if the layer doesn't learn, or before the first inference)
+  pub fn get_learned_extra_weights(&self) -> Option<&Vec<Vec<f64>>> {
+    self.learned_extra_weights.as_ref()
+  }
+}
+
+impl <N: Neuron + Clone + Send + Sync + Debug + 'static> NetworkLayerState for LayerState<N> {
+  fn get_membrane_potentials(&self) -> Vec<f64> {
+    self.neurons.iter().map(|neuron| neuron.get_membrane_potential()).collect()
+  }
+
+  fn get_learned_extra_weights(&self) -> Option<&Vec<Vec<f64>>> {
+    LayerState::get_learned_extra_weights(self)
+  }
+
+  fn clone_state(&self) -> Box<dyn NetworkLayerState> {
+    Box::new(self.clone())
+  }
+
+  fn as_any(&self) -> &dyn Any {
+    self
+  }
+
+  fn as_any_mut(&mut self) -> &mut dyn Any {
+    self
+  }
+}
+
+impl <N: Neuron + Clone + Send + Sync + Debug + 'static> NetworkLayer for Layer<N> {
+  fn new_state(&self) -> Box<dyn NetworkLayerState> {
+    Box::new(Layer::new_state(self))
+  }
+
+  fn process_input(&self, state: &mut dyn NetworkLayerState, input_rc: Receiver<SpikeEvent>, output_tx: Sender<SpikeEvent>, fault: Option<InjectedFault>, num_time_steps: u64) {
+    match state.downcast_mut::<LayerState<N>>() {
+      Some(state) => Layer::process_input(self, state, input_rc, output_tx, fault, num_time_steps),
+      None => panic!("Error: The state of the inference doesn't belong to a layer of the same kind"),
     }
   }
 
-}
\ No newline at end of file
+  fn get_num_neurons(&self) -> usize {
+    Layer::get_num_neurons(self)
+  }
+
+  fn get_input_length(&self) -> usize {
+    Layer::get_input_length(self)
+  }
+
+  fn get_skip_sources(&self) -> Vec<(usize, usize)> {
+    self.weights.skip_connections.iter().map(|s| (s.get_source(), s.get_input_length())).collect()
+  }
+
+  fn get_dense_extra_weights(&self) -> Vec<Vec<f64>> {
+    Layer::get_dense_extra_weights(self)
+  }
+
+  fn get_dense_intra_weights(&self) -> Vec<Vec<f64>> {
+    Layer::get_dense_intra_weights(self)
+  }
+
+  fn is_fully_connected(&self) -> bool {
+    self.weights.convolution.is_none() && self.pooling.is_none() && self.weights.skip_connections.is_empty()
+  }
+
+  fn prune_weights(&mut self, criterion: PruningCriterion) -> usize {
+    Layer::prune_weights(self, criterion)
+  }
+
+  fn get_fault_surface(&self) -> Vec<FaultableComponent> {
+    Layer::get_fault_surface(self)
+  }
+
+  fn get_faultable_component(&self, component_type: ComponentType, component_index: usize) -> Option<FaultableComponent> {
+    Layer::get_faultable_component(self, component_type, component_index)
+  }
+
+  fn get_faultable_indices(&self, component_type: ComponentType) -> Vec<usize> {
+    Layer::get_faultable_indices(self, component_type)
+  }
+
+  fn apply_fault_in_component(&mut self, fault_info: &mut InjectedFault) -> bool {
+    Layer::apply_fault_in_component(self, fault_info)
+  }
+
+  fn clone_layer(&self) -> Box<dyn NetworkLayer> {
+    Box::new(self.clone())
+  }
+
+  fn as_any(&self) -> &dyn Any {
+    self
+  }
+
+  fn as_any_mut(&mut self) -> &mut dyn Any {
+    self
+  }
+}
+
+
+/**
+  It adds the weight of a connection to the weighted sum if the spike is 1:
+  - f64 weights => floating point product and sum
+  - reduced precision floating point weights => sum rounded to the format of the weights
+  - fixed-point weights => saturating fixed-point sum (as in the hardware accumulator)
+ */
+pub(crate) fn accumulate(sum: f64, weight: f64, spike: u8, weight_format: StorageFormat) -> f64 {
+  match weight_format {
+    StorageFormat::F64 => sum + weight * spike as f64,
+    _ if spike == 0 => sum,
+    StorageFormat::Fixed(q) => Fixed::from_f64(sum, q).saturating_add(Fixed::from_f64(weight, q)).to_f64(),
+    _ => weight_format.quantize(sum + weight),
+  }
+}
+
+/**
+  Weights of the row i of a weights matrix, as (index of the weight among the components, column, weight):
+  - dense weights => all the connections, numbered row by row
+  - sparse weights => only the stored weights, numbered as in the CSR matrix (the dense rows are empty)
+ */
+fn row_weights<'a>(dense_weights: &'a [Vec<f64>], sparse_weights: Option<&'a CsrMatrix>, i: usize) -> impl Iterator<Item = (usize, usize, f64)> + 'a {
+  let row_len = dense_weights[i].len();
+  let dense = dense_weights[i].iter().enumerate().map(move |(j, weight)| (i*row_len + j, j, *weight));
+  let sparse = sparse_weights.into_iter().flat_map(move |weights| weights.row(i));
+  dense.chain(sparse)
+}
+
+/**
+  It selects the winners of a k-winner-take-all layer among the neurons that spiked:
+  - the neurons are ranked by their weighted input (ties are won by the lowest index)
+  - the arbiter grants the spike to the first k neurons and inhibits the others
+  - a fault in the arbitration logic corrupts the grant signal of the selected neuron
+ */
+fn arbitrate(output_spikes: &mut [u8], weighted_inputs: &[f64], k: usize, timestamp: u64, fault: Option<InjectedFault>) {
+  let mut candidates = (0..output_spikes.len()).filter(|i| output_spikes[*i] == 1).collect::<Vec<usize>>();
+  candidates.sort_by(|a, b| weighted_inputs[*b].total_cmp(&weighted_inputs[*a]).then(a.cmp(b)));
+
+  let mut grants = vec![0u8; output_spikes.len()];
+  for i in candidates.into_iter().take(k) {
+    grants[i] = 1;
+  }
+
+  for (i, (spike, grant)) in output_spikes.iter_mut().zip(grants).enumerate() {
+    let grant = match fault {
+      Some(f) if f.component_type == ComponentType::WtaArbiter && f.component_index == i => f.apply_fault(grant, timestamp),
+      _ => grant,
+    };
+    *spike &= grant;
+  }
+}
+
+/**
+  It returns the fault to be injected in the i-th neuron of the layer (if it targets one of its components)
+ */
+fn neuron_fault(fault: Option<InjectedFault>, i: usize) -> Option<InjectedFault> {
+  fault.filter(|f| f.component_category != ComponentCategory::Connection
+    && f.component_category != ComponentCategory::Arbitration
+    && f.component_index == i)
+}
+
+/**
+  It applies the fault to the weight of a connection, in the encoding used to store it
+ */
+fn apply_weight_fault(fault: InjectedFault, weight: f64, weight_format: StorageFormat, timestamp: u64) -> f64 {
+  fault.apply_fault_in_format(weight, weight_format, timestamp)
+}
@@ -2,7 +2,19 @@ pub mod event;
 
 pub mod neuron;
 pub mod layer;
+pub mod network_layer;
 pub mod snn;
+pub mod inference_state;
+pub mod fixed_point;
+pub mod storage_format;
+pub mod stdp;
+pub mod convolution;
+pub mod pooling;
+pub mod skip_connection;
+pub mod sparse;
+pub mod pruning;
+pub mod decoder;
+pub mod batch;
 
 pub mod config;
 pub mod builder;
\ No newline at end of file
@@ -0,0 +1,128 @@
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::{Sender, Receiver};
+use crate::network::event::spike_event::SpikeEvent;
+use crate::resilience::components::{ComponentType, FaultableComponent};
+use crate::resilience::fault_models::InjectedFault;
+use crate::network::pruning::PruningCriterion;
+
+/**
+    Interface of the layers composing an SNN, used by the network to process the spike events
+    (one thread for each layer, connected by channels) and by the resilience analysis to inject the faults.
+    It is implemented by `Layer<N>` for any neuron model N, so that the same SNN can contain
+    dense, convolutional and pooling layers of different neuron models, connected as a directed acyclic graph.
+    The layers are only read by the inferences, which keep the state of each layer in their own NetworkLayerState:
+    the threads of concurrent inferences can share the same layers without locking them.
+ */
+pub trait NetworkLayer: Debug + Send + Sync {
+    // New state of an inference of the layer
+    fn new_state(&self) -> Box<dyn NetworkLayerState>;
+
+    /**
+        It processes the spike events received from the previous layer until the channel is closed,
+        and sends its output spike events to the next layer.
+        - @param state: state of the layer in the inference (created by new_state of a layer of the same kind)
+        - @param num_time_steps: duration of the input
+     */
+    fn process_input(&self, state: &mut dyn NetworkLayerState, input_rc: Receiver<SpikeEvent>, output_tx: Sender<SpikeEvent>, fault: Option<InjectedFault>, num_time_steps: u64);
+
+    // Number of neurons of the layer (length of its output spike vectors)
+    fn get_num_neurons(&self) -> usize;
+
+    // Number of neurons of the previous layer (or of the input) connected to the layer
+    fn get_input_length(&self) -> usize;
+
+    /**
+        Incoming edges of the layer from the earlier layers of the network (skip connections),
+        as (index of the source layer, number of neurons of the source layer), sorted by source layer.
+        The input spike events of the layer are the concatenation of the spikes of the previous layer
+        and of the spikes of the source layers, in this order.
+     */
+    fn get_skip_sources(&self) -> Vec<(usize, usize)>;
+
+    // Extra and intra weights as dense matrices (with zeros in place of the missing synapses)
+    fn get_dense_extra_weights(&self) -> Vec<Vec<f64>>;
+    fn get_dense_intra_weights(&self) -> Vec<Vec<f64>>;
+
+    // True if the neurons are connected to the previous layer only by the extra weights (no convolution, pooling or skip connections)
+    fn is_fully_connected(&self) -> bool;
+
+    /**
+        It prunes the extra and intra weights of the layer with the given criterion
+        - @return the number of non-zero weights removed
+     */
+    fn prune_weights(&mut self, criterion: PruningCriterion) -> usize;
+
+    // Components that can be faulted in the layer (each component type appears once)
+    fn get_fault_surface(&self) -> Vec<FaultableComponent>;
+
+    // Descriptor of the component of the given type and index (None if it can't be faulted)
+    fn get_faultable_component(&self, component_type: ComponentType, component_index: usize) -> Option<FaultableComponent>;
+
+    // Indices among which the component of the given type can be selected for a fault
+    fn get_faultable_indices(&self, component_type: ComponentType) -> Vec<usize>;
+
+    /**
+        It injects a static fault in the component before the processing phase.
+        - @return true if the bit in the component is unchanged after the fault is applied
+     */
+    fn apply_fault_in_component(&mut self, fault_info: &mut InjectedFault) -> bool;
+
+    // Independent copy of the layer: the weights and the neurons are shared with the original layer until one of them modifies them
+    fn clone_layer(&self) -> Box<dyn NetworkLayer>;
+
+    // Access to the concrete layer
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    /**
+        Number of bits of the encoding of the component, used to select the bit index of the fault
+     */
+    fn get_component_width(&self, component_type: ComponentType, component_index: usize) -> usize {
+        match self.get_faultable_component(component_type, component_index) {
+            Some(component) => component.bit_width,
+            None => panic!("Error: the component can't be faulted in the selected neuron"),
+        }
+    }
+}
+
+impl dyn NetworkLayer + '_ {
+    // The concrete layer, if it is of type L (e.g. Layer<Lif>)
+    pub fn downcast_ref<L: NetworkLayer + 'static>(&self) -> Option<&L> {
+        self.as_any().downcast_ref::<L>()
+    }
+
+    pub fn downcast_mut<L: NetworkLayer + 'static>(&mut self) -> Option<&mut L> {
+        self.as_any_mut().downcast_mut::<L>()
+    }
+}
+
+/**
+    State of a layer during an inference (implemented by `LayerState<N>`), owned by the inference:
+    the neurons processing the input, the spikes in flight and the extra weights learned with the STDP rule.
+ */
+pub trait NetworkLayerState: Debug + Send + Sync {
+    // Membrane potential of each neuron after the last processed time instant
+    fn get_membrane_potentials(&self) -> Vec<f64>;
+
+    // Extra weights learned with the STDP rule, if any
+    fn get_learned_extra_weights(&self) -> Option<&Vec<Vec<f64>>>;
+
+    // Independent copy of the state (e.g. to continue the learning from the same weights)
+    fn clone_state(&self) -> Box<dyn NetworkLayerState>;
+
+    // Access to the concrete state
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl dyn NetworkLayerState + '_ {
+    // The concrete state, if it is of type S (e.g. LayerState<Lif>)
+    pub fn downcast_ref<S: NetworkLayerState + 'static>(&self) -> Option<&S> {
+        self.as_any().downcast_ref::<S>()
+    }
+
+    pub fn downcast_mut<S: NetworkLayerState + 'static>(&mut self) -> Option<&mut S> {
+        self.as_any_mut().downcast_mut::<S>()
+    }
+}
@@ -0,0 +1,151 @@
+use crate::network::neuron::neuron::Neuron;
+use crate::network::neuron::lif::Lif;
+use crate::resilience::components::{ComponentType, ComponentCategory, FaultableComponent};
+use crate::resilience::fault_models::{InjectedFault, ApplyFault, StoredValue};
+use crate::network::storage_format::StorageFormat;
+// Implements the Neuron trait with the adaptive threshold LIF model (homeostasis):
+// each spike raises the threshold of the membrane by an increment, and the offset of the threshold
+// decays back to 0 (baseline threshold of the Lif) with its own time constant.
+
+#[derive(Debug, Clone)]
+pub struct AdaptiveLif {
+    lif: Lif, // membrane of the neuron (baseline threshold, reset, refractory period...)
+    threshold_offset: f64, // offset added to the baseline threshold
+    threshold_increment: f64, // increment of the offset after each spike
+    threshold_tau: f64 // time constant of the decay of the offset
+}
+
+impl AdaptiveLif {
+    pub fn new(lif: Lif, threshold_increment: f64, threshold_tau: f64) -> Self {
+        AdaptiveLif {
+            lif,
+            threshold_offset: 0.0, // at the beginning the threshold is the baseline one
+            threshold_increment,
+            threshold_tau
+        }
+    }
+
+    // Getters for the AdaptiveLif parameters
+    pub fn get_lif(&self) -> &Lif { &self.lif }
+    pub fn get_threshold_offset(&self) -> f64 { self.threshold_offset }
+    pub fn get_threshold_increment(&self) -> f64 { self.threshold_increment }
+    pub fn get_threshold_tau(&self) -> f64 { self.threshold_tau }
+
+    // Setter for the storage format of the memory areas (of the threshold and of the membrane)
+    pub fn set_storage_format(&mut self, format: StorageFormat) {
+        self.lif.set_storage_format(format);
+        self.threshold_offset = format.quantize(self.threshold_offset);
+        self.threshold_increment = format.quantize(self.threshold_increment);
+        self.threshold_tau = format.quantize(self.threshold_tau);
+    }
+
+    // Integration of the input at the time instant t: the inhibited neuron doesn't spike (see process_inhibited_input)
+    fn integrate(&mut self, time: u64, weighted_sum: f64, fault: Option<InjectedFault>, inhibited: bool) -> u8 {
+
+        // Get the parameters of the neuron checking during runtime if there is a fault to inject
+        let (mut threshold_offset, threshold_increment, threshold_tau, dt, ts) = self.read_memory_areas(fault, time);
+
+        // The offset decays since the last processed instant (no decay if a faulted ts is after the time instant t)
+        threshold_offset *= (-(time.saturating_sub(ts) as f64) * dt / threshold_tau).exp();
+
+        // The membrane compares its potential with the raised threshold (the faults in the other components are injected by the Lif)
+        // => the inhibited neuron has an infinite threshold
+        let output_spike = self.lif.integrate_and_fire(time, weighted_sum, fault, if inhibited { f64::INFINITY } else { threshold_offset });
+
+        // Each spike emitted by the neuron raises the threshold
+        if output_spike == 1 && !inhibited {
+            threshold_offset += threshold_increment;
+        }
+
+        // update the offset to be stored in the memory area (in the storage format)
+        let format = self.lif.get_storage_format();
+        let threshold_offset = format.quantize(threshold_offset);
+        match fault {
+            Some(f) if f.component_type == ComponentType::ThresholdOffset => self.threshold_offset = f.apply_fault_in_format(threshold_offset, format, time),
+            _ => self.threshold_offset = threshold_offset,
+        }
+
+        output_spike
+    }
+}
+
+impl Neuron for AdaptiveLif {
+    /**
+    Computes the threshold offset and the membrane potential of the neuron at the time instant t
+    and returns 1 if the neuron spikes, 0 otherwise.
+    - offset[t] = offset[ts] * exp(-(t - ts) * dt / threshold_tau)
+    - the neuron spikes if v_mem[t] > v_th + offset[t]
+    - after a spike: offset[t] += threshold_increment
+    - @param time (u64)
+    - @param weighted_sum (f64): added to the membrane potential, as for the Lif neuron
+    - @return u8 (0/1)
+     */
+    fn process_input(&mut self, time: u64, weighted_sum: f64, fault: Option<InjectedFault>) -> u8 {
+        self.integrate(time, weighted_sum, fault, false)
+    }
+
+    fn process_inhibited_input(&mut self, time: u64, weighted_sum: f64, fault: Option<InjectedFault>) {
+        self.integrate(time, weighted_sum, fault, true);
+    }
+
+    fn get_membrane_potential(&self) -> f64 {
+        self.lif.get_v_mem()
+    }
+
+    // Reset the threshold to the baseline and the membrane to its initial state
+    fn initialize(&mut self) {
+        self.threshold_offset = 0.0;
+        self.lif.initialize();
+    }
+
+    // Get the value of the parameter to fault
+    fn get_parameter_to_fault(&mut self, component_type: ComponentType) -> &mut dyn StoredValue {
+        match component_type {
+            ComponentType::ThresholdOffset      => &mut self.threshold_offset,
+            ComponentType::ThresholdIncrement   => &mut self.threshold_increment,
+            ComponentType::ThresholdTau         => &mut self.threshold_tau,
+            _                                   => self.lif.get_parameter_to_fault(component_type),
+        }
+    }
+
+    fn get_fault_surface(&self) -> Vec<FaultableComponent> {
+        let width = self.lif.get_storage_format().get_width();
+        let mut surface = self.lif.get_fault_surface();
+        surface.push(FaultableComponent::new(ComponentType::ThresholdOffset, "Threshold offset", width, false));
+        surface.push(FaultableComponent::new(ComponentType::ThresholdIncrement, "Threshold increment", width, true));
+        surface.push(FaultableComponent::new(ComponentType::ThresholdTau, "Threshold tau", width, true));
+        surface
+    }
+
+    fn get_storage_format(&self) -> StorageFormat {
+        self.lif.get_storage_format()
+    }
+}
+
+impl AdaptiveLif {
+    fn read_memory_areas(&mut self, fault: Option<InjectedFault>, time: u64) -> (f64, f64, f64, f64, u64) {
+        // Get the parameters of the neuron
+        // => In this way we are not soiling the original values of the built network with the fault,
+        //    since it's sufficient to inject the fault only when the neuron is processed
+        let mut threshold_offset = self.threshold_offset;
+        let mut threshold_increment = self.threshold_increment;
+        let mut threshold_tau = self.threshold_tau;
+        let mut dt = self.lif.get_dt();
+        let mut ts = self.lif.get_ts();
+        let format = self.lif.get_storage_format();
+
+        if let Some(injected_fault) = fault {
+            if injected_fault.component_category == ComponentCategory::MemoryArea {
+                match injected_fault.component_type {
+                    ComponentType::ThresholdOffset      => threshold_offset = injected_fault.apply_fault_in_format(threshold_offset, format, time),
+                    ComponentType::ThresholdIncrement   => threshold_increment = injected_fault.apply_fault_in_format(threshold_increment, format, time),
+                    ComponentType::ThresholdTau         => threshold_tau = injected_fault.apply_fault_in_format(threshold_tau, format, time),
+                    ComponentType::DT                   => dt = injected_fault.apply_fault_in_format(dt, format, time),
+                    ComponentType::Ts                   => ts = injected_fault.apply_fault(ts, time),
+                    _                                   => {}
+                }
+            }
+        }
+        (threshold_offset, threshold_increment, threshold_tau, dt, ts)
+    }
+}
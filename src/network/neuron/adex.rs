@@ -0,0 +1,262 @@
+use crate::network::neuron::neuron::Neuron;
+use crate::resilience::components::{ComponentType, ComponentCategory, FaultableComponent};
+use crate::resilience::fault_models::{InjectedFault, ApplyFault, StoredValue};
+// Implements the Neuron trait with the adaptive exponential integrate-and-fire (AdEx) model.
+
+// Parameters describing an AdEx neuron (potentials in mV, time constants in the same unit of dt)
+#[derive(Debug, Clone, Copy)]
+pub struct AdExParameters {
+    pub reset_potential: f64, // reset potential
+    pub resting_potential: f64, // resting potential (leak reversal potential)
+    pub threshold: f64, // rheobase threshold, where the exponential spike initiation starts
+    pub peak_potential: f64, // potential at which the spike is detected
+    pub slope_factor: f64, // sharpness of the exponential spike initiation
+    pub tau: f64, // membrane time constant
+    pub adaptation_coupling: f64, // subthreshold adaptation (a)
+    pub adaptation_increment: f64, // spike-triggered adaptation increment (b)
+    pub tau_adaptation: f64, // time constant of the adaptation current
+    pub dt: f64 // quantization parameter that defines the amplitude of the interval between two consecutive instants
+}
+
+#[derive(Debug, Clone)]
+pub struct AdEx {
+    reset_potential: f64, // reset potential
+    resting_potential: f64, // resting potential
+    threshold: f64, // rheobase threshold
+    peak_potential: f64, // spike detection potential
+    slope_factor: f64, // slope factor of the exponential spike initiation
+    membrane_potential: f64, // membrane potential
+    adaptation_current: f64, // adaptation current (w)
+    adaptation_coupling: f64, // subthreshold adaptation (a)
+    adaptation_increment: f64, // spike-triggered adaptation increment (b)
+    tau: f64, // membrane time constant
+    tau_adaptation: f64, // adaptation time constant
+    ts: u64, // last time instant where a spike has been received
+    dt: f64 // quantization parameter that defines the amplitude of the interval between two consecutive instants
+}
+
+impl AdEx {
+    pub fn new(p: AdExParameters) -> Self {
+        AdEx {
+            reset_potential: p.reset_potential,
+            resting_potential: p.resting_potential,
+            threshold: p.threshold,
+            peak_potential: p.peak_potential,
+            slope_factor: p.slope_factor,
+            membrane_potential: p.resting_potential, // at the beginning the membrane potential is equal to the resting potential
+            adaptation_current: 0.0, // and there is no adaptation
+            adaptation_coupling: p.adaptation_coupling,
+            adaptation_increment: p.adaptation_increment,
+            tau: p.tau,
+            tau_adaptation: p.tau_adaptation,
+            ts: 0, // starting from time instant 0
+            dt: p.dt
+        }
+    }
+
+    // Getters for the AdEx parameters
+    pub fn get_v_reset(&self) -> f64 { self.reset_potential }
+    pub fn get_v_rest(&self) -> f64 { self.resting_potential }
+    pub fn get_v_th(&self) -> f64 { self.threshold }
+    pub fn get_v_peak(&self) -> f64 { self.peak_potential }
+    pub fn get_slope_factor(&self) -> f64 { self.slope_factor }
+    pub fn get_v_mem(&self) -> f64 { self.membrane_potential }
+    pub fn get_w(&self) -> f64 { self.adaptation_current }
+    pub fn get_tau(&self) -> f64 { self.tau }
+    pub fn get_tau_w(&self) -> f64 { self.tau_adaptation }
+    pub fn get_ts(&self) -> u64 { self.ts }
+
+    // Integration of the input at the time instant t: the inhibited neuron doesn't spike (see process_inhibited_input)
+    fn integrate(&mut self, time: u64, mut weighted_sum: f64, fault: Option<InjectedFault>, inhibited: bool) -> u8 {
+
+        // Get the parameters of the neuron checking during runtime if there is a fault to inject
+        let p = self.read_memory_areas(fault, time);
+        let mut membrane_potential = p.membrane_potential;
+        let mut adaptation_current = p.adaptation_current;
+
+        // Possible fault in the adder/multiplier
+        if let Some(f) = fault {
+            if f.component_type == ComponentType::Adder || f.component_type == ComponentType::Multiplier {
+                weighted_sum = f.apply_fault(weighted_sum, time);
+            }
+        }
+
+        // Integrate the dynamics over the time steps elapsed since the last processed instant (forward Euler method)
+        // => The layer processes the neuron only when it receives an input spike event, so if the peak
+        //    is reached in between, the integration stops and the spike is emitted at the current instant
+        for _ in p.ts..time {
+            if membrane_potential >= p.peak_potential {
+                break;
+            }
+            let exponential = p.slope_factor * ((membrane_potential - p.threshold) / p.slope_factor).exp();
+            let dv = (-(membrane_potential - p.resting_potential) + exponential - adaptation_current) / p.tau;
+            let dw = (p.adaptation_coupling * (membrane_potential - p.resting_potential) - adaptation_current) / p.tau_adaptation;
+            membrane_potential += dv * p.dt;
+            adaptation_current += dw * p.dt;
+        }
+        let mp = membrane_potential + weighted_sum;
+
+        // update the variables to be stored each step in the memory areas
+        match fault {
+            Some(f) if f.component_type == ComponentType::MembranePotential => self.membrane_potential = f.apply_fault(mp, time),
+            _ => self.membrane_potential = mp,
+        }
+        match fault {
+            Some(f) if f.component_type == ComponentType::AdaptationCurrent => self.adaptation_current = f.apply_fault(adaptation_current, time),
+            _ => self.adaptation_current = adaptation_current,
+        }
+        self.ts = time;
+
+        // Check if the neuron spikes
+        let mut output_spike: u8;
+        if !inhibited && self.membrane_potential >= p.peak_potential {
+            self.membrane_potential = p.reset_potential;
+            self.adaptation_current += p.adaptation_increment;
+            output_spike = 1; // spike only if v_mem >= v_peak
+        }
+        else {
+            output_spike = 0;
+        }
+
+        // Possible fault in the threshold comparator
+        if let Some(f) = fault {
+            if f.component_type == ComponentType::ThresholdComparator {
+                output_spike = f.apply_fault(output_spike, time);
+            }
+        }
+
+        output_spike
+    }
+}
+
+impl Neuron for AdEx {
+    /**
+    Integrates the membrane potential and the adaptation current of the neuron up to the time instant t
+    and returns 1 if the neuron spikes, 0 otherwise.
+    - tau * dv/dt = -(v - v_rest) + slope_factor * exp((v - v_th) / slope_factor) - w
+    - tau_w * dw/dt = a * (v - v_rest) - w
+    - @param time (u64)
+    - @param weighted_sum (f64): added directly to the membrane potential, as for the Lif neuron
+    - @return u8 (0/1)
+     */
+    fn process_input(&mut self, time: u64, weighted_sum: f64, fault: Option<InjectedFault>) -> u8 {
+        self.integrate(time, weighted_sum, fault, false)
+    }
+
+    fn process_inhibited_input(&mut self, time: u64, weighted_sum: f64, fault: Option<InjectedFault>) {
+        self.integrate(time, weighted_sum, fault, true);
+    }
+
+    fn get_membrane_potential(&self) -> f64 {
+        self.membrane_potential
+    }
+
+    // Reset the membrane potential to the resting potential, the adaptation current to 0 and the time instant to 0
+    fn initialize(&mut self) {
+        self.membrane_potential = self.resting_potential;
+        self.adaptation_current = 0.0;
+        self.ts = 0;
+    }
+
+    // Get the value of the parameter to fault
+    fn get_parameter_to_fault(&mut self, component_type: ComponentType) -> &mut dyn StoredValue {
+        match component_type {
+            ComponentType::ResetPotential       => &mut self.reset_potential,
+            ComponentType::RestingPotential     => &mut self.resting_potential,
+            ComponentType::Threshold            => &mut self.threshold,
+            ComponentType::PeakPotential        => &mut self.peak_potential,
+            ComponentType::SlopeFactor          => &mut self.slope_factor,
+            ComponentType::MembranePotential    => &mut self.membrane_potential,
+            ComponentType::AdaptationCurrent    => &mut self.adaptation_current,
+            ComponentType::AdaptationCoupling   => &mut self.adaptation_coupling,
+            ComponentType::AdaptationIncrement  => &mut self.adaptation_increment,
+            ComponentType::Tau                  => &mut self.tau,
+            ComponentType::AdaptationTau        => &mut self.tau_adaptation,
+            ComponentType::DT                   => &mut self.dt,
+            ComponentType::Ts                   => &mut self.ts,
+            _                                   => panic!("Error: the component type is not valid for the AdEx neuron"),
+        }
+    }
+
+    fn get_fault_surface(&self) -> Vec<FaultableComponent> {
+        let mut surface = vec![
+            FaultableComponent::new(ComponentType::ResetPotential, "Reset potential", 64, true),
+            FaultableComponent::new(ComponentType::RestingPotential, "Resting potential", 64, true),
+            FaultableComponent::new(ComponentType::Threshold, "Threshold (rheobase)", 64, true),
+            FaultableComponent::new(ComponentType::MembranePotential, "Membrane potential", 64, false),
+            FaultableComponent::new(ComponentType::Tau, "Tau", 64, true),
+            FaultableComponent::new(ComponentType::Ts, "Ts", 64, false),
+            FaultableComponent::new(ComponentType::DT, "dt", 64, true),
+            FaultableComponent::new(ComponentType::PeakPotential, "Peak potential", 64, true),
+            FaultableComponent::new(ComponentType::SlopeFactor, "Slope factor", 64, true),
+            FaultableComponent::new(ComponentType::AdaptationCurrent, "Adaptation current", 64, false),
+            FaultableComponent::new(ComponentType::AdaptationCoupling, "Adaptation coupling (a)", 64, true),
+            FaultableComponent::new(ComponentType::AdaptationIncrement, "Adaptation increment (b)", 64, true),
+            FaultableComponent::new(ComponentType::AdaptationTau, "Adaptation tau", 64, true),
+        ];
+        surface.extend(FaultableComponent::processing_blocks(64));
+        surface
+    }
+}
+
+// Values of the memory areas read during the processing of the neuron
+struct AdExMemoryAreas {
+    reset_potential: f64,
+    resting_potential: f64,
+    threshold: f64,
+    peak_potential: f64,
+    slope_factor: f64,
+    membrane_potential: f64,
+    adaptation_current: f64,
+    adaptation_coupling: f64,
+    adaptation_increment: f64,
+    tau: f64,
+    tau_adaptation: f64,
+    dt: f64,
+    ts: u64
+}
+
+impl AdEx {
+    fn read_memory_areas(&mut self, fault: Option<InjectedFault>, time: u64) -> AdExMemoryAreas {
+        // Get the parameters of the neuron
+        // => In this way we are not soiling the original values of the built network with the fault,
+        //    since it's sufficient to inject the fault only when the neuron is processed
+        let mut p = AdExMemoryAreas {
+            reset_potential: self.reset_potential,
+            resting_potential: self.resting_potential,
+            threshold: self.threshold,
+            peak_potential: self.peak_potential,
+            slope_factor: self.slope_factor,
+            membrane_potential: self.membrane_potential,
+            adaptation_current: self.adaptation_current,
+            adaptation_coupling: self.adaptation_coupling,
+            adaptation_increment: self.adaptation_increment,
+            tau: self.tau,
+            tau_adaptation: self.tau_adaptation,
+            dt: self.dt,
+            ts: self.ts
+        };
+
+        if let Some(injected_fault) = fault {
+            if injected_fault.component_category == ComponentCategory::MemoryArea {
+                match injected_fault.component_type {
+                    ComponentType::ResetPotential       => p.reset_potential = injected_fault.apply_fault(p.reset_potential, time),
+                    ComponentType::RestingPotential     => p.resting_potential = injected_fault.apply_fault(p.resting_potential, time),
+                    ComponentType::Threshold            => p.threshold = injected_fault.apply_fault(p.threshold, time),
+                    ComponentType::PeakPotential        => p.peak_potential = injected_fault.apply_fault(p.peak_potential, time),
+                    ComponentType::SlopeFactor          => p.slope_factor = injected_fault.apply_fault(p.slope_factor, time),
+                    ComponentType::MembranePotential    => p.membrane_potential = injected_fault.apply_fault(p.membrane_potential, time),
+                    ComponentType::AdaptationCurrent    => p.adaptation_current = injected_fault.apply_fault(p.adaptation_current, time),
+                    ComponentType::AdaptationCoupling   => p.adaptation_coupling = injected_fault.apply_fault(p.adaptation_coupling, time),
+                    ComponentType::AdaptationIncrement  => p.adaptation_increment = injected_fault.apply_fault(p.adaptation_increment, time),
+                    ComponentType::Tau                  => p.tau = injected_fault.apply_fault(p.tau, time),
+                    ComponentType::AdaptationTau        => p.tau_adaptation = injected_fault.apply_fault(p.tau_adaptation, time),
+                    ComponentType::DT                   => p.dt = injected_fault.apply_fault(p.dt, time),
+                    ComponentType::Ts                   => p.ts = injected_fault.apply_fault(p.ts, time),
+                    _                                   => {}
+                }
+            }
+        }
+        p
+    }
+}
@@ -0,0 +1,150 @@
+use crate::network::neuron::neuron::Neuron;
+use crate::network::neuron::lif::Lif;
+use crate::resilience::components::{ComponentType, ComponentCategory, FaultableComponent};
+use crate::resilience::fault_models::{InjectedFault, ApplyFault, StoredValue};
+use crate::network::storage_format::StorageFormat;
+// Implements the Neuron trait with the current-based (CuBa) LIF model, as the snnTorch Synaptic neuron.
+// The weighted input feeds a synaptic current that decays with its own time constant,
+// and the synaptic current is integrated by the membrane of a Lif neuron.
+
+#[derive(Debug, Clone)]
+pub struct CubaLif {
+    lif: Lif, // membrane of the neuron (threshold, reset, refractory period...)
+    synaptic_current: f64, // synaptic current
+    synaptic_tau: f64 // time constant of the synaptic current
+}
+
+impl CubaLif {
+    pub fn new(lif: Lif, synaptic_tau: f64) -> Self {
+        CubaLif {
+            lif,
+            synaptic_current: 0.0, // at the beginning there is no synaptic current
+            synaptic_tau
+        }
+    }
+
+    // Getters for the CubaLif parameters
+    pub fn get_lif(&self) -> &Lif { &self.lif }
+    pub fn get_i_syn(&self) -> f64 { self.synaptic_current }
+    pub fn get_tau_syn(&self) -> f64 { self.synaptic_tau }
+
+    // Setter for the storage format of the memory areas (synaptic and of the membrane)
+    pub fn set_storage_format(&mut self, format: StorageFormat) {
+        self.lif.set_storage_format(format);
+        self.synaptic_current = format.quantize(self.synaptic_current);
+        self.synaptic_tau = format.quantize(self.synaptic_tau);
+    }
+
+    // Integration of the input at the time instant t: the inhibited neuron doesn't spike (see process_inhibited_input)
+    fn integrate(&mut self, time: u64, weighted_sum: f64, fault: Option<InjectedFault>, inhibited: bool) -> u8 {
+
+        // Get the parameters of the neuron checking during runtime if there is a fault to inject
+        let (mut synaptic_current, synaptic_tau, tau, dt, ts) = self.read_memory_areas(fault, time);
+
+        // The layer processes the neuron only when it receives an input spike event
+        // => the charge given to the membrane by the synaptic current in the time steps elapsed since
+        //    the last processed instant is accumulated with the same discrete-time dynamics
+        let alpha = (-dt/synaptic_tau).exp();
+        let beta = (-dt/tau).exp();
+        let mut charge = 0.0;
+        for _ in ts..time {
+            synaptic_current *= alpha;
+            charge = charge * beta + synaptic_current;
+        }
+        synaptic_current += weighted_sum;
+        charge += weighted_sum;
+
+        // update the synaptic current to be stored in the memory area (in the storage format)
+        let format = self.lif.get_storage_format();
+        let synaptic_current = format.quantize(synaptic_current);
+        match fault {
+            Some(f) if f.component_type == ComponentType::SynapticCurrent => self.synaptic_current = f.apply_fault_in_format(synaptic_current, format, time),
+            _ => self.synaptic_current = synaptic_current,
+        }
+
+        // The membrane integrates the charge (the faults in the other components are injected by the Lif)
+        if inhibited {
+            self.lif.process_inhibited_input(time, charge, fault);
+            return 0;
+        }
+        self.lif.process_input(time, charge, fault)
+    }
+}
+
+impl Neuron for CubaLif {
+    /**
+    Computes the synaptic current and the membrane potential of the neuron at the time instant t
+    and returns 1 if the neuron spikes, 0 otherwise.
+    - i_syn[t] = alpha * i_syn[t-1] + weighted_sum[t], with alpha = exp(-dt/tau_syn)
+    - v_mem[t] = beta * v_mem[t-1] + i_syn[t], with beta = exp(-dt/tau)
+    - @param time (u64)
+    - @param weighted_sum (f64): added to the synaptic current
+    - @return u8 (0/1)
+     */
+    fn process_input(&mut self, time: u64, weighted_sum: f64, fault: Option<InjectedFault>) -> u8 {
+        self.integrate(time, weighted_sum, fault, false)
+    }
+
+    fn process_inhibited_input(&mut self, time: u64, weighted_sum: f64, fault: Option<InjectedFault>) {
+        self.integrate(time, weighted_sum, fault, true);
+    }
+
+    fn get_membrane_potential(&self) -> f64 {
+        self.lif.get_v_mem()
+    }
+
+    // Reset the synaptic current to 0 and the membrane to its initial state
+    fn initialize(&mut self) {
+        self.synaptic_current = 0.0;
+        self.lif.initialize();
+    }
+
+    // Get the value of the parameter to fault
+    fn get_parameter_to_fault(&mut self, component_type: ComponentType) -> &mut dyn StoredValue {
+        match component_type {
+            ComponentType::SynapticCurrent      => &mut self.synaptic_current,
+            ComponentType::SynapticTau          => &mut self.synaptic_tau,
+            _                                   => self.lif.get_parameter_to_fault(component_type),
+        }
+    }
+
+    fn get_fault_surface(&self) -> Vec<FaultableComponent> {
+        let width = self.lif.get_storage_format().get_width();
+        let mut surface = self.lif.get_fault_surface();
+        surface.push(FaultableComponent::new(ComponentType::SynapticCurrent, "Synaptic current", width, false));
+        surface.push(FaultableComponent::new(ComponentType::SynapticTau, "Synaptic tau", width, true));
+        surface
+    }
+
+    fn get_storage_format(&self) -> StorageFormat {
+        self.lif.get_storage_format()
+    }
+}
+
+impl CubaLif {
+    fn read_memory_areas(&mut self, fault: Option<InjectedFault>, time: u64) -> (f64, f64, f64, f64, u64) {
+        // Get the parameters of the neuron
+        // => In this way we are not soiling the original values of the built network with the fault,
+        //    since it's sufficient to inject the fault only when the neuron is processed
+        let mut synaptic_current = self.synaptic_current;
+        let mut synaptic_tau = self.synaptic_tau;
+        let mut tau = self.lif.get_tau();
+        let mut dt = self.lif.get_dt();
+        let mut ts = self.lif.get_ts();
+        let format = self.lif.get_storage_format();
+
+        if let Some(injected_fault) = fault {
+            if injected_fault.component_category == ComponentCategory::MemoryArea {
+                match injected_fault.component_type {
+                    ComponentType::SynapticCurrent      => synaptic_current = injected_fault.apply_fault_in_format(synaptic_current, format, time),
+                    ComponentType::SynapticTau          => synaptic_tau = injected_fault.apply_fault_in_format(synaptic_tau, format, time),
+                    ComponentType::Tau                  => tau = injected_fault.apply_fault_in_format(tau, format, time),
+                    ComponentType::DT                   => dt = injected_fault.apply_fault_in_format(dt, format, time),
+                    ComponentType::Ts                   => ts = injected_fault.apply_fault(ts, time),
+                    _                                   => {}
+                }
+            }
+        }
+        (synaptic_current, synaptic_tau, tau, dt, ts)
+    }
+}
@@ -0,0 +1,188 @@
+use crate::network::neuron::neuron::Neuron;
+use crate::network::neuron::lif::Lif;
+use crate::network::fixed_point::{Fixed, QFormat};
+use crate::network::storage_format::StorageFormat;
+use crate::resilience::components::{ComponentType, ComponentCategory, FaultableComponent};
+use crate::resilience::fault_models::{InjectedFault, ApplyFault, StoredValue};
+// Implements the Neuron trait with the leaky integrate-and-fire (LIF) model in fixed-point arithmetic (Qm.n),
+// as in the hardware accelerators: the memory areas are stored as integers and the operations saturate.
+
+#[derive(Debug, Clone)]
+pub struct FixedLif {
+    reset_potential: Fixed, // reset potential
+    resting_potential: Fixed, // resting potential
+    threshold: Fixed, // threshold potential
+    membrane_potential: Fixed, // membrane potential
+    beta: Fixed, // decay factor of the membrane potential in each time step (exp(-dt/tau))
+    ts: u64, // last time instant where a spike has been received
+    format: QFormat // fixed-point format of the memory areas
+}
+
+impl FixedLif {
+    pub fn new(reset_potential: f64, resting_potential: f64, threshold: f64, beta: f64, format: QFormat) -> Self {
+        FixedLif {
+            reset_potential: Fixed::from_f64(reset_potential, format),
+            resting_potential: Fixed::from_f64(resting_potential, format),
+            threshold: Fixed::from_f64(threshold, format),
+            membrane_potential: Fixed::from_f64(resting_potential, format), // at the beginning the membrane potential is equal to the resting potential
+            beta: Fixed::from_f64(beta, format),
+            ts: 0, // starting from time instant 0
+            format
+        }
+    }
+
+    // Quantization of a Lif neuron in the given format
+    pub fn from_lif(lif: &Lif, format: QFormat) -> Self {
+        FixedLif::new(lif.get_v_reset(), lif.get_v_rest(), lif.get_v_th(), (-lif.get_dt() / lif.get_tau()).exp(), format)
+    }
+
+    // Getters for the FixedLif parameters (as real values)
+    pub fn get_v_reset(&self) -> f64 { self.reset_potential.to_f64() }
+    pub fn get_v_rest(&self) -> f64 { self.resting_potential.to_f64() }
+    pub fn get_v_th(&self) -> f64 { self.threshold.to_f64() }
+    pub fn get_v_mem(&self) -> f64 { self.membrane_potential.to_f64() }
+    pub fn get_beta(&self) -> f64 { self.beta.to_f64() }
+    pub fn get_ts(&self) -> u64 { self.ts }
+    pub fn get_format(&self) -> QFormat { self.format }
+
+    // Integration of the input at the time instant t: the inhibited neuron doesn't spike (see process_inhibited_input)
+    fn integrate(&mut self, time: u64, weighted_sum: f64, fault: Option<InjectedFault>, inhibited: bool) -> u8 {
+
+        // Get the parameters of the neuron checking during runtime if there is a fault to inject
+        let (reset_potential, resting_potential, threshold, membrane_potential, beta, ts)
+            = self.read_memory_areas(fault, time);
+
+        // Possible fault in the adder/multiplier (in the integer encoding of the weighted sum)
+        let mut weighted_sum = Fixed::from_f64(weighted_sum, self.format);
+        if let Some(f) = fault {
+            if f.component_type == ComponentType::Adder || f.component_type == ComponentType::Multiplier {
+                weighted_sum = f.apply_fault(weighted_sum, time);
+            }
+        }
+
+        // Decay of the membrane potential in the time steps elapsed since the last processed instant:
+        // one saturating multiplication by beta for each time step, as the hardware does
+        let mut difference = membrane_potential.saturating_sub(resting_potential);
+        for _ in ts..time {
+            if difference.get_raw() == 0 {
+                break;
+            }
+            difference = difference.saturating_mul(beta);
+        }
+        let mp = resting_potential.saturating_add(difference).saturating_add(weighted_sum);
+
+        // update the variables to be stored each step in the memory areas
+        match fault {
+            Some(f) if f.component_type == ComponentType::MembranePotential => self.membrane_potential = f.apply_fault(mp, time),
+            _ => self.membrane_potential = mp,
+        }
+        self.ts = time;
+
+        // Check if the neuron spikes
+        let mut output_spike: u8;
+        if !inhibited && self.membrane_potential.get_raw() > threshold.get_raw() {
+            self.membrane_potential = reset_potential;
+            output_spike = 1; // spike only if v_mem > v_th
+        }
+        else {
+            output_spike = 0;
+        }
+
+        // Possible fault in the threshold comparator
+        if let Some(f) = fault {
+            if f.component_type == ComponentType::ThresholdComparator {
+                output_spike = f.apply_fault(output_spike, time);
+            }
+        }
+
+        output_spike
+    }
+}
+
+impl Neuron for FixedLif {
+    /**
+    Computes the membrane potential of the neuron at the time instant t
+    and returns 1 if the neuron spikes, 0 otherwise.
+    - v_mem[t] = v_rest + (v_mem[ts] - v_rest) * beta^(t-ts) + weighted_sum
+    - @param time (u64)
+    - @param weighted_sum (f64): quantized in the format of the neuron (with saturation)
+    - @return u8 (0/1)
+     */
+    fn process_input(&mut self, time: u64, weighted_sum: f64, fault: Option<InjectedFault>) -> u8 {
+        self.integrate(time, weighted_sum, fault, false)
+    }
+
+    fn process_inhibited_input(&mut self, time: u64, weighted_sum: f64, fault: Option<InjectedFault>) {
+        self.integrate(time, weighted_sum, fault, true);
+    }
+
+    fn get_membrane_potential(&self) -> f64 {
+        self.membrane_potential.to_f64()
+    }
+
+    // Reset the membrane potential to the resting potential and the time instant to 0
+    fn initialize(&mut self) {
+        self.membrane_potential = self.resting_potential;
+        self.ts = 0;
+    }
+
+    // Get the value of the parameter to fault
+    fn get_parameter_to_fault(&mut self, component_type: ComponentType) -> &mut dyn StoredValue {
+        match component_type {
+            ComponentType::ResetPotential       => &mut self.reset_potential,
+            ComponentType::RestingPotential     => &mut self.resting_potential,
+            ComponentType::Threshold            => &mut self.threshold,
+            ComponentType::MembranePotential    => &mut self.membrane_potential,
+            ComponentType::Beta                 => &mut self.beta,
+            ComponentType::Ts                   => &mut self.ts,
+            _                                   => panic!("Error: the component type is not valid for the fixed-point LIF neuron"),
+        }
+    }
+
+    fn get_fault_surface(&self) -> Vec<FaultableComponent> {
+        let width = self.format.get_width();
+        let mut surface = vec![
+            FaultableComponent::new(ComponentType::ResetPotential, "Reset potential", width, true),
+            FaultableComponent::new(ComponentType::RestingPotential, "Resting potential", width, true),
+            FaultableComponent::new(ComponentType::Threshold, "Threshold", width, true),
+            FaultableComponent::new(ComponentType::MembranePotential, "Membrane potential", width, false),
+            FaultableComponent::new(ComponentType::Ts, "Ts", 64, false),
+            FaultableComponent::new(ComponentType::Beta, "Beta", width, true),
+        ];
+        surface.extend(FaultableComponent::processing_blocks(width));
+        surface
+    }
+
+    fn get_storage_format(&self) -> StorageFormat {
+        StorageFormat::Fixed(self.format)
+    }
+}
+
+impl FixedLif {
+    fn read_memory_areas(&mut self, fault: Option<InjectedFault>, time: u64) -> (Fixed, Fixed, Fixed, Fixed, Fixed, u64) {
+        // Get the parameters of the neuron
+        // => In this way we are not soiling the original values of the built network with the fault,
+        //    since it's sufficient to inject the fault only when the neuron is processed
+        let mut reset_potential = self.reset_potential;
+        let mut resting_potential = self.resting_potential;
+        let mut threshold = self.threshold;
+        let mut membrane_potential = self.membrane_potential;
+        let mut beta = self.beta;
+        let mut ts = self.ts;
+
+        if let Some(injected_fault) = fault {
+            if injected_fault.component_category == ComponentCategory::MemoryArea {
+                match injected_fault.component_type {
+                    ComponentType::ResetPotential       => reset_potential = injected_fault.apply_fault(reset_potential, time),
+                    ComponentType::RestingPotential     => resting_potential = injected_fault.apply_fault(resting_potential, time),
+                    ComponentType::Threshold            => threshold = injected_fault.apply_fault(threshold, time),
+                    ComponentType::MembranePotential    => membrane_potential = injected_fault.apply_fault(membrane_potential, time),
+                    ComponentType::Beta                 => beta = injected_fault.apply_fault(beta, time),
+                    ComponentType::Ts                   => ts = injected_fault.apply_fault(ts, time),
+                    _                                   => {}
+                }
+            }
+        }
+        (reset_potential, resting_potential, threshold, membrane_potential, beta, ts)
+    }
+}
@@ -0,0 +1,198 @@
+use crate::network::neuron::neuron::Neuron;
+use crate::resilience::components::{ComponentType, ComponentCategory, FaultableComponent};
+use crate::resilience::fault_models::{InjectedFault, ApplyFault, StoredValue};
+// Implements the Neuron trait with the Izhikevich model.
+
+#[derive(Debug, Clone)]
+pub struct Izhikevich {
+    a: f64, // time scale of the recovery variable
+    b: f64, // sensitivity of the recovery variable to the subthreshold fluctuations of the membrane potential
+    c: f64, // after-spike reset value of the membrane potential
+    d: f64, // after-spike increment of the recovery variable
+    threshold: f64, // peak potential at which the spike is detected
+    membrane_potential: f64, // membrane potential (v)
+    recovery_variable: f64, // recovery variable (u)
+    ts: u64, // last time instant where a spike has been received
+    dt: f64 // quantization parameter that defines the amplitude of the interval between two consecutive instants
+}
+
+impl Izhikevich {
+    pub fn new(a: f64, b: f64, c: f64, d: f64, threshold: f64, dt: f64) -> Self {
+        Izhikevich {
+            a,
+            b,
+            c,
+            d,
+            threshold,
+            membrane_potential: c, // at the beginning the membrane potential is equal to the reset potential
+            recovery_variable: b * c, // and the recovery variable is at its equilibrium value
+            ts: 0, // starting from time instant 0
+            dt
+        }
+    }
+
+    // Getters for the Izhikevich parameters
+    pub fn get_a(&self) -> f64 { self.a }
+    pub fn get_b(&self) -> f64 { self.b }
+    pub fn get_c(&self) -> f64 { self.c }
+    pub fn get_d(&self) -> f64 { self.d }
+    pub fn get_v_th(&self) -> f64 { self.threshold }
+    pub fn get_v_mem(&self) -> f64 { self.membrane_potential }
+    pub fn get_u(&self) -> f64 { self.recovery_variable }
+    pub fn get_ts(&self) -> u64 { self.ts }
+
+    // Integration of the input at the time instant t: the inhibited neuron doesn't spike (see process_inhibited_input)
+    fn integrate(&mut self, time: u64, mut weighted_sum: f64, fault: Option<InjectedFault>, inhibited: bool) -> u8 {
+
+        // Get the parameters of the neuron checking during runtime if there is a fault to inject
+        let (a, b, c, d, threshold, mut membrane_potential, mut recovery_variable, dt, ts)
+            = self.read_memory_areas(fault, time);
+
+        // Possible fault in the adder/multiplier
+        if let Some(f) = fault {
+            if f.component_type == ComponentType::Adder || f.component_type == ComponentType::Multiplier {
+                weighted_sum = f.apply_fault(weighted_sum, time);
+            }
+        }
+
+        // Integrate the dynamics over the time steps elapsed since the last processed instant (forward Euler method)
+        // => The layer processes the neuron only when it receives an input spike event, so if the peak
+        //    is reached in between, the integration stops and the spike is emitted at the current instant
+        for _ in ts..time {
+            if membrane_potential >= threshold {
+                break;
+            }
+            let dv = 0.04 * membrane_potential * membrane_potential + 5.0 * membrane_potential + 140.0 - recovery_variable;
+            let du = a * (b * membrane_potential - recovery_variable);
+            membrane_potential += dv * dt;
+            recovery_variable += du * dt;
+        }
+        let mp = membrane_potential + weighted_sum;
+
+        // update the variables to be stored each step in the memory areas
+        match fault {
+            Some(f) if f.component_type == ComponentType::MembranePotential => self.membrane_potential = f.apply_fault(mp, time),
+            _ => self.membrane_potential = mp,
+        }
+        match fault {
+            Some(f) if f.component_type == ComponentType::RecoveryVariable => self.recovery_variable = f.apply_fault(recovery_variable, time),
+            _ => self.recovery_variable = recovery_variable,
+        }
+        self.ts = time;
+
+        // Check if the neuron spikes
+        let mut output_spike: u8;
+        if !inhibited && self.membrane_potential >= threshold {
+            self.membrane_potential = c;
+            self.recovery_variable += d;
+            output_spike = 1; // spike only if v_mem >= v_th
+        }
+        else {
+            output_spike = 0;
+        }
+
+        // Possible fault in the threshold comparator
+        if let Some(f) = fault {
+            if f.component_type == ComponentType::ThresholdComparator {
+                output_spike = f.apply_fault(output_spike, time);
+            }
+        }
+
+        output_spike
+    }
+}
+
+impl Neuron for Izhikevich {
+    /**
+    Integrates the membrane potential and the recovery variable of the neuron up to the time instant t
+    and returns 1 if the neuron spikes, 0 otherwise.
+    - @param time (u64)
+    - @param weighted_sum (f64): added directly to the membrane potential, as for the Lif neuron
+    - @return u8 (0/1)
+     */
+    fn process_input(&mut self, time: u64, weighted_sum: f64, fault: Option<InjectedFault>) -> u8 {
+        self.integrate(time, weighted_sum, fault, false)
+    }
+
+    fn process_inhibited_input(&mut self, time: u64, weighted_sum: f64, fault: Option<InjectedFault>) {
+        self.integrate(time, weighted_sum, fault, true);
+    }
+
+    fn get_membrane_potential(&self) -> f64 {
+        self.membrane_potential
+    }
+
+    // Reset the membrane potential and the recovery variable to their initial values and the time instant to 0
+    fn initialize(&mut self) {
+        self.membrane_potential = self.c;
+        self.recovery_variable = self.b * self.c;
+        self.ts = 0;
+    }
+
+    // Get the value of the parameter to fault
+    fn get_parameter_to_fault(&mut self, component_type: ComponentType) -> &mut dyn StoredValue {
+        match component_type {
+            ComponentType::RecoveryTimeScale    => &mut self.a,
+            ComponentType::RecoverySensitivity  => &mut self.b,
+            ComponentType::ResetPotential       => &mut self.c,
+            ComponentType::RecoveryReset        => &mut self.d,
+            ComponentType::Threshold            => &mut self.threshold,
+            ComponentType::MembranePotential    => &mut self.membrane_potential,
+            ComponentType::RecoveryVariable     => &mut self.recovery_variable,
+            ComponentType::DT                   => &mut self.dt,
+            ComponentType::Ts                   => &mut self.ts,
+            _                                   => panic!("Error: the component type is not valid for the Izhikevich neuron"),
+        }
+    }
+
+    fn get_fault_surface(&self) -> Vec<FaultableComponent> {
+        let mut surface = vec![
+            FaultableComponent::new(ComponentType::ResetPotential, "Reset potential (c)", 64, true),
+            FaultableComponent::new(ComponentType::Threshold, "Threshold", 64, true),
+            FaultableComponent::new(ComponentType::MembranePotential, "Membrane potential", 64, false),
+            FaultableComponent::new(ComponentType::Ts, "Ts", 64, false),
+            FaultableComponent::new(ComponentType::DT, "dt", 64, true),
+            FaultableComponent::new(ComponentType::RecoveryVariable, "Recovery variable", 64, false),
+            FaultableComponent::new(ComponentType::RecoveryTimeScale, "Recovery time scale (a)", 64, true),
+            FaultableComponent::new(ComponentType::RecoverySensitivity, "Recovery sensitivity (b)", 64, true),
+            FaultableComponent::new(ComponentType::RecoveryReset, "Recovery reset (d)", 64, true),
+        ];
+        surface.extend(FaultableComponent::processing_blocks(64));
+        surface
+    }
+}
+
+impl Izhikevich {
+    fn read_memory_areas(&mut self, fault: Option<InjectedFault>, time: u64) -> (f64, f64, f64, f64, f64, f64, f64, f64, u64) {
+        // Get the parameters of the neuron
+        // => In this way we are not soiling the original values of the built network with the fault,
+        //    since it's sufficient to inject the fault only when the neuron is processed
+        let mut a = self.a;
+        let mut b = self.b;
+        let mut c = self.c;
+        let mut d = self.d;
+        let mut threshold = self.threshold;
+        let mut membrane_potential = self.membrane_potential;
+        let mut recovery_variable = self.recovery_variable;
+        let mut dt = self.dt;
+        let mut ts = self.ts;
+
+        if let Some(injected_fault) = fault {
+            if injected_fault.component_category == ComponentCategory::MemoryArea {
+                match injected_fault.component_type {
+                    ComponentType::RecoveryTimeScale    => a = injected_fault.apply_fault(a, time),
+                    ComponentType::RecoverySensitivity  => b = injected_fault.apply_fault(b, time),
+                    ComponentType::ResetPotential       => c = injected_fault.apply_fault(c, time),
+                    ComponentType::RecoveryReset        => d = injected_fault.apply_fault(d, time),
+                    ComponentType::Threshold            => threshold = injected_fault.apply_fault(threshold, time),
+                    ComponentType::MembranePotential    => membrane_potential = injected_fault.apply_fault(membrane_potential, time),
+                    ComponentType::RecoveryVariable     => recovery_variable = injected_fault.apply_fault(recovery_variable, time),
+                    ComponentType::DT                   => dt = injected_fault.apply_fault(dt, time),
+                    ComponentType::Ts                   => ts = injected_fault.apply_fault(ts, time),
+                    _                                   => {}
+                }
+            }
+        }
+        (a, b, c, d, threshold, membrane_potential, recovery_variable, dt, ts)
+    }
+}
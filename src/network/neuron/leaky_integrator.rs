@@ -0,0 +1,127 @@
+use crate::network::neuron::neuron::Neuron;
+use crate::resilience::components::{ComponentType, ComponentCategory, FaultableComponent};
+use crate::resilience::fault_models::{InjectedFault, ApplyFault, StoredValue};
+// Implements the Neuron trait with a non-spiking leaky integrator, used in the readout (output) layer of the
+// trained models whose final membrane potential is the logit of each class.
+// The weighted input spikes are integrated in the accumulator, which decays in each time step,
+// without threshold and reset: the neuron never spikes, and its output is the accumulator at the end of the input.
+
+#[derive(Debug, Clone)]
+pub struct LeakyIntegrator {
+    accumulator: f64, // membrane potential (integrated weighted input)
+    decay: f64, // decay factor of the accumulator in each time step
+    ts: u64 // last time instant in which the accumulator was updated
+}
+
+impl LeakyIntegrator {
+    pub fn new(decay: f64) -> Self {
+        if !(0.0..=1.0).contains(&decay) {
+            panic!("Error: The decay of a leaky integrator should be between 0 and 1");
+        }
+        LeakyIntegrator {
+            accumulator: 0.0, // at the beginning there is no integrated input
+            decay,
+            ts: 0
+        }
+    }
+
+    // Getters for the LeakyIntegrator parameters
+    pub fn get_accumulator(&self) -> f64 { self.accumulator }
+    pub fn get_decay(&self) -> f64 { self.decay }
+    pub fn get_ts(&self) -> u64 { self.ts }
+}
+
+impl Neuron for LeakyIntegrator {
+    /**
+    Computes the accumulator of the neuron at the time instant t (the neuron never spikes).
+    - acc[t] = decay^(t - ts) * acc[ts] + weighted_sum[t]
+    - @param time (u64)
+    - @param weighted_sum (f64): added to the accumulator
+    - @return u8 (always 0)
+     */
+    fn process_input(&mut self, time: u64, weighted_sum: f64, fault: Option<InjectedFault>) -> u8 {
+
+        // Get the parameters of the neuron checking during runtime if there is a fault to inject
+        let (accumulator, decay) = self.read_memory_areas(fault, time);
+
+        // Possible fault in the multiplier (decay of the accumulator) or in the adder (integration of the input)
+        let mut decayed = accumulator * decay.powi((time - self.ts) as i32);
+        if let Some(f) = fault.filter(|f| f.component_type == ComponentType::Multiplier) {
+            decayed = f.apply_fault(decayed, time);
+        }
+        let mut sum = decayed + weighted_sum;
+        if let Some(f) = fault.filter(|f| f.component_type == ComponentType::Adder) {
+            sum = f.apply_fault(sum, time);
+        }
+
+        // update the accumulator to be stored in the memory area
+        match fault {
+            Some(f) if f.component_type == ComponentType::Accumulator => self.accumulator = f.apply_fault(sum, time),
+            _ => self.accumulator = sum,
+        }
+        self.ts = time;
+
+        0
+    }
+
+    // The neuron never spikes, so the inhibition doesn't change its processing
+    fn process_inhibited_input(&mut self, time: u64, weighted_sum: f64, fault: Option<InjectedFault>) {
+        self.process_input(time, weighted_sum, fault);
+    }
+
+    // The accumulator decays until the last time instant of the input
+    fn complete_input(&mut self, time: u64) {
+        self.accumulator *= self.decay.powi((time - self.ts) as i32);
+        self.ts = time;
+    }
+
+    fn get_membrane_potential(&self) -> f64 {
+        self.accumulator
+    }
+
+    // Reset the accumulator to 0 and the time instant to 0
+    fn initialize(&mut self) {
+        self.accumulator = 0.0;
+        self.ts = 0;
+    }
+
+    // Get the value of the parameter to fault
+    fn get_parameter_to_fault(&mut self, component_type: ComponentType) -> &mut dyn StoredValue {
+        match component_type {
+            ComponentType::Accumulator          => &mut self.accumulator,
+            ComponentType::Decay                => &mut self.decay,
+            _                                   => panic!("Error: the component type is not valid for the leaky integrator"),
+        }
+    }
+
+    // The neuron has no threshold comparator, since it never spikes
+    fn get_fault_surface(&self) -> Vec<FaultableComponent> {
+        vec![
+            FaultableComponent::new(ComponentType::Accumulator, "Accumulator", 64, false),
+            FaultableComponent::new(ComponentType::Decay, "Decay", 64, true),
+            FaultableComponent::new(ComponentType::Adder, "Adder", 64, false),
+            FaultableComponent::new(ComponentType::Multiplier, "Multiplier", 64, false),
+        ]
+    }
+}
+
+impl LeakyIntegrator {
+    fn read_memory_areas(&mut self, fault: Option<InjectedFault>, time: u64) -> (f64, f64) {
+        // Get the parameters of the neuron
+        // => In this way we are not soiling the original values of the built network with the fault,
+        //    since it's sufficient to inject the fault only when the neuron is processed
+        let mut accumulator = self.accumulator;
+        let mut decay = self.decay;
+
+        if let Some(injected_fault) = fault {
+            if injected_fault.component_category == ComponentCategory::MemoryArea {
+                match injected_fault.component_type {
+                    ComponentType::Accumulator          => accumulator = injected_fault.apply_fault(accumulator, time),
+                    ComponentType::Decay                => decay = injected_fault.apply_fault(decay, time),
+                    _                                   => {}
+                }
+            }
+        }
+        (accumulator, decay)
+    }
+}
@@ -1,8 +1,17 @@
 use crate::network::neuron::neuron::Neuron;
-use crate::resilience::components::{ComponentType, ComponentCategory};
-use crate::resilience::fault_models::{InjectedFault, ApplyFault};
+use crate::resilience::components::{ComponentType, ComponentCategory, FaultableComponent};
+use crate::resilience::fault_models::{InjectedFault, ApplyFault, StoredValue};
+use crate::network::storage_format::StorageFormat;
 // Implements the Neuron trait with the leaky integrate-and-fire (LIF) model.
 
+// Mechanism used to reset the membrane potential after a spike
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetMode {
+    ToValue,    // the membrane potential is set to the reset potential (snnTorch "zero" if the reset potential is 0)
+    Subtract,   // the threshold is subtracted from the membrane potential (snnTorch default)
+    None,       // the membrane potential is not reset
+}
+
 #[derive(Debug)]
 pub struct Lif {
     reset_potential: f64, // reset potential
@@ -11,7 +20,12 @@ pub struct Lif {
     membrane_potential: f64, // membrane potential
     tau: f64, // time constant
     ts: u64, // last time instant where a spike has been received
-    dt: f64 // quantization parameter that defines the amplitude of the interval between two consecutive instants
+    dt: f64, // quantization parameter that defines the amplitude of the interval between two consecutive instants
+    refractory_period: u64, // number of time steps after a spike during which the neuron cannot spike again
+    refractory_counter: u64, // number of time steps of the refractory period still to be waited
+    reset_mode: ResetMode, // mechanism used to reset the membrane potential after a spike
+    reset_pending: bool, // true if the threshold has to be subtracted in the next time step (reset by subtraction)
+    storage_format: StorageFormat // format in which the memory areas are stored (f64 by default)
 }
 
 impl Lif {
@@ -23,7 +37,12 @@ impl Lif {
             membrane_potential: resting_potential, // at the beginning the membrane potential is equal to the resting potential
             tau,
             ts: 0, // starting from time instant 0
-            dt
+            dt,
+            refractory_period: 0, // no refractory period by default
+            refractory_counter: 0,
+            reset_mode: ResetMode::ToValue, // reset to the reset potential by default
+            reset_pending: false,
+            storage_format: StorageFormat::F64
         }
     }
 
@@ -34,11 +53,32 @@ impl Lif {
     pub fn get_v_mem(&self) -> f64 { self.membrane_potential }
     pub fn get_tau(&self) -> f64 { self.tau }
     pub fn get_ts(&self) -> u64 { self.ts }
+    pub fn get_dt(&self) -> f64 { self.dt }
+    pub fn get_refractory_period(&self) -> u64 { self.refractory_period }
+    pub fn get_refractory_counter(&self) -> u64 { self.refractory_counter }
+    pub fn get_reset_mode(&self) -> ResetMode { self.reset_mode }
     
     // Setters for potential parameters of Lif
     pub fn set_v_reset(&mut self, new_v_reset: f64) -> () { self.reset_potential = new_v_reset }
     pub fn set_v_rest(&mut self, new_v_rest: f64) -> () { self.resting_potential = new_v_rest }
     pub fn set_v_th(&mut self, new_v_th: f64) -> () { self.threshold = new_v_th }
+
+    // Setter for the absolute refractory period (in time steps)
+    pub fn set_refractory_period(&mut self, new_refractory_period: u64) { self.refractory_period = new_refractory_period }
+
+    // Setter for the reset mechanism
+    pub fn set_reset_mode(&mut self, new_reset_mode: ResetMode) { self.reset_mode = new_reset_mode }
+
+    // Setter for the storage format: the memory areas are rounded to the nearest representable value
+    pub fn set_storage_format(&mut self, format: StorageFormat) {
+        self.storage_format = format;
+        self.reset_potential = format.quantize(self.reset_potential);
+        self.resting_potential = format.quantize(self.resting_potential);
+        self.threshold = format.quantize(self.threshold);
+        self.membrane_potential = format.quantize(self.membrane_potential);
+        self.tau = format.quantize(self.tau);
+        self.dt = format.quantize(self.dt);
+    }
 }
 
 impl Neuron for Lif {
@@ -49,37 +89,137 @@ impl Neuron for Lif {
     - @param weights_sum (f64) #to_do: check if it is correct
     - @return u8 (0/1)
      */
-    fn process_input(&mut self, time: u64, mut weighted_sum: f64, fault: Option<InjectedFault>) -> u8 {
+    fn process_input(&mut self, time: u64, weighted_sum: f64, fault: Option<InjectedFault>) -> u8 {
+        self.integrate_and_fire(time, weighted_sum, fault, 0.0)
+    }
+
+    // The inhibited neuron has an infinite threshold, so the membrane integrates the input without the reset
+    fn process_inhibited_input(&mut self, time: u64, weighted_sum: f64, fault: Option<InjectedFault>) {
+        self.integrate_and_fire(time, weighted_sum, fault, f64::INFINITY);
+    }
+    
+    fn get_membrane_potential(&self) -> f64 {
+        self.membrane_potential
+    }
+
+    // Reset the membrane potential to the resting potential and the time instant to 0
+    fn initialize(&mut self) {
+        self.membrane_potential = self.resting_potential;
+        self.ts = 0;
+        self.refractory_counter = 0;
+        self.reset_pending = false;
+    }
+
+    // Get the value of the parameter to fault
+    fn get_parameter_to_fault(&mut self, component_type: ComponentType) -> &mut dyn StoredValue{
+        match component_type {
+            ComponentType::ResetPotential       => &mut self.reset_potential,
+            ComponentType::RestingPotential     => &mut self.resting_potential,
+            ComponentType::Threshold            => &mut self.threshold,
+            ComponentType::MembranePotential    => &mut self.membrane_potential,
+            ComponentType::Tau                  => &mut self.tau,
+            ComponentType::DT                   => &mut self.dt,
+            ComponentType::Ts                   => &mut self.ts,
+            ComponentType::RefractoryPeriod     => &mut self.refractory_period,
+            ComponentType::RefractoryCounter    => &mut self.refractory_counter,
+            _                                   => panic!("Error: the component type is not valid for the LIF neuron"),
+        }   
+    }
+
+    // Ts and the refractory period and counter are integer memory areas (64 bits), faulted during the processing phase.
+    // With the reset by subtraction the reset path has a single-bit flag, set by a spike and cleared when the threshold is subtracted
+    fn get_fault_surface(&self) -> Vec<FaultableComponent> {
+        let width = self.storage_format.get_width();
+        let mut surface = vec![
+            FaultableComponent::new(ComponentType::ResetPotential, "Reset potential", width, true),
+            FaultableComponent::new(ComponentType::RestingPotential, "Resting potential", width, true),
+            FaultableComponent::new(ComponentType::Threshold, "Threshold", width, true),
+            FaultableComponent::new(ComponentType::MembranePotential, "Membrane potential", width, false),
+            FaultableComponent::new(ComponentType::Tau, "Tau", width, true),
+            FaultableComponent::new(ComponentType::Ts, "Ts", 64, false),
+            FaultableComponent::new(ComponentType::DT, "dt", width, true),
+            FaultableComponent::new(ComponentType::RefractoryPeriod, "Refractory period", 64, false),
+            FaultableComponent::new(ComponentType::RefractoryCounter, "Refractory counter", 64, false),
+        ];
+        if self.reset_mode == ResetMode::Subtract {
+            surface.push(FaultableComponent::new(ComponentType::ResetFlag, "Reset flag", 1, false));
+        }
+        surface.extend(FaultableComponent::processing_blocks(width));
+        surface
+    }
+
+    fn get_storage_format(&self) -> StorageFormat {
+        self.storage_format
+    }
+
+}
+
+impl Lif {
+    /**
+    Computes the membrane potential of the neuron at the time instant t and returns 1 if the neuron spikes, 0 otherwise,
+    comparing the membrane potential with the threshold raised by the given offset (adaptive threshold neurons).
+     */
+    pub fn integrate_and_fire(&mut self, time: u64, mut weighted_sum: f64, fault: Option<InjectedFault>, threshold_offset: f64) -> u8 {
 
         // Get the parameters of the neuron checking during runtime if there is a fault to inject
         // => In this way we are not soiling the original values of the built network with the fault,
         //    since it's sufficient to inject the fault only when the neuron is processed
-        let (reset_potential,resting_potential, threshold, membrane_potential, tau, dt, ts)
+        let (reset_potential,resting_potential, threshold, membrane_potential, tau, dt, ts, refractory_period, refractory_counter)
             = self.read_memory_areas(fault, time);
 
         // Possible fault in the adder/multiplier
         // #to_do: CHECK CORRECTNESS !!!
         if fault.is_some() && (fault.unwrap().component_type == ComponentType::Adder || fault.unwrap().component_type == ComponentType::Multiplier) {
-            weighted_sum = fault.unwrap().apply_fault(weighted_sum, time);
+            weighted_sum = fault.unwrap().apply_fault_in_format(weighted_sum, self.storage_format, time);
         }
 
+        // Check if the neuron is still in its refractory period at the time instant t
+        // => the input is discarded and the membrane potential is held at the reset potential
+        //    (with the reset by subtraction or without reset, at its value after the spike:
+        //     the pending subtraction of the threshold is applied at the end of the refractory period)
+        // (a faulted ts after the time instant t means that no time has elapsed since the last update)
+        let elapsed_steps = time.saturating_sub(ts);
+        if refractory_counter > 0 && elapsed_steps <= refractory_counter {
+            self.membrane_potential = match self.reset_mode {
+                ResetMode::ToValue => reset_potential,
+                _ => membrane_potential,
+            };
+            self.ts = time;
+            self.update_refractory_counter(refractory_counter - elapsed_steps, fault, time);
+            return 0;
+        }
+        self.update_refractory_counter(0, fault, time);
+
         // Compute the membrane potential at the time instant t
         let mut output_spike: u8;
-        let delta_t = ((time - ts) as f64) * dt; // time interval between two input spikes
+        let delta_t = (elapsed_steps as f64) * dt; // time interval between two input spikes
         let exponential = (-delta_t/tau) as f64;
-        let mp = resting_potential + (membrane_potential - resting_potential) * exponential.exp() + weighted_sum;
+        let mut mp = resting_potential + (membrane_potential - resting_potential) * exponential.exp() + weighted_sum;
+
+        // Reset by subtraction: as in snnTorch, the threshold is subtracted in the time step
+        // following the spike, and then decays with the membrane potential up to the time instant t
+        if self.read_reset_flag(fault, time) {
+            mp -= threshold * (-(delta_t - dt)/tau).exp();
+        }
+        self.update_reset_flag(false, fault, time);
 
-        // update the variables to be stored each step in the memory areas
+        // update the variables to be stored each step in the memory areas (in the storage format)
+        let mp = self.storage_format.quantize(mp);
         if fault.is_some() && fault.unwrap().component_type == ComponentType::MembranePotential{
-            self.membrane_potential = fault.unwrap().apply_fault(mp, time);
+            self.membrane_potential = fault.unwrap().apply_fault_in_format(mp, self.storage_format, time);
         } else {
             self.membrane_potential = mp;
         }
         self.ts = time;
 
         // Check if the neuron spikes
-        if self.membrane_potential > threshold {
-            self.membrane_potential = reset_potential;
+        if self.membrane_potential > threshold + threshold_offset {
+            match self.reset_mode {
+                ResetMode::ToValue  => self.membrane_potential = reset_potential,
+                ResetMode::Subtract => self.update_reset_flag(true, fault, time),
+                ResetMode::None     => {}
+            }
+            self.update_refractory_counter(refractory_period, fault, time);
             output_spike = 1; // spike only if v_mem > v_th
         }
         else {
@@ -96,31 +236,8 @@ impl Neuron for Lif {
 
         output_spike
     }
-    
-    // Reset the membrane potential to the resting potential and the time instant to 0
-    fn initialize(&mut self) {
-        self.membrane_potential = self.resting_potential;
-        self.ts = 0;
-    }
 
-    // Get the value of the parameter to fault
-    fn get_parameter_to_fault(&mut self, component_type: ComponentType) -> &mut f64{
-        match component_type {
-            ComponentType::ResetPotential       => &mut self.reset_potential,
-            ComponentType::RestingPotential     => &mut self.resting_potential,
-            ComponentType::Threshold            => &mut self.threshold,
-            ComponentType::MembranePotential    => &mut self.membrane_potential,
-            ComponentType::Tau                  => &mut self.tau,
-            ComponentType::DT                   => &mut self.dt,
-            //ComponentType::Ts                   => &mut self.ts,
-            _                                   => panic!("Error: the component type is not valid for the LIF neuron"),
-        }   
-    }
-
-}
-
-impl Lif {
-    fn read_memory_areas(&mut self, fault: Option<InjectedFault>, time: u64) -> (f64, f64, f64, f64, f64, f64, u64) {
+    fn read_memory_areas(&mut self, fault: Option<InjectedFault>, time: u64) -> (f64, f64, f64, f64, f64, f64, u64, u64, u64) {
         // Get the parameters of the neuron
         // => In this way we are not soiling the original values of the built network with the fault,
         //    since it's sufficient to inject the fault only when the neuron is processed
@@ -131,22 +248,50 @@ impl Lif {
         let mut tau = self.tau;
         let mut ts = self.ts;
         let mut dt = self.dt;
+        let mut refractory_period = self.refractory_period;
+        let mut refractory_counter = self.refractory_counter;
     
         if let Some(injected_fault) = fault {
             if injected_fault.component_category == ComponentCategory::MemoryArea {
                 match injected_fault.component_type {
-                    ComponentType::ResetPotential       => reset_potential = injected_fault.apply_fault(reset_potential, time),
-                    ComponentType::RestingPotential     => resting_potential = injected_fault.apply_fault(resting_potential, time),
-                    ComponentType::Threshold            => threshold = injected_fault.apply_fault(threshold, time),
-                    ComponentType::MembranePotential    => membrane_potential = injected_fault.apply_fault(membrane_potential, time),
-                    ComponentType::Tau                  => tau = injected_fault.apply_fault(tau, time),
+                    ComponentType::ResetPotential       => reset_potential = injected_fault.apply_fault_in_format(reset_potential, self.storage_format, time),
+                    ComponentType::RestingPotential     => resting_potential = injected_fault.apply_fault_in_format(resting_potential, self.storage_format, time),
+                    ComponentType::Threshold            => threshold = injected_fault.apply_fault_in_format(threshold, self.storage_format, time),
+                    ComponentType::MembranePotential    => membrane_potential = injected_fault.apply_fault_in_format(membrane_potential, self.storage_format, time),
+                    ComponentType::Tau                  => tau = injected_fault.apply_fault_in_format(tau, self.storage_format, time),
                     ComponentType::Ts                   => ts = injected_fault.apply_fault(ts, time),
-                    ComponentType::DT                   => dt = injected_fault.apply_fault(dt, time),
+                    ComponentType::DT                   => dt = injected_fault.apply_fault_in_format(dt, self.storage_format, time),
+                    ComponentType::RefractoryPeriod     => refractory_period = injected_fault.apply_fault(refractory_period, time),
+                    ComponentType::RefractoryCounter    => refractory_counter = injected_fault.apply_fault(refractory_counter, time),
                     _                                   => {}
                 }
             }
         }
-        (reset_potential, resting_potential, threshold, membrane_potential, tau, dt, ts)
+        (reset_potential, resting_potential, threshold, membrane_potential, tau, dt, ts, refractory_period, refractory_counter)
+    }
+
+    // Read the reset flag (reset by subtraction), injecting the fault if it targets the flag
+    fn read_reset_flag(&self, fault: Option<InjectedFault>, time: u64) -> bool {
+        match fault {
+            Some(f) if f.component_type == ComponentType::ResetFlag => f.apply_fault(self.reset_pending as u8, time) == 1,
+            _ => self.reset_pending,
+        }
+    }
+
+    // Store the reset flag, injecting the fault if it targets the flag
+    fn update_reset_flag(&mut self, reset_pending: bool, fault: Option<InjectedFault>, time: u64) {
+        match fault {
+            Some(f) if f.component_type == ComponentType::ResetFlag => self.reset_pending = f.apply_fault(reset_pending as u8, time) == 1,
+            _ => self.reset_pending = reset_pending,
+        }
+    }
+
+    // Store the refractory counter, injecting the fault if it targets the counter
+    fn update_refractory_counter(&mut self, refractory_counter: u64, fault: Option<InjectedFault>, time: u64) {
+        match fault {
+            Some(f) if f.component_type == ComponentType::RefractoryCounter => self.refractory_counter = f.apply_fault(refractory_counter, time),
+            _ => self.refractory_counter = refractory_counter,
+        }
     }
 }
 
@@ -159,7 +304,12 @@ impl Clone for Lif {
             membrane_potential: self.membrane_potential,
             tau: self.tau,
             ts: self.ts,
-            dt: self.dt
+            dt: self.dt,
+            refractory_period: self.refractory_period,
+            refractory_counter: self.refractory_counter,
+            reset_mode: self.reset_mode,
+            reset_pending: self.reset_pending,
+            storage_format: self.storage_format
         }
     }
 }
\ No newline at end of file
@@ -1,2 +1,10 @@
 pub mod lif;
-pub mod neuron;
\ No newline at end of file
+pub mod izhikevich;
+pub mod adex;
+pub mod cuba_lif;
+pub mod fixed_lif;
+pub mod stochastic_lif;
+pub mod adaptive_lif;
+pub mod leaky_integrator;
+pub mod neuron;
+pub mod neuron_model;
\ No newline at end of file
@@ -1,9 +1,23 @@
-use crate::resilience::fault_models::InjectedFault;
-use crate::resilience::components::ComponentType;
+use crate::resilience::fault_models::{InjectedFault, StoredValue};
+use crate::resilience::components::{ComponentType, FaultableComponent};
+use crate::network::storage_format::StorageFormat;
 
 // generic trait Neuron that defines the interface for a neuron
 pub trait Neuron {
     fn process_input(&mut self, time: u64, weighted_sum: f64, fault: Option<InjectedFault>) -> u8;
+    // It processes the input as process_input while the neuron is inhibited (it lost the winner-take-all arbitration):
+    // the input is integrated, but the neuron doesn't spike, so its state isn't reset by the spike
+    fn process_inhibited_input(&mut self, time: u64, weighted_sum: f64, fault: Option<InjectedFault>);
     fn initialize(&mut self);
-    fn get_parameter_to_fault(&mut self, component_type: ComponentType) -> &mut f64;
+    // Membrane potential of the neuron after the last processed time instant (used by the membrane potential readout)
+    fn get_membrane_potential(&self) -> f64;
+    // It brings the state of the neuron to the last time instant of the input, after the last processed one
+    // (only the non-spiking neurons, whose final state is the output of the network, need it)
+    fn complete_input(&mut self, _time: u64) {}
+    fn get_parameter_to_fault(&mut self, component_type: ComponentType) -> &mut dyn StoredValue;
+    // Memory areas and processing blocks of the neuron that can be faulted (name, bit width, static/dynamic):
+    // the static memory areas must be accessible through get_parameter_to_fault
+    fn get_fault_surface(&self) -> Vec<FaultableComponent>;
+    // Format in which the memory areas of the neuron are stored (f64 by default)
+    fn get_storage_format(&self) -> StorageFormat { StorageFormat::F64 }
 }
\ No newline at end of file
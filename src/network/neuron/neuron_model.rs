@@ -0,0 +1,180 @@
+use crate::network::neuron::neuron::Neuron;
+use crate::network::neuron::lif::Lif;
+use crate::network::neuron::izhikevich::Izhikevich;
+use crate::network::neuron::adex::AdEx;
+use crate::network::neuron::cuba_lif::CubaLif;
+use crate::network::neuron::fixed_lif::FixedLif;
+use crate::network::neuron::stochastic_lif::StochasticLif;
+use crate::network::neuron::adaptive_lif::AdaptiveLif;
+use crate::network::neuron::leaky_integrator::LeakyIntegrator;
+use crate::resilience::components::{ComponentType, FaultableComponent};
+use crate::network::storage_format::StorageFormat;
+use crate::resilience::fault_models::{InjectedFault, StoredValue};
+// Wraps the available neuron models, so that the model of the network can be selected at runtime
+// (e.g. from the configuration file) while the SNN stays generic over a single Neuron type.
+
+#[derive(Debug, Clone)]
+pub enum NeuronModel {
+    Lif(Lif),
+    Izhikevich(Izhikevich),
+    AdEx(AdEx),
+    CubaLif(CubaLif),
+    FixedLif(FixedLif),
+    StochasticLif(StochasticLif),
+    AdaptiveLif(AdaptiveLif),
+    LeakyIntegrator(LeakyIntegrator),
+}
+
+impl Neuron for NeuronModel {
+    fn process_input(&mut self, time: u64, weighted_sum: f64, fault: Option<InjectedFault>) -> u8 {
+        match self {
+            NeuronModel::Lif(n) => n.process_input(time, weighted_sum, fault),
+            NeuronModel::Izhikevich(n) => n.process_input(time, weighted_sum, fault),
+            NeuronModel::AdEx(n) => n.process_input(time, weighted_sum, fault),
+            NeuronModel::CubaLif(n) => n.process_input(time, weighted_sum, fault),
+            NeuronModel::FixedLif(n) => n.process_input(time, weighted_sum, fault),
+            NeuronModel::StochasticLif(n) => n.process_input(time, weighted_sum, fault),
+            NeuronModel::AdaptiveLif(n) => n.process_input(time, weighted_sum, fault),
+            NeuronModel::LeakyIntegrator(n) => n.process_input(time, weighted_sum, fault),
+        }
+    }
+
+    fn process_inhibited_input(&mut self, time: u64, weighted_sum: f64, fault: Option<InjectedFault>) {
+        match self {
+            NeuronModel::Lif(n) => n.process_inhibited_input(time, weighted_sum, fault),
+            NeuronModel::Izhikevich(n) => n.process_inhibited_input(time, weighted_sum, fault),
+            NeuronModel::AdEx(n) => n.process_inhibited_input(time, weighted_sum, fault),
+            NeuronModel::CubaLif(n) => n.process_inhibited_input(time, weighted_sum, fault),
+            NeuronModel::FixedLif(n) => n.process_inhibited_input(time, weighted_sum, fault),
+            NeuronModel::StochasticLif(n) => n.process_inhibited_input(time, weighted_sum, fault),
+            NeuronModel::AdaptiveLif(n) => n.process_inhibited_input(time, weighted_sum, fault),
+            NeuronModel::LeakyIntegrator(n) => n.process_inhibited_input(time, weighted_sum, fault),
+        }
+    }
+
+    fn initialize(&mut self) {
+        match self {
+            NeuronModel::Lif(n) => n.initialize(),
+            NeuronModel::Izhikevich(n) => n.initialize(),
+            NeuronModel::AdEx(n) => n.initialize(),
+            NeuronModel::CubaLif(n) => n.initialize(),
+            NeuronModel::FixedLif(n) => n.initialize(),
+            NeuronModel::StochasticLif(n) => n.initialize(),
+            NeuronModel::AdaptiveLif(n) => n.initialize(),
+            NeuronModel::LeakyIntegrator(n) => n.initialize(),
+        }
+    }
+
+    fn get_parameter_to_fault(&mut self, component_type: ComponentType) -> &mut dyn StoredValue {
+        match self {
+            NeuronModel::Lif(n) => n.get_parameter_to_fault(component_type),
+            NeuronModel::Izhikevich(n) => n.get_parameter_to_fault(component_type),
+            NeuronModel::AdEx(n) => n.get_parameter_to_fault(component_type),
+            NeuronModel::CubaLif(n) => n.get_parameter_to_fault(component_type),
+            NeuronModel::FixedLif(n) => n.get_parameter_to_fault(component_type),
+            NeuronModel::StochasticLif(n) => n.get_parameter_to_fault(component_type),
+            NeuronModel::AdaptiveLif(n) => n.get_parameter_to_fault(component_type),
+            NeuronModel::LeakyIntegrator(n) => n.get_parameter_to_fault(component_type),
+        }
+    }
+
+    fn get_membrane_potential(&self) -> f64 {
+        match self {
+            NeuronModel::Lif(n) => n.get_membrane_potential(),
+            NeuronModel::Izhikevich(n) => n.get_membrane_potential(),
+            NeuronModel::AdEx(n) => n.get_membrane_potential(),
+            NeuronModel::CubaLif(n) => n.get_membrane_potential(),
+            NeuronModel::FixedLif(n) => n.get_membrane_potential(),
+            NeuronModel::StochasticLif(n) => n.get_membrane_potential(),
+            NeuronModel::AdaptiveLif(n) => n.get_membrane_potential(),
+            NeuronModel::LeakyIntegrator(n) => n.get_membrane_potential(),
+        }
+    }
+
+    fn complete_input(&mut self, time: u64) {
+        match self {
+            NeuronModel::Lif(n) => n.complete_input(time),
+            NeuronModel::Izhikevich(n) => n.complete_input(time),
+            NeuronModel::AdEx(n) => n.complete_input(time),
+            NeuronModel::CubaLif(n) => n.complete_input(time),
+            NeuronModel::FixedLif(n) => n.complete_input(time),
+            NeuronModel::StochasticLif(n) => n.complete_input(time),
+            NeuronModel::AdaptiveLif(n) => n.complete_input(time),
+            NeuronModel::LeakyIntegrator(n) => n.complete_input(time),
+        }
+    }
+
+    fn get_fault_surface(&self) -> Vec<FaultableComponent> {
+        match self {
+            NeuronModel::Lif(n) => n.get_fault_surface(),
+            NeuronModel::Izhikevich(n) => n.get_fault_surface(),
+            NeuronModel::AdEx(n) => n.get_fault_surface(),
+            NeuronModel::CubaLif(n) => n.get_fault_surface(),
+            NeuronModel::FixedLif(n) => n.get_fault_surface(),
+            NeuronModel::StochasticLif(n) => n.get_fault_surface(),
+            NeuronModel::AdaptiveLif(n) => n.get_fault_surface(),
+            NeuronModel::LeakyIntegrator(n) => n.get_fault_surface(),
+        }
+    }
+
+    fn get_storage_format(&self) -> StorageFormat {
+        match self {
+            NeuronModel::Lif(n) => n.get_storage_format(),
+            NeuronModel::Izhikevich(n) => n.get_storage_format(),
+            NeuronModel::AdEx(n) => n.get_storage_format(),
+            NeuronModel::CubaLif(n) => n.get_storage_format(),
+            NeuronModel::FixedLif(n) => n.get_storage_format(),
+            NeuronModel::StochasticLif(n) => n.get_storage_format(),
+            NeuronModel::AdaptiveLif(n) => n.get_storage_format(),
+            NeuronModel::LeakyIntegrator(n) => n.get_storage_format(),
+        }
+    }
+}
+
+impl From<Lif> for NeuronModel {
+    fn from(neuron: Lif) -> Self {
+        NeuronModel::Lif(neuron)
+    }
+}
+
+impl From<Izhikevich> for NeuronModel {
+    fn from(neuron: Izhikevich) -> Self {
+        NeuronModel::Izhikevich(neuron)
+    }
+}
+
+impl From<AdEx> for NeuronModel {
+    fn from(neuron: AdEx) -> Self {
+        NeuronModel::AdEx(neuron)
+    }
+}
+
+impl From<CubaLif> for NeuronModel {
+    fn from(neuron: CubaLif) -> Self {
+        NeuronModel::CubaLif(neuron)
+    }
+}
+
+impl From<FixedLif> for NeuronModel {
+    fn from(neuron: FixedLif) -> Self {
+        NeuronModel::FixedLif(neuron)
+    }
+}
+
+impl From<StochasticLif> for NeuronModel {
+    fn from(neuron: StochasticLif) -> Self {
+        NeuronModel::StochasticLif(neuron)
+    }
+}
+
+impl From<AdaptiveLif> for NeuronModel {
+    fn from(neuron: AdaptiveLif) -> Self {
+        NeuronModel::AdaptiveLif(neuron)
+    }
+}
+
+impl From<LeakyIntegrator> for NeuronModel {
+    fn from(neuron: LeakyIntegrator) -> Self {
+        NeuronModel::LeakyIntegrator(neuron)
+    }
+}
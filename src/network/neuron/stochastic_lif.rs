@@ -0,0 +1,198 @@
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use crate::network::neuron::neuron::Neuron;
+use crate::network::neuron::lif::Lif;
+use crate::resilience::components::{ComponentType, ComponentCategory, FaultableComponent};
+use crate::resilience::fault_models::{InjectedFault, ApplyFault, StoredValue};
+// Implements the Neuron trait with a stochastic leaky integrate-and-fire model:
+// the membrane potential evolves as in the Lif neuron, but the neuron fires with a probability
+// that is a sigmoid of the distance between the membrane potential and the threshold.
+
+#[derive(Debug, Clone)]
+pub struct StochasticLif {
+    reset_potential: f64, // reset potential
+    resting_potential: f64, // resting potential
+    threshold: f64, // threshold potential (firing probability = 0.5)
+    membrane_potential: f64, // membrane potential
+    tau: f64, // time constant
+    ts: u64, // last time instant where a spike has been received
+    dt: f64, // quantization parameter that defines the amplitude of the interval between two consecutive instants
+    temperature: f64, // noise of the firing: the lower the temperature, the closer the neuron to a deterministic Lif
+    seed: u64, // seed of the random number generator
+    rng: Box<StdRng> // random number generator owned by the neuron (reseeded at each inference)
+}
+
+impl StochasticLif {
+    pub fn new(reset_potential: f64, resting_potential: f64, threshold: f64, tau: f64, dt: f64, temperature: f64, seed: u64) -> Self {
+        if temperature <= 0.0 {
+            panic!("Error: The temperature of a stochastic LIF neuron should be positive");
+        }
+        StochasticLif {
+            reset_potential,
+            resting_potential,
+            threshold,
+            membrane_potential: resting_potential, // at the beginning the membrane potential is equal to the resting potential
+            tau,
+            ts: 0, // starting from time instant 0
+            dt,
+            temperature,
+            seed,
+            rng: Box::new(StdRng::seed_from_u64(seed))
+        }
+    }
+
+    // Stochastic version of a Lif neuron with the given temperature and seed
+    pub fn from_lif(lif: &Lif, temperature: f64, seed: u64) -> Self {
+        StochasticLif::new(lif.get_v_reset(), lif.get_v_rest(), lif.get_v_th(), lif.get_tau(), lif.get_dt(), temperature, seed)
+    }
+
+    // Getters for the StochasticLif parameters
+    pub fn get_v_reset(&self) -> f64 { self.reset_potential }
+    pub fn get_v_rest(&self) -> f64 { self.resting_potential }
+    pub fn get_v_th(&self) -> f64 { self.threshold }
+    pub fn get_v_mem(&self) -> f64 { self.membrane_potential }
+    pub fn get_tau(&self) -> f64 { self.tau }
+    pub fn get_ts(&self) -> u64 { self.ts }
+    pub fn get_temperature(&self) -> f64 { self.temperature }
+    pub fn get_seed(&self) -> u64 { self.seed }
+
+    // Integration of the input at the time instant t: the inhibited neuron doesn't spike (see process_inhibited_input)
+    fn integrate(&mut self, time: u64, mut weighted_sum: f64, fault: Option<InjectedFault>, inhibited: bool) -> u8 {
+
+        // Get the parameters of the neuron checking during runtime if there is a fault to inject
+        let (reset_potential, resting_potential, threshold, membrane_potential, tau, dt, ts, temperature)
+            = self.read_memory_areas(fault, time);
+
+        // Possible fault in the adder/multiplier
+        if let Some(f) = fault {
+            if f.component_type == ComponentType::Adder || f.component_type == ComponentType::Multiplier {
+                weighted_sum = f.apply_fault(weighted_sum, time);
+            }
+        }
+
+        // Compute the membrane potential at the time instant t
+        let delta_t = (time.saturating_sub(ts) as f64) * dt; // time interval between two input spikes (0 if a faulted ts is after t)
+        let mp = resting_potential + (membrane_potential - resting_potential) * (-delta_t/tau).exp() + weighted_sum;
+
+        // update the variables to be stored each step in the memory areas
+        match fault {
+            Some(f) if f.component_type == ComponentType::MembranePotential => self.membrane_potential = f.apply_fault(mp, time),
+            _ => self.membrane_potential = mp,
+        }
+        self.ts = time;
+
+        // Check if the neuron spikes, drawing a number from the random number generator of the neuron
+        // (the number is drawn also when the neuron is inhibited, so that the sequence doesn't depend on the inhibition)
+        let firing_probability = 1.0 / (1.0 + (-(self.membrane_potential - threshold) / temperature).exp());
+        let mut output_spike: u8;
+        if self.rng.gen::<f64>() < firing_probability && !inhibited {
+            self.membrane_potential = reset_potential;
+            output_spike = 1;
+        }
+        else {
+            output_spike = 0;
+        }
+
+        // Possible fault in the threshold comparator
+        if let Some(f) = fault {
+            if f.component_type == ComponentType::ThresholdComparator {
+                output_spike = f.apply_fault(output_spike, time);
+            }
+        }
+
+        output_spike
+    }
+}
+
+impl Neuron for StochasticLif {
+    /**
+    Computes the membrane potential of the neuron at the time instant t
+    and returns 1 if the neuron spikes, 0 otherwise.
+    - P(spike) = 1 / (1 + exp(-(v_mem - v_th) / temperature))
+    - @param time (u64)
+    - @param weighted_sum (f64): added directly to the membrane potential, as for the Lif neuron
+    - @return u8 (0/1)
+     */
+    fn process_input(&mut self, time: u64, weighted_sum: f64, fault: Option<InjectedFault>) -> u8 {
+        self.integrate(time, weighted_sum, fault, false)
+    }
+
+    fn process_inhibited_input(&mut self, time: u64, weighted_sum: f64, fault: Option<InjectedFault>) {
+        self.integrate(time, weighted_sum, fault, true);
+    }
+
+    fn get_membrane_potential(&self) -> f64 {
+        self.membrane_potential
+    }
+
+    // Reset the membrane potential to the resting potential, the time instant to 0
+    // and the random number generator to its seed, so that each inference is reproducible
+    fn initialize(&mut self) {
+        self.membrane_potential = self.resting_potential;
+        self.ts = 0;
+        *self.rng = StdRng::seed_from_u64(self.seed);
+    }
+
+    // Get the value of the parameter to fault
+    fn get_parameter_to_fault(&mut self, component_type: ComponentType) -> &mut dyn StoredValue {
+        match component_type {
+            ComponentType::ResetPotential       => &mut self.reset_potential,
+            ComponentType::RestingPotential     => &mut self.resting_potential,
+            ComponentType::Threshold            => &mut self.threshold,
+            ComponentType::MembranePotential    => &mut self.membrane_potential,
+            ComponentType::Tau                  => &mut self.tau,
+            ComponentType::DT                   => &mut self.dt,
+            ComponentType::Temperature          => &mut self.temperature,
+            ComponentType::Ts                   => &mut self.ts,
+            _                                   => panic!("Error: the component type is not valid for the stochastic LIF neuron"),
+        }
+    }
+
+    fn get_fault_surface(&self) -> Vec<FaultableComponent> {
+        let mut surface = vec![
+            FaultableComponent::new(ComponentType::ResetPotential, "Reset potential", 64, true),
+            FaultableComponent::new(ComponentType::RestingPotential, "Resting potential", 64, true),
+            FaultableComponent::new(ComponentType::Threshold, "Threshold", 64, true),
+            FaultableComponent::new(ComponentType::MembranePotential, "Membrane potential", 64, false),
+            FaultableComponent::new(ComponentType::Tau, "Tau", 64, true),
+            FaultableComponent::new(ComponentType::Ts, "Ts", 64, false),
+            FaultableComponent::new(ComponentType::DT, "dt", 64, true),
+            FaultableComponent::new(ComponentType::Temperature, "Temperature", 64, true),
+        ];
+        surface.extend(FaultableComponent::processing_blocks(64));
+        surface
+    }
+}
+
+impl StochasticLif {
+    fn read_memory_areas(&mut self, fault: Option<InjectedFault>, time: u64) -> (f64, f64, f64, f64, f64, f64, u64, f64) {
+        // Get the parameters of the neuron
+        // => In this way we are not soiling the original values of the built network with the fault,
+        //    since it's sufficient to inject the fault only when the neuron is processed
+        let mut reset_potential = self.reset_potential;
+        let mut resting_potential = self.resting_potential;
+        let mut threshold = self.threshold;
+        let mut membrane_potential = self.membrane_potential;
+        let mut tau = self.tau;
+        let mut dt = self.dt;
+        let mut ts = self.ts;
+        let mut temperature = self.temperature;
+
+        if let Some(injected_fault) = fault {
+            if injected_fault.component_category == ComponentCategory::MemoryArea {
+                match injected_fault.component_type {
+                    ComponentType::ResetPotential       => reset_potential = injected_fault.apply_fault(reset_potential, time),
+                    ComponentType::RestingPotential     => resting_potential = injected_fault.apply_fault(resting_potential, time),
+                    ComponentType::Threshold            => threshold = injected_fault.apply_fault(threshold, time),
+                    ComponentType::MembranePotential    => membrane_potential = injected_fault.apply_fault(membrane_potential, time),
+                    ComponentType::Tau                  => tau = injected_fault.apply_fault(tau, time),
+                    ComponentType::DT                   => dt = injected_fault.apply_fault(dt, time),
+                    ComponentType::Ts                   => ts = injected_fault.apply_fault(ts, time),
+                    ComponentType::Temperature          => temperature = injected_fault.apply_fault(temperature, time),
+                    _                                   => {}
+                }
+            }
+        }
+        (reset_potential, resting_potential, threshold, membrane_potential, tau, dt, ts, temperature)
+    }
+}
@@ -0,0 +1,130 @@
+use crate::network::layer::accumulate;
+use crate::network::storage_format::StorageFormat;
+use crate::resilience::components::ComponentType;
+use crate::resilience::fault_models::{InjectedFault, ApplyFault};
+
+// Pooling of the spikes of the previous layer
+// -------------------------------------------
+// The neurons of the previous layer (or the input) are arranged as a (channels, height, width) volume, as for the
+// convolutional layers, and each neuron of the pooling layer receives the pooled spikes of a kernel_size x kernel_size
+// window of the same channel:
+// - Max: OR of the spikes in the window (1 if at least one spike arrives)
+// - Sum: number of spikes in the window
+// - Average: number of spikes in the window divided by the size of the window
+// The pooled value is the weighted input of the neuron (a neuron with a threshold below 1 relays the OR of the spikes).
+// Each position of the output volume has its own pooling unit, an internal processing block that can be faulted.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PoolingMode {
+    Max,
+    Sum,
+    Average,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pooling {
+    channels: usize,    // number of channels of the input (and output) volume
+    in_height: usize,   // height of the input volume
+    in_width: usize,    // width of the input volume
+    kernel_size: usize, // side of the square pooling window
+    stride: usize,      // step between two consecutive positions of the window
+    mode: PoolingMode,  // function pooling the spikes of the window
+}
+
+impl Pooling {
+    /**
+        It creates the pooling of the input volume (channels, height, width) with square windows
+        of the given size, moved with the given stride
+     */
+    pub fn new(input_shape: (usize, usize, usize), kernel_size: usize, stride: usize, mode: PoolingMode) -> Self {
+        let (channels, in_height, in_width) = input_shape;
+        if kernel_size == 0 || stride == 0 {
+            panic!("Error: The window size and the stride of the pooling layer should be at least 1");
+        }
+        if kernel_size > in_height || kernel_size > in_width {
+            panic!("Error: The pooling window should not be larger than the input");
+        }
+
+        Pooling { channels, in_height, in_width, kernel_size, stride, mode }
+    }
+
+    // Getters
+    pub fn get_input_shape(&self) -> (usize, usize, usize) {
+        (self.channels, self.in_height, self.in_width)
+    }
+
+    pub fn get_output_shape(&self) -> (usize, usize, usize) {
+        let out_height = (self.in_height - self.kernel_size) / self.stride + 1;
+        let out_width = (self.in_width - self.kernel_size) / self.stride + 1;
+        (self.channels, out_height, out_width)
+    }
+
+    pub fn get_input_length(&self) -> usize {
+        self.channels * self.in_height * self.in_width
+    }
+
+    pub fn get_output_length(&self) -> usize {
+        let (channels, height, width) = self.get_output_shape();
+        channels * height * width
+    }
+
+    pub fn get_kernel_size(&self) -> usize {
+        self.kernel_size
+    }
+
+    pub fn get_stride(&self) -> usize {
+        self.stride
+    }
+
+    pub fn get_mode(&self) -> PoolingMode {
+        self.mode
+    }
+
+    // Number of bits of the output of a pooling unit: a single bit for the OR, a value in the weight format otherwise
+    pub fn get_output_width(&self, weight_format: StorageFormat) -> usize {
+        match self.mode {
+            PoolingMode::Max => 1,
+            _ => weight_format.get_width(),
+        }
+    }
+
+    /**
+        It pools the input spikes in the window of the neuron at the given index of the output volume.
+        The sums are computed in the weight format of the layer, and if the fault targets the pooling unit
+        of the neuron, it is applied to the output of the unit.
+     */
+    pub fn pool(&self, neuron_index: usize, input_spikes: &[u8], weight_format: StorageFormat, fault: Option<InjectedFault>, timestamp: u64) -> f64 {
+        let (_, out_height, out_width) = self.get_output_shape();
+        let channel = neuron_index / (out_height * out_width);
+        let out_row = (neuron_index / out_width) % out_height;
+        let out_column = neuron_index % out_width;
+
+        let window = (0..self.kernel_size).flat_map(|row| (0..self.kernel_size).map(move |column| (row, column)))
+            .map(|(row, column)| {
+                let in_row = out_row * self.stride + row;
+                let in_column = out_column * self.stride + column;
+                input_spikes[(channel * self.in_height + in_row) * self.in_width + in_column]
+            });
+
+        let faulted = matches!(fault, Some(f) if f.component_type == ComponentType::PoolingUnit && f.component_index == neuron_index);
+        match self.mode {
+            PoolingMode::Max => {
+                let mut spike = window.max().unwrap_or(0);
+                if faulted {
+                    spike = fault.unwrap().apply_fault(spike, timestamp);
+                }
+                spike as f64
+            },
+            PoolingMode::Sum | PoolingMode::Average => {
+                let mut pooled = window.fold(0.0, |sum, spike| accumulate(sum, 1.0, spike, weight_format));
+                if self.mode == PoolingMode::Average {
+                    pooled = weight_format.quantize(pooled / (self.kernel_size * self.kernel_size) as f64);
+                }
+                if faulted {
+                    pooled = fault.unwrap().apply_fault_in_format(pooled, weight_format, timestamp);
+                }
+                pooled
+            },
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use std::fs::File;
+use std::io::Write;
+use crate::network::snn::SNN;
+
+// Pruning of the weights of a network
+// -----------------------------------
+// The extra and intra weights with the smallest magnitude are removed (set to 0), to compress the network:
+// - Magnitude(threshold): the weights with absolute value below the threshold are removed
+// - Sparsity(fraction): the given fraction of the weights of each matrix, with the smallest absolute value, is removed
+// Fewer synapses give fewer fault sites (with sparse weights only the remaining synapses are stored)
+// but less redundancy, so the pruned network can be compared with the original one in the resilience analysis.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PruningCriterion {
+    Magnitude(f64), // minimum absolute value of the weights kept
+    Sparsity(f64),  // fraction (between 0 and 1) of zero weights in each matrix after the pruning
+}
+
+impl PruningCriterion {
+    /**
+        It removes the weights of the matrix selected by the criterion (the weights already 0 count for the sparsity)
+        - @return the number of non-zero weights removed
+     */
+    pub fn prune(&self, weights: &mut [Vec<f64>]) -> usize {
+        let mut num_pruned = 0;
+        match *self {
+            PruningCriterion::Magnitude(threshold) => {
+                if threshold < 0.0 {
+                    panic!("Error: The magnitude threshold of the pruning should not be negative");
+                }
+                for w in weights.iter_mut().flatten() {
+                    if *w != 0.0 && w.abs() < threshold {
+                        *w = 0.0;
+                        num_pruned += 1;
+                    }
+                }
+            },
+            PruningCriterion::Sparsity(fraction) => {
+                if !(0.0..=1.0).contains(&fraction) {
+                    panic!("Error: The target sparsity of the pruning should be between 0 and 1");
+                }
+                // the weights are ranked by magnitude (ties are removed in order of row and column)
+                let mut positions = weights.iter().enumerate()
+                    .flat_map(|(i, row)| (0..row.len()).map(move |j| (i, j)))
+                    .collect::<Vec<(usize, usize)>>();
+                positions.sort_by(|a, b| weights[a.0][a.1].abs().total_cmp(&weights[b.0][b.1].abs()));
+                let num_zeros = (fraction * positions.len() as f64).round() as usize;
+                for (i, j) in positions.into_iter().take(num_zeros) {
+                    if weights[i][j] != 0.0 {
+                        weights[i][j] = 0.0;
+                        num_pruned += 1;
+                    }
+                }
+            },
+        }
+        num_pruned
+    }
+}
+
+// Result of the pruning of a network, used to study how the compression trades off against the resilience
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PruningReport {
+    pub accuracy_before: f64,       // accuracy of the network before the pruning
+    pub accuracy_after: f64,        // accuracy of the network after the pruning
+    pub num_weights: usize,         // number of extra and intra weights of the network
+    pub num_synapses_before: usize, // number of non-zero weights before the pruning
+    pub num_synapses_after: usize,  // number of non-zero weights after the pruning
+}
+
+impl PruningReport {
+    // Fraction of zero weights in the pruned network
+    pub fn get_sparsity(&self) -> f64 {
+        if self.num_weights == 0 {
+            return 0.0;
+        }
+        1.0 - self.num_synapses_after as f64 / self.num_weights as f64
+    }
+}
+
+impl SNN
+{
+    /**
+        It prunes the extra and intra weights of all the layers with the given criterion
+        - @return the number of non-zero weights removed
+     */
+    pub fn prune(&mut self, criterion: PruningCriterion) -> usize {
+        (0..self.get_num_layers())
+            .map(|i| self.get_layer_mut(i).prune_weights(criterion))
+            .sum()
+    }
+
+    /**
+        It prunes the weights of the network and reports the accuracy on the input spike trains
+        before and after the pruning (computed as in the processing of the network without faults)
+     */
+    pub fn prune_with_report(&mut self, criterion: PruningCriterion, input_spike_train: &[Vec<Vec<u8>>], targets: &Vec<u8>) -> PruningReport {
+        let accuracy_before = self.get_accuracy(input_spike_train, targets);
+        let num_synapses_before = self.get_num_synapses();
+        self.prune(criterion);
+        PruningReport {
+            accuracy_before,
+            accuracy_after: self.get_accuracy(input_spike_train, targets),
+            num_weights: self.get_num_weights(),
+            num_synapses_before,
+            num_synapses_after: self.get_num_synapses(),
+        }
+    }
+
+    /**
+        Accuracy of the network on the input spike trains: the output neuron with the highest
+        number of spikes is compared with the target of each input
+     */
+    pub fn get_accuracy(&self, input_spike_train: &[Vec<Vec<u8>>], targets: &Vec<u8>) -> f64 {
+        self.process_batch(input_spike_train, targets, None, None).accuracy
+    }
+
+    // Number of extra and intra weights of the network
+    pub fn get_num_weights(&self) -> usize {
+        self.get_layers().iter()
+            .map(|layer| {
+                layer.get_dense_extra_weights().iter().chain(layer.get_dense_intra_weights().iter()).map(|row| row.len()).sum::<usize>()
+            })
+            .sum()
+    }
+
+    // Number of non-zero extra and intra weights of the network (existing synapses)
+    pub fn get_num_synapses(&self) -> usize {
+        self.get_layers().iter()
+            .map(|layer| {
+                layer.get_dense_extra_weights().iter().chain(layer.get_dense_intra_weights().iter()).flatten().filter(|w| **w != 0.0).count()
+            })
+            .sum()
+    }
+
+    /**
+        It saves the extra and intra weights of each layer in the directory, in the format of the weight files of the
+        configuration file (weightsFileN.txt and intraWeightsFileN.txt for the layer N, one row of the matrix for each line).
+        Only the networks of fully connected layers can be saved, since the configuration file can't describe
+        the convolutional and pooling layers and the skip connections.
+        The extra weights of the layers learning with STDP are the ones learned by the inferences of process_input.
+        - @return the names of the saved files (extra and intra weights of each layer)
+     */
+    pub fn save_weights(&self, directory: &str) -> Vec<(String, String)> {
+        if !self.get_layers().iter().all(|layer| layer.is_fully_connected()) {
+            panic!("Error: Only the weights of the fully connected layers can be saved in the weight files");
+        }
+        let mut filenames = Vec::new();
+        for (i, layer) in self.get_layers().iter().enumerate() {
+            let extra_filename = format!("{}/weightsFile{}.txt", directory, i + 1);
+            let intra_filename = format!("{}/intraWeightsFile{}.txt", directory, i + 1);
+            write_weights(&extra_filename, &self.get_learned_extra_weights(i));
+            write_weights(&intra_filename, &layer.get_dense_intra_weights());
+            filenames.push((extra_filename, intra_filename));
+        }
+        filenames
+    }
+}
+
+// It writes the weight matrix in the file, one row for each line
+fn write_weights(filename: &str, weights: &[Vec<f64>]) {
+    let mut file = File::create(filename).expect("Error: The weight file can't be created");
+    for row in weights {
+        let line = row.iter().map(|w| w.to_string()).collect::<Vec<String>>().join(" ");
+        writeln!(file, "{}", line).expect("Error: The weight file can't be written");
+    }
+}
@@ -0,0 +1,39 @@
+// Skip (residual) connection of a layer with an earlier layer of the network
+// ----------------------------------------------------------------------------
+// Besides the previous layer, a layer can receive the spikes of any earlier layer, so that the SNN is a
+// directed acyclic graph. Each incoming edge has its own weight matrix, with one row for each neuron of the layer
+// and one column for each neuron of the source layer. The weighted sums of the skip connections are added to the
+// weighted sum of the extra weights, in the same time instant.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkipConnection {
+    source: usize,              // index of the layer producing the spikes
+    pub weights: Vec<Vec<f64>>, // weights of the connections between each neuron and the neurons of the source layer
+}
+
+impl SkipConnection {
+    pub fn new(source: usize, weights: Vec<Vec<f64>>) -> Self {
+        if weights.iter().any(|row| row.len() != weights[0].len()) {
+            panic!("Error: All the rows of the weights matrix of a skip connection should have the same length");
+        }
+        SkipConnection { source, weights }
+    }
+
+    // Getters
+    pub fn get_source(&self) -> usize {
+        self.source
+    }
+
+    pub fn get_weights(&self) -> &Vec<Vec<f64>> {
+        &self.weights
+    }
+
+    // Number of neurons of the source layer
+    pub fn get_input_length(&self) -> usize {
+        self.weights.first().map_or(0, |w| w.len())
+    }
+
+    pub fn get_num_weights(&self) -> usize {
+        self.weights.len() * self.get_input_length()
+    }
+}
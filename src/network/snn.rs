@@ -1,24 +1,71 @@
-use std::sync::{Arc, Mutex};
+use std::sync::Mutex;
 use std::thread;
-use std::thread::JoinHandle;
+use std::thread::{Scope, ScopedJoinHandle};
 use std::sync::mpsc::channel;
 use std::sync::mpsc::{Sender, Receiver};
-use crate::network::layer::Layer;
-use crate::network::neuron::neuron::Neuron;
+use crate::network::network_layer::{NetworkLayer, NetworkLayerState};
+use crate::network::inference_state::InferenceState;
 use crate::network::event::spike_event::SpikeEvent;
 use crate::resilience::fault_models::InjectedFault;
 
 
-#[derive(Debug, Clone)]
-pub struct SNN < N: Neuron + Clone + Send + 'static >
+// Input channels of a layer (with the number of spikes of each one) and output channels
+type LayerChannels = (Vec<(Receiver<SpikeEvent>, usize)>, Vec<Sender<SpikeEvent>>);
+
+// The layers are accessed through the NetworkLayer trait, so that the same SNN can mix
+// different kinds of layers (dense, convolutional, pooling) and different neuron models.
+// Each layer receives the spikes of the previous layer and, through the skip connections,
+// of any earlier layer (directed acyclic graph): the last layer is the output of the network.
+// The layers are only read by the inferences, each one with its own InferenceState: the network keeps the state
+// of the inferences of process_input (locked for the whole inference, so that the inferences on it are serialized),
+// so that the weights learned with STDP are kept for the next inputs.
+#[derive(Debug)]
+pub struct SNN
 {
-  layers:  Vec<Arc<Mutex<Layer<N>>>>,
+  layers:  Vec<Box<dyn NetworkLayer>>,
+  state: Mutex<InferenceState>,
 }
 
-impl < N: Neuron + Clone + Send + 'static > SNN < N >
+// Cloning the SNN copies each layer sharing the weights and the neurons until the clone modifies them,
+// and the state of the last inference: the faults injected in the clone never reach the original network, nor the other clones
+impl Clone for SNN {
+  fn clone(&self) -> Self {
+    SNN {
+      layers: self.layers.iter().map(|layer| layer.clone_layer()).collect(),
+      state: Mutex::new(self.state.lock().unwrap().clone()),
+    }
+  }
+}
+
+impl SNN
 {
-  pub fn new(layers:  Vec<Arc<Mutex<Layer<N>>>>) -> Self {
-    SNN { layers }
+  /**
+    It creates the SNN from its layers, checking that the input of each layer
+    is consistent with the number of neurons of the previous one, and that each skip connection
+    comes from a layer before the previous one, with the number of neurons of the source layer
+   */
+  pub fn new(layers:  Vec<Box<dyn NetworkLayer>>) -> Self {
+    for i in 1..layers.len() {
+      let layer = &layers[i];
+      if layer.get_input_length() != layers[i-1].get_num_neurons() {
+        panic!("Error: The input of the layer {} is not consistent with the number of neurons of the previous layer", i);
+      }
+      for (source, input_length) in layer.get_skip_sources() {
+        if source + 1 >= i {
+          panic!("Error: The skip connection of the layer {} should come from a layer before the previous one", i);
+        }
+        if input_length != layers[source].get_num_neurons() {
+          panic!("Error: The skip connection of the layer {} is not consistent with the number of neurons of the layer {}", i, source);
+        }
+      }
+    }
+    if let Some(layer) = layers.first() {
+      if !layer.get_skip_sources().is_empty() {
+        panic!("Error: The first layer can't have skip connections");
+      }
+    }
+    let state = Mutex::new(InferenceState::new(layers.iter().map(|layer| layer.new_state()).collect()));
+    SNN { layers, state }
   }
 
   // Getters
@@ -26,20 +73,59 @@ impl < N: Neuron + Clone + Send + 'static > SNN < N >
     self.layers.len()
   }
 
-  pub fn get_layers(&self) -> &Vec<Arc<Mutex<Layer<N>>>> {
+  pub fn get_layers(&self) -> &Vec<Box<dyn NetworkLayer>> {
     &self.layers
   }
 
-  pub fn get_layer(&self, index: usize) -> &Arc<Mutex<Layer<N>>> {
-    &self.layers[index]
+  pub fn get_layer(&self, index: usize) -> &dyn NetworkLayer {
+    self.layers[index].as_ref()
+  }
+
+  /**
+    Layer to be modified (e.g. to inject a static fault): the state of the inferences of process_input is reset,
+    so that the next inference starts from the modified layer (the weights learned with STDP are discarded)
+   */
+  pub fn get_layer_mut(&mut self, index: usize) -> &mut dyn NetworkLayer {
+    *self.state.get_mut().unwrap() = self.new_inference_state();
+    self.layers[index].as_mut()
+  }
+
+  // New state of an inference of the network (the extra weights of the layers learning with STDP are not learned yet)
+  pub fn new_inference_state(&self) -> InferenceState {
+    InferenceState::new(self.layers.iter().map(|layer| layer.new_state()).collect())
+  }
+
+  /**
+    Extra weights of the layer used by the next inference of process_input:
+    the weights learned with STDP in the previous inferences, if any, or the extra weights of the layer
+   */
+  pub fn get_learned_extra_weights(&self, index: usize) -> Vec<Vec<f64>> {
+    match self.state.lock().unwrap().get_layer(index).get_learned_extra_weights() {
+      Some(learned_extra_weights) => learned_extra_weights.clone(),
+      None => self.layers[index].get_dense_extra_weights(),
+    }
+  }
+
+  /**
+    It processes the input spikes as process_input, and returns the membrane potential of each output neuron
+    at the end of the input: in a network ending with a non-spiking readout layer (LeakyIntegrator neurons)
+    the membrane potentials are the logits of the classes.
+   */
+  pub fn process_input_readout(&self, spikes: &Vec<Vec<u8>>, injected_fault: Option<InjectedFault>) -> Vec<f64> {
+    self.process_input_with_potentials(spikes, injected_fault).1
+  }
+
+  // Membrane potential of each output neuron at the end of the last input processed by process_input
+  pub fn get_output_membrane_potentials(&self) -> Vec<f64> {
+    self.state.lock().unwrap().get_output_membrane_potentials()
   }
 
   fn get_input_layer_num_neurons(&self) -> usize {
-    self.layers[0].lock().unwrap().get_extra_weights().first().unwrap().len()
+    self.layers[0].get_input_length()
   }
 
   fn get_output_layer_num_neurons(&self) -> usize {
-    self.layers[self.layers.len()-1].lock().unwrap().get_num_neurons()
+    self.layers[self.layers.len()-1].get_num_neurons()
   }
 
   /**
@@ -63,20 +149,46 @@ impl < N: Neuron + Clone + Send + 'static > SNN < N >
 
     @return Vec<Vec<u8>>
     The output of the SNN is a matrix of 0/1, where each row represents the array of spikes produced by each output neuron.
+
+    The inference starts from the state of the last inference of process_input (the weights learned with STDP),
+    and its state is stored in the network at the end: the concurrent inferences should use process_input_with_state.
    */
   pub fn process_input(&self, spikes: &Vec<Vec<u8>>, injected_fault: Option<InjectedFault>) -> Vec<Vec<u8>> {
+    self.process_input_with_potentials(spikes, injected_fault).0
+  }
+
+  /**
+    It processes the input spikes as process_input, and returns the output spikes together with the membrane potential
+    of each output neuron at the end of the input (read from the state of this inference).
+   */
+  pub fn process_input_with_potentials(&self, spikes: &Vec<Vec<u8>>, injected_fault: Option<InjectedFault>) -> (Vec<Vec<u8>>, Vec<f64>) {
+    // the state is locked for the whole inference, so that concurrent inferences don't lose the STDP updates
+    let mut state = self.state.lock().unwrap();
+    let output_spikes = self.process_input_with_state(&mut state, spikes, injected_fault);
+    (output_spikes, state.get_output_membrane_potentials())
+  }
+
+  /**
+    It processes the input spikes as process_input, keeping the state of the layers in the given state
+    (created by new_inference_state): the network is only read, so that any number of inferences
+    can share it concurrently, each one with its own state.
+   */
+  pub fn process_input_with_state(&self, state: &mut InferenceState, spikes: &Vec<Vec<u8>>, injected_fault: Option<InjectedFault>) -> Vec<Vec<u8>> {
+
+    if state.get_num_layers() != self.layers.len() {
+      panic!("Error: The state of the inference doesn't belong to the network");
+    }
 
     // PRE-PROCESSING: convert the input spikes into spike events
     let input_spike_events = self.derive_input_spike_events(spikes);
 
     // PARALLEL PROCESSING: process the input spike events
-    let output_spike_events = self.process_input_spike_events(input_spike_events, injected_fault);
-    //let output_spike_events = self.verbose_process_input_spike_events(input_spike_events);
+    let num_time_steps = spikes.first().unwrap().len() as u64;
+    let output_spike_events = self.process_input_spike_events(state.get_layers_mut(), input_spike_events, injected_fault, num_time_steps);
+    //let output_spike_events = self.verbose_process_input_spike_events(state.get_layers_mut(), input_spike_events, injected_fault, num_time_steps);
 
     // POST-PROCESSING: convert the output spike events into output spikes
-    let output_spikes = self.derive_output_spikes(&output_spike_events, spikes.first().unwrap().len());
-
-    output_spikes   
+    self.derive_output_spikes(&output_spike_events, spikes.first().unwrap().len())
   }
 
   // PRE-PROCESSING PHASE
@@ -146,128 +258,212 @@ impl < N: Neuron + Clone + Send + 'static > SNN < N >
     The output of the SNN is a vector of SpikeEvent, where each SpikeEvent represents the array of spikes produced by each output neuron at a given time instant.
    */
   #[allow(dead_code)]
-  fn verbose_process_input_spike_events(&self, input_spike_events: Vec<SpikeEvent>, injected_fault: Option<InjectedFault>) -> Vec<SpikeEvent> {
+  fn verbose_process_input_spike_events(&self, layer_states: &mut [Box<dyn NetworkLayerState>], input_spike_events: Vec<SpikeEvent>, injected_fault: Option<InjectedFault>, num_time_steps: u64) -> Vec<SpikeEvent> {
+    // the threads of the layers borrow the layers and their states until the end of the inference
+    thread::scope(|scope| {
 
-    let mut thread_handles = Vec::<JoinHandle<()>>::new();
+      let mut thread_handles = Vec::<ScopedJoinHandle<()>>::new();
 
-    // create the first channel for the input
-    let (input_tx, mut layer_rc) = channel::<SpikeEvent>();    
+      // create the first channel for the input
+      let (input_tx, layer_rc) = channel::<SpikeEvent>();    
 
-    // for each layer create a new thread and process the input
-    for (i, layer) in self.layers.iter().enumerate() {
+      // create the channels between the layers (previous layer and skip connections)
+      let (connections, output_rc) = self.connect_layers(layer_rc);
 
-      // clone the Arc pointer to the layer 
-      let layer = layer.clone();
+      // for each layer create a new thread and process the input
+      for (i, ((layer, layer_state), (input_rcs, output_txs))) in self.layers.iter().zip(layer_states.iter_mut()).zip(connections).enumerate() {
 
-      // create another channel for its communication with the next layer
-      let (layer_tx, next_layer_rc) = channel::<SpikeEvent>();
+        // merge the spike events coming from several layers in a single input,
+        // and send the output spike events to all the layers connected to this one
+        let layer_rc = SNN::merge_input_channels(scope, input_rcs, &mut thread_handles);
+        let layer_tx = SNN::broadcast_output_channel(scope, output_txs, &mut thread_handles);
       
-      // create a new thread
-      let handle = thread::spawn(move || {
-        let mut layer = layer.lock().unwrap();
-
-        if injected_fault.is_some() && injected_fault.unwrap().layer_index == i {
-            layer.process_input(layer_rc,layer_tx, injected_fault);
-        }
-        else {
-            layer.process_input(layer_rc,layer_tx, None);
-        }
-      });
-
-      // push the handle in the vector
-      thread_handles.push(handle);
-
-      // update the channel for the next layer
-      layer_rc = next_layer_rc;
-    }
+        // create a new thread (the layer is only read, its state is owned by the inference)
+        let handle = scope.spawn(move || {
+          if injected_fault.is_some() && injected_fault.unwrap().layer_index == i {
+              layer.process_input(layer_state.as_mut(), layer_rc,layer_tx, injected_fault, num_time_steps);
+          }
+          else {
+              layer.process_input(layer_state.as_mut(), layer_rc,layer_tx, None, num_time_steps);
+          }
+        });
 
-    // the last channel is the output channel
-    let output_rc = layer_rc;
+        // push the handle in the vector
+        thread_handles.push(handle);
+      }
 
-    // send the input spike events to the first layer
-    // (only if there is at least one spike with value 1)
-    for spike_event in input_spike_events {
-      if spike_event.get_spikes().iter().any(|&spike| spike == 1) {
+      // send the input spike events to the first layer
+      // (only if there is at least one spike with value 1)
+      for spike_event in input_spike_events {
+        if spike_event.get_spikes().iter().any(|&spike| spike == 1) {
 
-        let time_istant = spike_event.get_t();
+          let time_istant = spike_event.get_t();
 
-        input_tx.send(spike_event)
-          .expect(&format!("Failed to send the input spike event to the first layer at t = {}.", time_istant));
+          input_tx.send(spike_event)
+            .expect(&format!("Failed to send the input spike event to the first layer at t = {}.", time_istant));
+        }
       }
-    }
 
-    // close the input channel to terminate the communication
-    drop(input_tx);
+      // close the input channel to terminate the communication
+      drop(input_tx);
 
-    // wait for the threads to finish
-    for handle in thread_handles {
-      handle.join().unwrap();
-    }
+      // wait for the threads to finish
+      for handle in thread_handles {
+        handle.join().unwrap();
+      }
 
-    // receive the output spike events from the last layer
-    let mut output_spike_events: Vec<SpikeEvent> = Vec::new();
-    while let Ok(spike_event) = output_rc.recv() {
-      output_spike_events.push(spike_event);
-    }
+      // receive the output spike events from the last layer
+      let mut output_spike_events: Vec<SpikeEvent> = Vec::new();
+      while let Ok(spike_event) = output_rc.recv() {
+        output_spike_events.push(spike_event);
+      }
 
-    output_spike_events
+      output_spike_events
+    })
   }
 
-  fn process_input_spike_events(&self, input_spike_events: Vec<SpikeEvent>, injected_fault: Option<InjectedFault>) -> Vec<SpikeEvent> {
+  fn process_input_spike_events(&self, layer_states: &mut [Box<dyn NetworkLayerState>], input_spike_events: Vec<SpikeEvent>, injected_fault: Option<InjectedFault>, num_time_steps: u64) -> Vec<SpikeEvent> {
     
-    // Step 1: create the first channel for the input
-    let (input_tx, layer_rc) = channel::<SpikeEvent>();
+    // the threads of the layers borrow the layers and their states until the end of the inference
+    thread::scope(|scope| {
+
+      // Step 1: create the first channel for the input
+      let (input_tx, layer_rc) = channel::<SpikeEvent>();
 
-    // Step 2: Create and spawn threads
-    let (thread_handles, output_rc) = self.create_and_spawn_threads(layer_rc, injected_fault);
+      // Step 2: Create and spawn threads
+      let (thread_handles, output_rc) = self.create_and_spawn_threads(scope, layer_states, layer_rc, injected_fault, num_time_steps);
 
-    // Step 3: Send input spike events to the first layer
-    SNN::<N>::send_input_spike_events(input_spike_events, input_tx);
+      // Step 3: Send input spike events to the first layer
+      SNN::send_input_spike_events(input_spike_events, input_tx);
 
-    // Step 4: Wait for the threads to finish
-    SNN::<N>::wait_for_threads(thread_handles);
+      // Step 4: Wait for the threads to finish
+      SNN::wait_for_threads(thread_handles);
 
-    // Step 5: Receive output spike events from the last layer
-    SNN::<N>::receive_output_spike_events(output_rc)
+      // Step 5: Receive output spike events from the last layer
+      SNN::receive_output_spike_events(output_rc)
+    })
   }
 
-  fn create_and_spawn_threads(&self, layer_rc: Receiver<SpikeEvent>, fault: Option<InjectedFault>) -> (Vec<JoinHandle<()>>, Receiver<SpikeEvent>) {
-    
-    let mut curr_layer_rc = layer_rc;
+  fn create_and_spawn_threads<'scope>(&'scope self, scope: &'scope Scope<'scope, '_>, layer_states: &'scope mut [Box<dyn NetworkLayerState>], layer_rc: Receiver<SpikeEvent>, fault: Option<InjectedFault>, num_time_steps: u64) -> (Vec<ScopedJoinHandle<'scope, ()>>, Receiver<SpikeEvent>) {
     
-    let mut thread_handles = Vec::<JoinHandle<()>>::new();
+    let mut thread_handles = Vec::<ScopedJoinHandle<()>>::new();
+
+    // create the channels between the layers (previous layer and skip connections)
+    let (connections, output_rc) = self.connect_layers(layer_rc);
     
     // for each layer create a new thread and activate the processing of the input
-    for (i,layer) in self.layers.iter().enumerate() {
-        // clone the Arc pointer to the layer 
-        let layer = layer.clone();
-
-        // create another channel for its communication with the next layer
-        let (curr_layer_tx, next_layer_rc) = channel::<SpikeEvent>();
+    for (i, ((layer, layer_state), (input_rcs, output_txs))) in self.layers.iter().zip(layer_states.iter_mut()).zip(connections).enumerate() {
 
-        // create a new thread
-        let handle = thread::spawn(move || {
-            let mut layer = layer.lock().unwrap();
+        // merge the spike events coming from several layers in a single input,
+        // and send the output spike events to all the layers connected to this one
+        let curr_layer_rc = SNN::merge_input_channels(scope, input_rcs, &mut thread_handles);
+        let curr_layer_tx = SNN::broadcast_output_channel(scope, output_txs, &mut thread_handles);
 
+        // create a new thread (the layer is only read, its state is owned by the inference)
+        let handle = scope.spawn(move || {
             if fault.is_some() && fault.unwrap().layer_index == i {
-                layer.process_input(curr_layer_rc,curr_layer_tx, fault);
+                layer.process_input(layer_state.as_mut(), curr_layer_rc,curr_layer_tx, fault, num_time_steps);
             }
             else {
-                layer.process_input(curr_layer_rc,curr_layer_tx, None);
+                layer.process_input(layer_state.as_mut(), curr_layer_rc,curr_layer_tx, None, num_time_steps);
             }
         });
 
         // push the handle in the vector
         thread_handles.push(handle);
+    }
+
+    (thread_handles, output_rc)
+}
+
+/**
+  It creates the channels connecting the layers of the network:
+  - each layer receives the spike events of the previous layer (the input for the first layer)
+    and of the source layers of its skip connections, each one with the number of its neurons
+  - each layer sends its spike events to the next layer (the output for the last layer)
+    and to the layers with a skip connection from it
+
+  It returns the input receivers and the output senders of each layer, and the output channel of the network.
+ */
+fn connect_layers(&self, input_rc: Receiver<SpikeEvent>) -> (Vec<LayerChannels>, Receiver<SpikeEvent>) {
+    let mut input_rcs: Vec<Vec<(Receiver<SpikeEvent>, usize)>> = Vec::new();
+    let mut output_txs: Vec<Vec<Sender<SpikeEvent>>> = Vec::new();
+    let mut curr_layer_rc = input_rc;
+
+    for layer in self.layers.iter() {
+        // input from the previous layer, followed by the inputs from the skip connections
+        let mut inputs = vec![(curr_layer_rc, layer.get_input_length())];
+        for (source, input_length) in layer.get_skip_sources() {
+            let (skip_tx, skip_rc) = channel::<SpikeEvent>();
+            output_txs[source].push(skip_tx);
+            inputs.push((skip_rc, input_length));
+        }
+        input_rcs.push(inputs);
 
-        // update the channel for the next layer
+        // output to the next layer
+        let (curr_layer_tx, next_layer_rc) = channel::<SpikeEvent>();
+        output_txs.push(vec![curr_layer_tx]);
         curr_layer_rc = next_layer_rc;
     }
 
-    // the last channel is the output channel
-    let output_rc = curr_layer_rc;
+    (input_rcs.into_iter().zip(output_txs).collect(), curr_layer_rc)
+}
 
-    (thread_handles, output_rc)
+/**
+  It merges the spike events received from several layers in a single channel, in order of time:
+  the spikes of the layers received at the same time instant are concatenated in a single spike event
+  (with 0s for the layers that don't send a spike event at that time instant).
+  The merging thread is added to the thread handles, and it ends when all the input channels are closed.
+ */
+fn merge_input_channels<'scope>(scope: &'scope Scope<'scope, '_>, inputs: Vec<(Receiver<SpikeEvent>, usize)>, thread_handles: &mut Vec<ScopedJoinHandle<'scope, ()>>) -> Receiver<SpikeEvent> {
+    if inputs.len() == 1 {
+        return inputs.into_iter().next().unwrap().0;
+    }
+
+    let (merged_tx, merged_rc) = channel::<SpikeEvent>();
+    let handle = scope.spawn(move || {
+        // next spike event of each input (None if the channel is closed)
+        let mut next_events: Vec<Option<SpikeEvent>> = inputs.iter().map(|(rc, _)| rc.recv().ok()).collect();
+
+        while let Some(t) = next_events.iter().flatten().map(|event| event.get_t()).min() {
+            let mut spikes = Vec::new();
+            for ((rc, input_length), next_event) in inputs.iter().zip(next_events.iter_mut()) {
+                if next_event.as_ref().is_some_and(|event| event.get_t() == t) {
+                    spikes.extend_from_slice(next_event.take().unwrap().get_spikes());
+                    *next_event = rc.recv().ok();
+                }
+                else {
+                    spikes.extend(vec![0; *input_length]);
+                }
+            }
+            merged_tx.send(SpikeEvent::new(t, spikes)).unwrap();
+        }
+    });
+    thread_handles.push(handle);
+
+    merged_rc
+}
+
+/**
+  It sends the spike events of a layer to all the layers connected to it.
+  The broadcasting thread is added to the thread handles, and it ends when the layer closes its output channel.
+ */
+fn broadcast_output_channel<'scope>(scope: &'scope Scope<'scope, '_>, output_txs: Vec<Sender<SpikeEvent>>, thread_handles: &mut Vec<ScopedJoinHandle<'scope, ()>>) -> Sender<SpikeEvent> {
+    if output_txs.len() == 1 {
+        return output_txs.into_iter().next().unwrap();
+    }
+
+    let (layer_tx, layer_rc) = channel::<SpikeEvent>();
+    let handle = scope.spawn(move || {
+        while let Ok(spike_event) = layer_rc.recv() {
+            for output_tx in output_txs.iter() {
+                output_tx.send(SpikeEvent::new(spike_event.get_t(), spike_event.get_spikes().clone())).unwrap();
+            }
+        }
+    });
+    thread_handles.push(handle);
+
+    layer_tx
 }
 
 fn send_input_spike_events(input_spike_events: Vec<SpikeEvent>, input_tx: Sender<SpikeEvent>) {
@@ -285,7 +481,7 @@ fn send_input_spike_events(input_spike_events: Vec<SpikeEvent>, input_tx: Sender
     drop(input_tx);
 }
 
-fn wait_for_threads(thread_handles: Vec<JoinHandle<()>>) {
+fn wait_for_threads(thread_handles: Vec<ScopedJoinHandle<()>>) {
     // wait for the threads to finish
     for handle in thread_handles {
         handle.join().unwrap();
@@ -0,0 +1,74 @@
+// Sparse storage of a weights matrix
+// ----------------------------------
+// Compressed sparse row (CSR) format: only the non-zero weights (the existing synapses) are stored, row by row.
+// - values: the non-zero weights, in order of row and then of column
+// - columns: the column of each stored weight
+// - row_offsets: index in values of the first weight of each row (row i is values[row_offsets[i]..row_offsets[i+1]])
+// The processing of a row visits only its synapses, and the faults in the weights are injected
+// only in the stored values (the index of the fault is the index in values).
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsrMatrix {
+    num_rows: usize,         // number of rows of the dense matrix
+    num_cols: usize,         // number of columns of the dense matrix
+    row_offsets: Vec<usize>, // index of the first stored weight of each row (num_rows + 1 entries)
+    columns: Vec<usize>,     // column of each stored weight
+    pub values: Vec<f64>,    // stored (non-zero) weights
+}
+
+impl CsrMatrix {
+    // It stores the non-zero entries of the dense matrix
+    pub fn from_dense(matrix: &[Vec<f64>]) -> Self {
+        let mut row_offsets = vec![0];
+        let mut columns = Vec::new();
+        let mut values = Vec::new();
+
+        for row in matrix.iter() {
+            for (j, weight) in row.iter().enumerate() {
+                if *weight != 0.0 {
+                    columns.push(j);
+                    values.push(*weight);
+                }
+            }
+            row_offsets.push(values.len());
+        }
+
+        CsrMatrix {
+            num_rows: matrix.len(),
+            num_cols: matrix.first().map_or(0, |row| row.len()),
+            row_offsets,
+            columns,
+            values,
+        }
+    }
+
+    // It rebuilds the dense matrix (with zeros in place of the missing synapses)
+    pub fn to_dense(&self) -> Vec<Vec<f64>> {
+        let mut matrix = vec![vec![0.0; self.num_cols]; self.num_rows];
+        for (i, row) in matrix.iter_mut().enumerate() {
+            for (_, j, weight) in self.row(i) {
+                row[j] = weight;
+            }
+        }
+        matrix
+    }
+
+    // Getters
+    pub fn get_num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    pub fn get_num_cols(&self) -> usize {
+        self.num_cols
+    }
+
+    // Number of stored weights (existing synapses)
+    pub fn get_num_values(&self) -> usize {
+        self.values.len()
+    }
+
+    // Stored weights of the row i, as (index in values, column, weight)
+    pub fn row(&self, i: usize) -> impl Iterator<Item = (usize, usize, f64)> + '_ {
+        (self.row_offsets[i]..self.row_offsets[i + 1]).map(move |k| (k, self.columns[k], self.values[k]))
+    }
+}
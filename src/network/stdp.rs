@@ -0,0 +1,42 @@
+// Pair-based spike-timing-dependent plasticity (STDP) rule, used by a layer to update its extra weights
+// while it processes the input (on-chip learning):
+// - a pre-synaptic spike followed by a post-synaptic spike potentiates the connection
+// - a post-synaptic spike followed by a pre-synaptic spike depresses the connection
+// Only the nearest pair of spikes is considered, and the time constants are expressed in time steps.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StdpParameters {
+    pub a_plus: f64, // maximum potentiation of a weight (pre-synaptic spike just before the post-synaptic one)
+    pub a_minus: f64, // maximum depression of a weight (post-synaptic spike just before the pre-synaptic one)
+    pub tau_plus: f64, // time constant of the potentiation window
+    pub tau_minus: f64, // time constant of the depression window
+    pub w_min: f64, // lower bound of the weights
+    pub w_max: f64 // upper bound of the weights
+}
+
+impl StdpParameters {
+    pub fn new(a_plus: f64, a_minus: f64, tau_plus: f64, tau_minus: f64, w_min: f64, w_max: f64) -> Self {
+        if tau_plus <= 0.0 || tau_minus <= 0.0 {
+            panic!("Error: The time constants of the STDP rule should be positive");
+        }
+        if w_min > w_max {
+            panic!("Error: The lower bound of the weights should not be greater than the upper bound");
+        }
+        StdpParameters { a_plus, a_minus, tau_plus, tau_minus, w_min, w_max }
+    }
+
+    // Weight change when the post-synaptic spike follows the pre-synaptic one by delta_t time steps
+    pub fn potentiation(&self, delta_t: u64) -> f64 {
+        self.a_plus * (-(delta_t as f64) / self.tau_plus).exp()
+    }
+
+    // Weight change when the pre-synaptic spike follows the post-synaptic one by delta_t time steps
+    pub fn depression(&self, delta_t: u64) -> f64 {
+        -self.a_minus * (-(delta_t as f64) / self.tau_minus).exp()
+    }
+
+    // Keep the weight within the bounds of the rule
+    pub fn bound(&self, weight: f64) -> f64 {
+        weight.clamp(self.w_min, self.w_max)
+    }
+}
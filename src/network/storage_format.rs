@@ -0,0 +1,74 @@
+use half::{bf16, f16};
+use crate::network::fixed_point::{Fixed, QFormat};
+
+// Storage format of the values of the network
+// --------------------------------------------
+// The weights and the memory areas of the neurons are handled as f64 values, but they can be stored
+// with a reduced precision, to measure how the precision choice changes the vulnerability of the network.
+// - `StorageFormat` describes the encoding of the stored values:
+//    - floating point: f64 (default), f32, f16 or bfloat16
+//    - fixed point: Qm.n
+// - the stored values are rounded to the nearest value representable in the format
+// - the faults are injected in the bits of the encoding, whose width depends on the format.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageFormat {
+    F64,
+    F32,
+    F16,
+    BF16,
+    Fixed(QFormat),
+}
+
+impl StorageFormat {
+    /**
+        It parses a format written as "f64", "f32", "f16", "bf16" or "Qm.n" (e.g. "Q8.8")
+     **/
+    pub fn from_name(name: &str) -> Result<Self, &'static str> {
+        match name {
+            "f64" => Ok(StorageFormat::F64),
+            "f32" => Ok(StorageFormat::F32),
+            "f16" => Ok(StorageFormat::F16),
+            "bf16" => Ok(StorageFormat::BF16),
+            _ if name.starts_with('Q') => Ok(StorageFormat::Fixed(QFormat::from_name(name)?)),
+            _ => Err("Error: unknown storage format")
+        }
+    }
+
+    // Number of bits of the encoding
+    pub fn get_width(&self) -> usize {
+        match self {
+            StorageFormat::F64 => 64,
+            StorageFormat::F32 => 32,
+            StorageFormat::F16 | StorageFormat::BF16 => 16,
+            StorageFormat::Fixed(q) => q.get_width(),
+        }
+    }
+
+    // Rounding of the value to the nearest value representable in the format
+    pub fn quantize(&self, value: f64) -> f64 {
+        self.decode(self.encode(value))
+    }
+
+    // Encoding of the value in the format (in the least significant bits)
+    pub fn encode(&self, value: f64) -> u64 {
+        match self {
+            StorageFormat::F64 => value.to_bits(),
+            StorageFormat::F32 => (value as f32).to_bits() as u64,
+            StorageFormat::F16 => f16::from_f64(value).to_bits() as u64,
+            StorageFormat::BF16 => bf16::from_f64(value).to_bits() as u64,
+            StorageFormat::Fixed(q) => Fixed::from_f64(value, *q).get_bits(),
+        }
+    }
+
+    // Value represented by the encoding
+    pub fn decode(&self, bits: u64) -> f64 {
+        match self {
+            StorageFormat::F64 => f64::from_bits(bits),
+            StorageFormat::F32 => f32::from_bits(bits as u32) as f64,
+            StorageFormat::F16 => f16::from_bits(bits as u16).to_f64(),
+            StorageFormat::BF16 => bf16::from_bits(bits as u16).to_f64(),
+            StorageFormat::Fixed(q) => Fixed::from_bits(bits, *q).to_f64(),
+        }
+    }
+}
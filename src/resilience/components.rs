@@ -10,16 +10,69 @@ neurons in the previous layer, while intra_weights holds the weights of the conn
 and the neurons in the same layer. These weights are used to compute the weighted sum of inputs during the 
 neuron's processing.
 
+In a **convolutional layer** the extra weights are replaced by the **`kernel_weights`** of the Convolution struct,
+shared by all the output positions of the same output channel: a fault in one kernel weight affects the weighted sum
+of every neuron whose receptive field uses it.
+
+A layer can also receive the spikes of earlier layers through **skip connections**, each with its own **`weights`**
+matrix (SkipConnection struct): the skip weights of all the incoming edges of a layer are numbered row by row,
+in the order of the source layers, so that a fault can target the weights of a specific edge.
+
+If the weights of a layer are stored as **sparse matrices** (CsrMatrix struct), only the non-zero extra and intra
+weights are kept in the **`values`** of the matrices, so that the faults target only the existing synapses.
+
+When the connections of a layer have synaptic delays, the spikes in flight are held in the **`delay_buffers`**
+field of the Layer struct: one shift register per connection, where bit k is set if a spike arrives k time steps later.
+
 The "**memory areas**" are represented by various fields in the **Lif** struct, such as 
   - **`reset_potential`**, 
   - **`resting_potential`**, 
   - **`threshold`**, 
   - **`membrane_potential`**, 
-  - **`tau`**, and 
-  - **`ts`**. 
+  - **`tau`**, 
+  - **`ts`**, 
+  - **`refractory_period`**, 
+  - **`refractory_counter`** and 
+  - **`reset_pending`** (the reset flag, only with the reset by subtraction). 
 These parameters are fundamental for the functioning of individual neurons, 
 and they hold important numerical values that govern the neuron's dynamics.
 
+The **Izhikevich** neuron reuses the reset (`c`), threshold and membrane potential areas, and adds
+  - **`recovery_variable`** (`u`),
+  - **`a`** (time scale of the recovery variable),
+  - **`b`** (sensitivity of the recovery variable) and
+  - **`d`** (after-spike increment of the recovery variable).
+
+The **AdEx** neuron adds to the LIF areas (where the threshold is the rheobase threshold)
+  - **`peak_potential`** (spike detection),
+  - **`slope_factor`** (exponential spike initiation),
+  - **`adaptation_current`** (`w`),
+  - **`adaptation_coupling`** (`a`),
+  - **`adaptation_increment`** (`b`, spike-triggered) and
+  - **`tau_adaptation`**.
+
+The **CuBa LIF** neuron (current-based synapse) adds to the LIF areas
+  - **`synaptic_current`**, fed by the weighted input and integrated by the membrane, and
+  - **`synaptic_tau`**, the time constant of the synaptic current.
+
+The **fixed-point LIF** neuron stores the LIF areas as Qm.n integers, replacing `tau` and `dt` with
+  - **`beta`**, the decay factor of the membrane potential in each time step.
+
+The **stochastic LIF** neuron fires with a probability that is a sigmoid of the distance between the membrane
+potential and the threshold, and adds to the LIF areas
+  - **`temperature`**, the noise of the firing (the lower the temperature, the closer to a deterministic LIF).
+
+The **adaptive threshold LIF** neuron (homeostasis) raises its threshold after each spike, and adds to the LIF areas
+  - **`threshold_offset`**, added to the baseline threshold and decaying back to 0,
+  - **`threshold_increment`**, the increment of the offset after each spike, and
+  - **`threshold_tau`**, the time constant of the decay of the offset.
+
+The **leaky integrator** of a non-spiking readout layer has no threshold and reset, and stores only
+  - **`accumulator`**, the integrated weighted input (the output of the network at the end of the input), and
+  - **`decay`**, the decay factor of the accumulator in each time step.
+
+In fixed-point layers and neurons the bit index of a fault refers to the two's complement encoding on m+n bits.
+
 To perform resilience analysis on "**internal processing blocks within the neuron**," we need to simulate faults 
 in the individual components responsible for 
   - `**summation**`, 
@@ -28,6 +81,14 @@ in the individual components responsible for
 These faults can be of the stuck-at-0, stuck-at-1, or transient bit-flip type, as described in the project requirements. 
 For instance, simulating a stuck-at-1 fault in the threshold comparator would mean the neuron always spikes, even when 
 the threshold condition is not met.
+
+In a **pooling layer** each neuron is fed by a **pooling unit**, which pools the spikes of its window (OR, sum or average)
+and is faulted as an internal processing block of the neuron: the fault corrupts the output of the unit
+(a single bit for the OR, a value in the weight format of the layer for the sum and the average).
+
+In a **winner-take-all layer** the "**arbitration logic**" grants the spike to at most k neurons in each time instant:
+the arbiter produces one grant signal for each neuron, so a stuck-at-0 fault in the grant of a neuron means the neuron 
+never spikes, while a stuck-at-1 fault means the neuron spikes whenever it reaches the threshold, even if it loses the arbitration.
 */
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -35,14 +96,18 @@ pub enum ComponentCategory {
   Connection,
   MemoryArea,
   InternalProcessingBlock,
+  Arbitration,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ComponentType {
 
   // Connections between neurons
   Extra,
   Intra,
+  KernelWeight,
+  SkipWeight,
+  DelayBuffer,
   // LIF Memory areas
   ResetPotential,
   RestingPotential,
@@ -51,10 +116,42 @@ pub enum ComponentType {
   Tau,
   Ts,
   DT,
+  RefractoryPeriod,
+  RefractoryCounter,
+  ResetFlag,
+  // Izhikevich Memory areas
+  RecoveryVariable,
+  RecoveryTimeScale,
+  RecoverySensitivity,
+  RecoveryReset,
+  // AdEx Memory areas
+  PeakPotential,
+  SlopeFactor,
+  AdaptationCurrent,
+  AdaptationCoupling,
+  AdaptationIncrement,
+  AdaptationTau,
+  // CuBa LIF Memory areas
+  SynapticCurrent,
+  SynapticTau,
+  // Fixed-point LIF Memory areas
+  Beta,
+  // Stochastic LIF Memory areas
+  Temperature,
+  // Adaptive threshold LIF Memory areas
+  ThresholdOffset,
+  ThresholdIncrement,
+  ThresholdTau,
+  // Leaky integrator (readout) Memory areas
+  Accumulator,
+  Decay,
   // Internal processing blocks
   Adder,
   Multiplier,
   ThresholdComparator,
+  PoolingUnit,
+  // Winner-take-all arbitration logic of the layer
+  WtaArbiter,
 }
 
 impl ComponentType{
@@ -62,30 +159,51 @@ impl ComponentType{
   pub fn get_category(&self) -> ComponentCategory {
     match self {
       ComponentType::Extra |
-      ComponentType::Intra => ComponentCategory::Connection,
+      ComponentType::Intra |
+      ComponentType::KernelWeight |
+      ComponentType::SkipWeight |
+      ComponentType::DelayBuffer => ComponentCategory::Connection,
 
       ComponentType::Adder |
       ComponentType::Multiplier |
-      ComponentType::ThresholdComparator => ComponentCategory::InternalProcessingBlock,
+      ComponentType::ThresholdComparator |
+      ComponentType::PoolingUnit => ComponentCategory::InternalProcessingBlock,
+
+      ComponentType::WtaArbiter => ComponentCategory::Arbitration,
 
       _ => ComponentCategory::MemoryArea,
     }
   }
+}
+/**
+ * Descriptor of a component that can be faulted, published by each neuron model through `Neuron::get_fault_surface`
+ * (the connections are published by the layer).
+ * - `name`: label of the component shown in the GUI
+ * - `bit_width`: number of bits of the encoding of the component, among which the bit index of the fault is selected
+ *   (1 for the threshold comparator, whose output is a single bit)
+ * - `is_static`: true if the value of the component doesn't change during the simulation,
+ *   so that the stuck-at faults can be injected once before the processing phase
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FaultableComponent {
+  pub component_type: ComponentType,
+  pub name: &'static str,
+  pub bit_width: usize,
+  pub is_static: bool,
+}
 
-  /**
-   * Returns true if the component is a static component 
-   * (i.e. a component whose value does not change during the simulation).
-   */
-  pub fn is_static_component(&self) -> bool{
-    match self {
-      ComponentType::Extra |
-      ComponentType::Intra |
-      ComponentType::ResetPotential |
-      ComponentType::RestingPotential |
-      ComponentType::Threshold |
-      ComponentType::Tau |
-      ComponentType::DT => true,
-      _ => false,
-    }
+impl FaultableComponent {
+  pub fn new(component_type: ComponentType, name: &'static str, bit_width: usize, is_static: bool) -> Self {
+    FaultableComponent { component_type, name, bit_width, is_static }
   }
-}
\ No newline at end of file
+
+  // Internal processing blocks of the neurons: the adder and the multiplier work on values
+  // of the given width, while the threshold comparator produces a single bit
+  pub fn processing_blocks(bit_width: usize) -> Vec<FaultableComponent> {
+    vec![
+      FaultableComponent::new(ComponentType::Adder, "Adder", bit_width, false),
+      FaultableComponent::new(ComponentType::Multiplier, "Multiplier", bit_width, false),
+      FaultableComponent::new(ComponentType::ThresholdComparator, "Threshold comparator", 1, false),
+    ]
+  }
+}
@@ -2,6 +2,8 @@ use std::fmt::{Debug, Display, Formatter};
 
 /* Module for fault models */
 use crate::resilience::components::{ComponentType, ComponentCategory};
+use crate::network::fixed_point::Fixed;
+use crate::network::storage_format::StorageFormat;
 
 // Enum representing the different fault types
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -71,6 +73,16 @@ impl InjectedFault {
     pub fn bit_flip(var: u64, bit_index: usize) -> u64 {
         var ^ (1 << bit_index)
     }
+
+    // Apply the fault to the encoding of the value in the storage format of the component:
+    // bit indices beyond the width of the format don't exist, so they leave the value unchanged
+    pub fn apply_fault_in_format(&self, var: f64, format: StorageFormat, timestamp: u64) -> f64 {
+        match format {
+            StorageFormat::F64 => self.apply_fault(var, timestamp),
+            _ if self.bit_index.unwrap() >= format.get_width() => var,
+            _ => format.decode(self.apply_fault(format.encode(var), timestamp)),
+        }
+    }
 }
 
 pub trait ApplyFault<T> {
@@ -108,6 +120,17 @@ impl ApplyFault<f64> for InjectedFault {
     }
 }
 
+impl ApplyFault<Fixed> for InjectedFault {
+    // The fault is applied to the two's complement encoding of the fixed-point value:
+    // bit indices beyond the width of the format don't exist in hardware, so they leave the value unchanged
+    fn apply_fault(&self, var: Fixed, timestamp: u64) -> Fixed {
+        if self.bit_index.unwrap() >= var.get_format().get_width() {
+            return var;
+        }
+        Fixed::from_bits(self.apply_fault(var.get_bits(), timestamp), var.get_format())
+    }
+}
+
 impl ApplyFault<u64> for InjectedFault {
     fn apply_fault(&self, mut var: u64, timestamp: u64) -> u64 {
         if self.fault_type == FaultType::StuckAt0 {
@@ -147,4 +170,32 @@ impl ApplyFault<u8> for InjectedFault {
             spike_value
         }
     }
-}
\ No newline at end of file
+}
+
+/**
+ * Value stored in a component of the network, whose bits can be permanently faulted
+ * before the processing phase (static components).
+ * - f64 values are encoded in the storage format of the component (f64, f32, f16, bf16 or Qm.n)
+ * - fixed-point values are always encoded in their own format (two's complement on m+n bits)
+ * - u64 values (integer memory areas) are always encoded in 64 bits
+ */
+pub trait StoredValue: Debug {
+    fn get_bits(&self, format: StorageFormat) -> u64;
+    fn set_bits(&mut self, bits: u64, format: StorageFormat);
+}
+
+impl StoredValue for f64 {
+    fn get_bits(&self, format: StorageFormat) -> u64 { format.encode(*self) }
+    fn set_bits(&mut self, bits: u64, format: StorageFormat) { *self = format.decode(bits) }
+}
+
+impl StoredValue for u64 {
+    // integer memory areas (time instants, refractory period and counter) are always stored in 64 bits
+    fn get_bits(&self, _format: StorageFormat) -> u64 { *self }
+    fn set_bits(&mut self, bits: u64, _format: StorageFormat) { *self = bits }
+}
+
+impl StoredValue for Fixed {
+    fn get_bits(&self, _format: StorageFormat) -> u64 { Fixed::get_bits(self) }
+    fn set_bits(&mut self, bits: u64, _format: StorageFormat) { *self = Fixed::from_bits(bits, self.get_format()) }
+}
@@ -5,10 +5,10 @@ use iced::{alignment, Application, Color, executor, Theme, window};
 use iced::theme::{self};
 use iced::widget::{checkbox, column, container, horizontal_space, radio, row, text, text_input, Button, Column, TextInput, scrollable, image};
 use iced::{Element, Length, Settings, Command};
-use crate::network::config::{build_network_from_setup, compute_accuracy, compute_max_output_spike, network_setup_from_file};
-use crate::network::neuron::lif::Lif;
+use crate::network::config::{build_network_from_setup, network_setup_from_file};
+use crate::network::decoder::Decoder;
 use crate::network::snn::SNN;
-use crate::resilience::components::{ComponentCategory, ComponentType};
+use crate::resilience::components::{ComponentCategory, ComponentType, FaultableComponent};
 use crate::resilience::fault_models::{FaultType, InjectedFault};
 use crate::resilience::simulation::UserSelection;
 use plotters::prelude::*;
@@ -30,23 +30,15 @@ impl Tour {
         let mut fault = FaultType::StuckAt0;
         let mut num_faults= 0;
         let mut input_spike_train = Vec::new();
+        let mut output_decoder = None;
 
         // For each step of the GUI, we check what the user has selected
         for i in 1..self.steps.steps.len() {
             match self.steps.steps.get(i).unwrap() {
-                Step::Components { intra,extra,reset,resting, threshold, vmem, tau, ts, dt, adder, multiplier, comparator} => {
-                    if *intra != false { v.push(ComponentType::Intra) }
-                    if *extra != false { v.push(ComponentType::Extra) }
-                    if *reset != false { v.push(ComponentType::ResetPotential) }
-                    if *resting != false { v.push(ComponentType::RestingPotential) }
-                    if *threshold != false { v.push(ComponentType::Threshold) }
-                    if *vmem != false { v.push(ComponentType::MembranePotential) }
-                    if *tau != false { v.push(ComponentType::Tau) }
-                    if *ts != false { v.push(ComponentType::Ts) }
-                    if *dt != false { v.push(ComponentType::DT) }
-                    if *adder != false { v.push(ComponentType::Adder) }
-                    if *multiplier != false { v.push(ComponentType::Multiplier) }
-                    if *comparator != false { v.push(ComponentType::ThresholdComparator) }
+                Step::Components { components } => {
+                    for (component, selected) in components.iter() {
+                        if *selected { v.push(component.component_type) }
+                    }
                 },
                 Step::FaultType {selection} => {
                     fault = selection.unwrap();
@@ -54,18 +46,23 @@ impl Tour {
                 Step::NumFaults {value} => {
                     num_faults = value.parse::<u64>().unwrap();
                 },
-                Step::Accuracy {input_spike_trains, ..} =>{
+                Step::Accuracy {input_spike_trains, decoder, ..} =>{
                     input_spike_train = (*input_spike_trains).clone();
+                    output_decoder = *decoder;
                 }
                 _ => {}
             }
         }
 
-        // Return the user selection
-        UserSelection::new(v, fault, num_faults,input_spike_train)
+        // Return the user selection (with the decoder used to compute the accuracy without faults)
+        let user_selection = UserSelection::new(v, fault, num_faults,input_spike_train);
+        match output_decoder {
+            Some(decoder) => user_selection.with_decoder(decoder),
+            None => user_selection,
+        }
     }
 
-    pub fn get_arguments_for_simulation(&self) -> (UserSelection, Vec<u8>, SNN<Lif>, f64){
+    pub fn get_arguments_for_simulation(&self) -> (UserSelection, Vec<u8>, SNN, f64){
         
         let mut user_selection = UserSelection::new(vec![], FaultType::StuckAt0, 0, vec![]);
         let mut target = Vec::new();
@@ -191,23 +188,23 @@ impl Application for Tour {
                 if self.steps.is_accuracy() {
                     let n = network_setup_from_file();
                     let (snn_net, input_spike_train, target) = build_network_from_setup(n.unwrap());
+                    // the components that can be selected are the ones published by the neuron models of the network
+                    let surface = snn_net.get_fault_surface();
                     let s = &mut self.steps.steps[3];
                     match s {
-                        Step::Accuracy { ref mut snn, ref mut input_spike_trains, ref mut targets , ref mut a}
+                        Step::Accuracy { ref mut snn, ref mut input_spike_trains, ref mut targets , ref mut a, decoder}
                             => { *snn = snn_net;
                                 *input_spike_trains = input_spike_train;
                                 *targets = target;
-                                let mut vec_max = Vec::new();
-                                for input_spikes in (*input_spike_trains).iter() {
-                                    let output_spikes = (*snn).process_input(&input_spikes, None);
-                                    let max = compute_max_output_spike(output_spikes);
-                                    vec_max.push(max);
-                                 }
-                                 let acc = compute_accuracy(vec_max, &(*targets));
-                                *a = acc;
+                                let batch = (*snn).process_batch(input_spike_trains, targets, None, *decoder);
+                                *a = batch.accuracy;
                         }
                         _ => {}
                     };
+
+                    if let Step::Components { ref mut components } = self.steps.steps[4] {
+                        *components = surface.into_iter().map(|c| (c, false)).collect();
+                    }
                 }
 
                 // #to_do: build the network and test the accuracy with faults
@@ -339,19 +336,10 @@ impl Application for Tour {
                 // Delete components
                 let s = &mut self.steps.steps[4];
                 match s {
-                    Step::Components { ref mut intra, ref mut extra, ref mut reset,ref mut resting, ref mut threshold, ref mut vmem, ref mut tau, ref mut ts, ref mut dt, ref mut adder, ref mut multiplier, ref mut comparator} => {
-                        *intra = false;
-                        *extra = false;
-                        *reset = false;
-                        *resting = false;
-                        *threshold = false;
-                        *vmem = false;
-                        *tau = false;
-                        *ts = false;
-                        *dt = false;
-                        *adder = false;
-                        *multiplier = false;
-                        *comparator = false;
+                    Step::Components { ref mut components } => {
+                        for (_, selected) in components.iter_mut() {
+                            *selected = false;
+                        }
                     },
                     _ => {}
                 };
@@ -465,12 +453,11 @@ impl Steps {
                     snn: SNN::new(Vec::new()),
                     input_spike_trains: Vec::new(),
                     targets: Vec::new(),
-                    a: 0.0
+                    a: 0.0,
+                    decoder: None
                 },
                 Step::Components {
-                    intra: false, extra: false,
-                    reset: false, resting: false, threshold: false, vmem: false, tau: false, ts: false, dt: false,
-                    adder: false, multiplier: false, comparator: false
+                    components: Vec::new()
                 },
                 Step::FaultType { selection: None },
                 Step::NumFaults { value: String::new() },
@@ -479,6 +466,7 @@ impl Steps {
                     fault_type: FaultType::StuckAt0,
                     num_faults: 0,
                     input_sequence: vec![],
+                    decoder: None,
                 }},
                 Step::Image {
                     a_inj: Vec::new(),
@@ -578,15 +566,14 @@ enum Step {
     },
     Waiting,
     Accuracy {
-        snn: SNN<Lif>,
+        snn: SNN,
         input_spike_trains: Vec<Vec<Vec<u8>>>,
         targets: Vec<u8>,
-        a: f64
+        a: f64,
+        decoder: Option<Decoder> // decoder of the output (None => output neuron with the most spikes)
     },
     Components {
-        intra: bool, extra: bool,
-        reset: bool, resting: bool, threshold: bool, vmem: bool, tau: bool, ts: bool, dt: bool,
-        adder: bool, multiplier: bool, comparator: bool,
+        components: Vec<(FaultableComponent, bool)>, // fault surface of the network and selection of the user
     },
     FaultType { selection: Option<FaultType>, },
     NumFaults { value: String },
@@ -603,21 +590,12 @@ enum Step {
 
 #[derive(Debug, Clone)]
 pub enum StepMessage {
-    // Components selection
-    IntraSelected(bool),
-    ExtraSelected(bool),
-    RstSelected(bool),
-    RestSelected(bool),
-    ThresholdSelected(bool),
-    MemSelected(bool),
-    TauSelected(bool),
-    TsSelected(bool),
-    DTSelected(bool),
-    AdderSelected(bool),
-    MulSelected(bool),
-    ComparatorSelected(bool),
+    // Components selection (index of the component in the fault surface of the network)
+    ComponentSelected(usize, bool),
     // Fault type selection
     FaultSelected(FaultType),
+    // Decoder of the output selection
+    DecoderSelected(Option<Decoder>),
     // Number of faults selection
     InputChanged(String),
     // Network configuration parameters
@@ -641,64 +619,9 @@ impl<'a> Step {
     
     fn update(&mut self, msg: StepMessage) {
         match msg {
-            StepMessage::IntraSelected(toggle) => {
-                if let Step::Components { intra, .. } = self {
-                    *intra = toggle;
-                }
-            }
-            StepMessage::ExtraSelected(toggle) => {
-                if let Step::Components { extra, .. } = self {
-                    *extra = toggle;
-                }
-            }
-            StepMessage::RstSelected(toggle) => {
-                if let Step::Components { reset, .. } = self {
-                    *reset = toggle;
-                }
-            }
-            StepMessage::RestSelected(toggle) => {
-                if let Step::Components { resting, .. } = self {
-                    *resting = toggle;
-                }
-            }
-            StepMessage::ThresholdSelected(toggle) => {
-                if let Step::Components { threshold, .. } = self {
-                    *threshold = toggle;
-                }
-            }
-            StepMessage::MemSelected(toggle) => {
-                if let Step::Components { vmem, .. } = self {
-                    *vmem = toggle;
-                }
-            }
-            StepMessage::TauSelected(toggle) => {
-                if let Step::Components { tau, .. } = self {
-                    *tau = toggle;
-                }
-            }
-            StepMessage::TsSelected(toggle) => {
-                if let Step::Components { ts, .. } = self {
-                    *ts = toggle;
-                }
-            }
-            StepMessage::DTSelected(toggle) => {
-                if let Step::Components { dt, .. } = self {
-                    *dt = toggle;
-                }
-            }
-            StepMessage::AdderSelected(toggle) => {
-                if let Step::Components { adder, .. } = self {
-                    *adder = toggle;
-                }
-            }
-            StepMessage::MulSelected(toggle) => {
-                if let Step::Components { multiplier, .. } = self {
-                    *multiplier = toggle;
-                }
-            }
-            StepMessage::ComparatorSelected(toggle) => {
-                if let Step::Components { comparator, .. } = self {
-                    *comparator = toggle;
+            StepMessage::ComponentSelected(index, toggle) => {
+                if let Step::Components { components } = self {
+                    components[index].1 = toggle;
                 }
             }
             StepMessage::FaultSelected(sel) => {
@@ -706,6 +629,13 @@ impl<'a> Step {
                     *selection = Some(sel);
                 }
             }
+            StepMessage::DecoderSelected(sel) => {
+                // the accuracy without faults is computed again with the selected decoder
+                if let Step::Accuracy { snn, input_spike_trains, targets, a, decoder } = self {
+                    *decoder = sel;
+                    *a = snn.process_batch(input_spike_trains, targets, None, sel).accuracy;
+                }
+            }
 
             // Network configuration parameters
             StepMessage::InputChanged(new_value) => {
@@ -808,8 +738,8 @@ impl<'a> Step {
             Step::Network {..} => network_setup_from_file().is_ok(),
             Step::Waiting => true,
             Step::Accuracy { .. } => true,
-            Step::Components { intra,extra,reset,resting, threshold, vmem, tau, ts, dt, adder, multiplier, comparator } => {
-                *intra != false || *extra != false || *reset != false || *resting != false || *threshold != false || *vmem != false || *tau != false || *dt != false || *ts != false || *adder != false || *multiplier != false || *comparator != false
+            Step::Components { components } => {
+                components.iter().any(|(_, selected)| *selected)
             },
             Step::FaultType { selection } => { selection.is_some() },
             Step::NumFaults { value, .. } => {
@@ -828,9 +758,8 @@ impl<'a> Step {
             Step::Network {input_length, hidden_layers_length, output_length, extra_files, intra_files, resting_potential, reset_potential, threshold, beta, tau, dt, spike_length, batch_size, input_spike_train_file}
                 => Self::network(input_length, hidden_layers_length, output_length, extra_files, intra_files, resting_potential, reset_potential, threshold, beta, tau, dt, spike_length, batch_size, input_spike_train_file),
             Step::Waiting{} => Self::waiting(),
-            Step::Accuracy {snn : _, input_spike_trains: _, targets: _, a} => Self::accuracy(*a),
-            Step::Components {intra,extra,reset,resting, threshold, vmem, tau, ts, dt, adder, multiplier, comparator }
-                => Self::components(*intra, *extra, *reset, *resting, *threshold, *vmem, *tau, *ts, *dt, *adder, *multiplier, *comparator),
+            Step::Accuracy {snn : _, input_spike_trains: _, targets: _, a, decoder} => Self::accuracy(*a, *decoder),
+            Step::Components { components } => Self::components(components),
             Step::FaultType { selection} => Self::fault_type(*selection),
             Step::NumFaults { value} => Self::num_faults(value),
             Step::Choices { c } => {
@@ -1014,11 +943,22 @@ impl<'a> Step {
             .push("Please press Next and wait for the result to appear", )
     }
 
-    fn accuracy(a: f64) -> Column<'a, StepMessage> { //OK
+    fn accuracy(a: f64, decoder: Option<Decoder>) -> Column<'a, StepMessage> { //OK
         let question = column![text(format!("The accuracy without faults is: {} %", a)).size(20)];
+        let decoders = [None, Some(Decoder::SpikeCount), Some(Decoder::FirstSpike), Some(Decoder::MembranePotential)];
+        let question2 = column![
+            text("Select the decoder of the output").size(20),
+            column(decoders.iter().cloned()
+                    .map(|d| { radio(decoder_name(d), d, Some(decoder), StepMessage::DecoderSelected) })
+                    .map(Element::from)
+                    .collect()
+            )
+            .spacing(10)
+        ].padding(20).spacing(10);
         Self::container("Network built")
             .push("Your network has been built", )
             .push(question)
+            .push(question2)
             .push("Please click Next to select the configuration of the faults to inject", )
     }
 
@@ -1038,27 +978,14 @@ impl<'a> Step {
             .push("Please click Next to insert the number of faults to check", )
     }
 
-    fn components( //OK
-              intra: bool, extra: bool,
-              reset: bool, resting: bool, threshold: bool, vmem: bool, tau: bool, ts: bool, dt: bool,
-              adder: bool, multiplier: bool, comparator: bool,
-    ) -> Column<'a, StepMessage> {
+    fn components(components: &[(FaultableComponent, bool)]) -> Column<'a, StepMessage> { //OK
         let question = column![text("Select in which components you want to insert a fault:").size(20)];
-        Self::container("Components selection")
-            .push(question)
-            .push(checkbox(ComponentType::Intra, intra, StepMessage::IntraSelected ))
-            .push(checkbox(ComponentType::Extra, extra, StepMessage::ExtraSelected ))
-            .push(checkbox(ComponentType::ResetPotential, reset, StepMessage::RstSelected ))
-            .push(checkbox(ComponentType::RestingPotential, resting, StepMessage::RestSelected ))
-            .push(checkbox(ComponentType::Threshold, threshold, StepMessage::ThresholdSelected ))
-            .push(checkbox(ComponentType::MembranePotential, vmem, StepMessage::MemSelected ))
-            .push(checkbox(ComponentType::Tau, tau, StepMessage::TauSelected ))
-            .push(checkbox(ComponentType::Ts, ts, StepMessage::TsSelected ))
-            .push(checkbox(ComponentType::DT, dt, StepMessage::DTSelected ))
-            .push(checkbox(ComponentType::Adder, adder, StepMessage::AdderSelected ))
-            .push(checkbox(ComponentType::Multiplier, multiplier, StepMessage::MulSelected ))
-            .push(checkbox(ComponentType::ThresholdComparator, comparator, StepMessage::ComparatorSelected ))
-            .push("Please click Next to choose the fault type", )
+        let mut container = Self::container("Components selection").push(question);
+        // one checkbox for each component published by the neuron models of the network
+        for (i, (component, selected)) in components.iter().enumerate() {
+            container = container.push(checkbox(component.name, *selected, move |toggle| StepMessage::ComponentSelected(i, toggle)));
+        }
+        container.push("Please click Next to choose the fault type", )
     }
 
     fn num_faults(value: &str) -> Column<'a, StepMessage> { //OK
@@ -1081,7 +1008,7 @@ impl<'a> Step {
         let question = column![
             text("Components selected:").size(20),
             column(u.components.iter().cloned()
-                    .map(|c| { checkbox(c, true, StepMessage::IntraSelected ) })
+                    .map(|c| { text(String::from(c)).size(16) })
                     .map(Element::from)
                     .collect()
             )
@@ -1090,7 +1017,7 @@ impl<'a> Step {
         let question2 = column![
             text("Fault selected:").size(20),
             column(fault.iter().cloned()
-                    .map(|c| { checkbox(c, true, StepMessage::IntraSelected ) })
+                    .map(|c| { text(String::from(c)).size(16) })
                     .map(Element::from)
                     .collect()
             )
@@ -1099,16 +1026,21 @@ impl<'a> Step {
         let question3 = column![
             text("Number of faults introduced:").size(20),
             column(num.iter().cloned()
-                    .map(|c| { checkbox(format!("{}", c), true, StepMessage::IntraSelected ) })
+                    .map(|c| { text(c).size(16) })
                     .map(Element::from)
                     .collect()
             )
             .spacing(10)
         ].padding(20).spacing(10);
+        let question4 = column![
+            text("Decoder of the output:").size(20),
+            text(decoder_name(u.decoder)).size(16)
+        ].padding(20).spacing(10);
         Self::container("Summary of your choices")
             .push(question)
             .push(question2)
             .push(question3)
+            .push(question4)
             .push("Please click Next to run the simulation", )
             .push("This process may take a while. Please wait for the result to appear", )
     }
@@ -1152,6 +1084,9 @@ impl From<ComponentType> for String {
         String::from(match component {
             ComponentType::Extra => "Extra weights",
             ComponentType::Intra => "Intra weights",
+            ComponentType::KernelWeight => "Kernel weights",
+            ComponentType::SkipWeight => "Skip weights",
+            ComponentType::DelayBuffer => "Delay buffers",
             ComponentType::ResetPotential => "Reset potential",
             ComponentType::RestingPotential => "Resting potential",
             ComponentType::Threshold => "Threshold",
@@ -1159,9 +1094,33 @@ impl From<ComponentType> for String {
             ComponentType::Tau => "Tau",
             ComponentType::Ts => "Ts",
             ComponentType::DT => "dt",
+            ComponentType::RefractoryPeriod => "Refractory period",
+            ComponentType::RefractoryCounter => "Refractory counter",
+            ComponentType::ResetFlag => "Reset flag",
+            ComponentType::RecoveryVariable => "Recovery variable",
+            ComponentType::RecoveryTimeScale => "Recovery time scale (a)",
+            ComponentType::RecoverySensitivity => "Recovery sensitivity (b)",
+            ComponentType::RecoveryReset => "Recovery reset (d)",
+            ComponentType::PeakPotential => "Peak potential",
+            ComponentType::SlopeFactor => "Slope factor",
+            ComponentType::AdaptationCurrent => "Adaptation current",
+            ComponentType::AdaptationCoupling => "Adaptation coupling (a)",
+            ComponentType::AdaptationIncrement => "Adaptation increment (b)",
+            ComponentType::AdaptationTau => "Adaptation tau",
+            ComponentType::SynapticCurrent => "Synaptic current",
+            ComponentType::SynapticTau => "Synaptic tau",
+            ComponentType::Beta => "Beta",
+            ComponentType::Temperature => "Temperature",
+            ComponentType::ThresholdOffset => "Threshold offset",
+            ComponentType::ThresholdIncrement => "Threshold increment",
+            ComponentType::ThresholdTau => "Threshold tau",
+            ComponentType::Accumulator => "Accumulator",
+            ComponentType::Decay => "Decay",
             ComponentType::Adder => "Adder",
             ComponentType::Multiplier => "Multiplier",
-            ComponentType::ThresholdComparator => "Threshold comparator"
+            ComponentType::ThresholdComparator => "Threshold comparator",
+            ComponentType::PoolingUnit => "Pooling unit",
+            ComponentType::WtaArbiter => "WTA arbiter"
         })
     }
 }
@@ -1182,6 +1141,18 @@ impl From<ComponentCategory> for String {
             ComponentCategory::MemoryArea => "Memory area",
             ComponentCategory::Connection => "Connection",
             ComponentCategory::InternalProcessingBlock => "Internal processing block",
+            ComponentCategory::Arbitration => "Arbitration logic",
         })
     }
-}
\ No newline at end of file
+}
+
+// Name of the decoder of the output shown in the GUI
+fn decoder_name(decoder: Option<Decoder>) -> String {
+    String::from(match decoder {
+        None => "Output neuron with the most spikes",
+        Some(Decoder::SpikeCount) => "Spike count (ties count as failures)",
+        Some(Decoder::FirstSpike) => "First spike",
+        Some(Decoder::MembranePotential) => "Membrane potential",
+        Some(Decoder::Population(_)) => "Population spike count",
+    })
+}
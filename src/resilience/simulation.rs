@@ -2,12 +2,13 @@
 use std::thread;
 use std::thread::JoinHandle;
 use rand::Rng;
-use crate::network::config::{compute_accuracy, compute_max_output_spike};
 use crate::network::neuron::neuron::Neuron;
 use crate::network::layer::Layer;
 use crate::network::snn::SNN;
-use crate::resilience::components::ComponentType;
-use crate::resilience::fault_models::{FaultType, InjectedFault, ApplyFault};
+use crate::resilience::components::{ComponentType, ComponentCategory, FaultableComponent};
+use crate::resilience::fault_models::{FaultType, InjectedFault, ApplyFault, StoredValue};
+use crate::network::storage_format::StorageFormat;
+use crate::network::decoder::Decoder;
 
 // Struct to hold the fault injection parameters defined by the user
 #[derive(Debug, Clone)]
@@ -16,6 +17,7 @@ pub struct UserSelection {
     pub fault_type: FaultType,
     pub num_faults: u64,
     pub input_sequence: Vec<Vec<Vec<u8>>>,
+    pub decoder: Option<Decoder>, // decoder of the output (None => output neuron with the most spikes, as compute_max_output_spike)
 }
 
 impl UserSelection {
@@ -26,11 +28,23 @@ impl UserSelection {
             fault_type,
             num_faults,
             input_sequence,
+            decoder: None,
+        }
+    }
+
+    /**
+     * It selects the decoder of the output used to compute the accuracy with the injected faults:
+     * the inputs without a decision count as failures (the accuracy without faults should use the same decoder)
+     */
+    pub fn with_decoder(self, decoder: Decoder) -> Self {
+        UserSelection {
+            decoder: Some(decoder),
+            ..self
         }
     }
 }
 
-impl < N: Neuron + Clone + Send + 'static > SNN < N >
+impl SNN
 {
     /**
      * Given the user selection, run the simulation of the SNN with the injected faults.
@@ -49,8 +63,9 @@ impl < N: Neuron + Clone + Send + 'static > SNN < N >
         for _ in 0..user_selection.num_faults {
 
             // Clone the user selection and the SNN to be used in separate threads
+            // (each fault is injected in its own copy of the network, the original SNN is never modified)
             let user_selection = user_selection.clone();
-            let snn = self.clone();
+            let mut snn = self.clone();
             let targets = targets.clone();
 
             // Spawn a thread to run the simulation
@@ -59,17 +74,15 @@ impl < N: Neuron + Clone + Send + 'static > SNN < N >
                 let input_spikes = user_selection.input_sequence;
                 let num_time_steps = input_spikes.get(0).unwrap().get(0).unwrap().len();
 
-                let mut v = Vec::new();
-
                 // Randomly generate the injected fault
                 let mut injected_fault = Self::generate_random_fault(user_selection.components,user_selection.fault_type, &snn, &num_time_steps);
                 let mut already_injected = false;
 
-                // Apply the injected fault to the cloned SNN
+                // Apply the injected fault to the cloned SNN (deep copy)
                 // - if the fault is a static fault
-                // - AND the component selected doesn't change over time
+                // - AND the component selected doesn't change over time (as published by the neuron model)
                 if injected_fault.fault_type != FaultType::TransientBitFlip 
-                && injected_fault.component_type.is_static_component() {
+                && snn.get_faultable_component(&injected_fault).is_static {
                     
                     // Check if the applied fault actually modifies the value of the bit in the component
                     // - if the bit was 0 and the fault is stuck-at-0 => the fault doesn't need to be applied
@@ -89,22 +102,12 @@ impl < N: Neuron + Clone + Send + 'static > SNN < N >
                     }
                 }
 
-                for input_spike_train in input_spikes {
-                    
-                    // Process the input sequence
-                    let output_spikes;
-                    if already_injected { // with the fault already injected
-                        output_spikes = snn.process_input(&input_spike_train, None)
-                    }else{ // with the fault to be injected during the processing phase
-                        output_spikes = snn.process_input(&input_spike_train, Some(injected_fault));
-                    };
-
-                    // Compute accuracy
-                    let max = compute_max_output_spike(output_spikes);
-                    v.push(max);
-                }
-                
-                let a = compute_accuracy(v, &targets);
+                // Process the input sequence
+                // - with the fault already injected
+                // - with the fault to be injected during the processing phase
+                // (a single worker, since the faults are already simulated in parallel)
+                let fault = if already_injected { None } else { Some(injected_fault) };
+                let a = snn.process_batch_with_workers(&input_spikes, &targets, fault, user_selection.decoder, 1).accuracy;
                 let injected_fault = injected_fault.clone();
                 (a, injected_fault)
             });
@@ -123,7 +126,7 @@ impl < N: Neuron + Clone + Send + 'static > SNN < N >
             
     }
 
-    fn generate_random_fault(components: Vec<ComponentType>, fault_type: FaultType,snn: &SNN<N>, num_time_steps: &usize) -> InjectedFault {
+    fn generate_random_fault(components: Vec<ComponentType>, fault_type: FaultType,snn: &SNN, num_time_steps: &usize) -> InjectedFault {
                 
         // If the fault is a transient bit-flip fault
         // -> Select a random time step from the input sequence
@@ -132,25 +135,38 @@ impl < N: Neuron + Clone + Send + 'static > SNN < N >
             time_step = Some(rand::thread_rng().gen_range(0..*num_time_steps) as u64);
         }
 
-        // Select a random component from the list of components
-        let component_index = rand::thread_rng().gen_range(0..components.len());
-        let component_type = components[component_index];
+        // Select a random component among the selected ones that can be faulted in the network
+        // => each neuron model publishes the components that can be faulted in its fault surface
+        let surface = snn.get_fault_surface();
+        let components: Vec<ComponentType> = components.into_iter()
+            .filter(|c| surface.iter().any(|f| f.component_type == *c))
+            .collect();
+        if components.is_empty() {
+            panic!("Error: none of the selected components can be faulted in the neurons of the network");
+        }
+        let component_type = components[rand::thread_rng().gen_range(0..components.len())];
 
         // Identify the category of the component
         let component_category = component_type.get_category();
 
-        // Select a random layer from the list of layers
-        let layer_index = rand::thread_rng().gen_range(0..snn.get_num_layers());
+        // Select a random layer among the layers where the component can be faulted
+        let layer_indices: Vec<usize> = (0..snn.get_num_layers())
+            .filter(|i| snn.get_layer(*i).get_fault_surface().iter().any(|f| f.component_type == component_type))
+            .collect();
+        let layer_index = layer_indices[rand::thread_rng().gen_range(0..layer_indices.len())];
 
         // Select a random index of the component from the list of components of the given type in the layer
+        // => the neurons that don't have the component (layers with different neuron models) are excluded
         let layer = snn.get_layer(layer_index);
-        let num_components = layer.lock().unwrap().get_num_components_from_type(&component_type);
-        let component_index = rand::thread_rng().gen_range(0..num_components);
+        let component_indices = layer.get_faultable_indices(component_type);
+        let component_index = component_indices[rand::thread_rng().gen_range(0..component_indices.len())];
 
-        // Select a random bit index for the component (not for threshold comparators)
+        // Select a random bit index among the bits of the encoding of the component
+        // (not for single-bit components, as the threshold comparator)
         let mut bit_index: Option<usize> = None;
-        if component_type != ComponentType::ThresholdComparator {
-            bit_index = Some(rand::thread_rng().gen_range(0..64));
+        let width = layer.get_component_width(component_type, component_index);
+        if width > 1 {
+            bit_index = Some(rand::thread_rng().gen_range(0..width));
         }
 
         // Create and return the injected fault object
@@ -158,19 +174,39 @@ impl < N: Neuron + Clone + Send + 'static > SNN < N >
 
     }
 
+    /**
+     * Components that can be faulted in the network: the connections, the winner-take-all arbiters and the components
+     * published by the neuron models of all the layers (each component type appears once).
+     */
+    pub fn get_fault_surface(&self) -> Vec<FaultableComponent> {
+        let mut surface = Vec::new();
+        for layer in self.get_layers() {
+            merge_fault_surface(&mut surface, layer.get_fault_surface());
+        }
+        surface
+    }
+
+    // Descriptor of the component in which the fault is injected
+    fn get_faultable_component(&self, injected_fault: &InjectedFault) -> FaultableComponent {
+        let layer = self.get_layer(injected_fault.layer_index);
+        match layer.get_faultable_component(injected_fault.component_type, injected_fault.component_index) {
+            Some(component) => component,
+            None => panic!("Error: the component can't be faulted in the selected neuron"),
+        }
+    }
+
     /**
      * Apply the injected fault to the SNN before processing the input sequence.
      * @param injected_fault: information about the fault to be injected.
      * @return true if the bit in the component is unchanged after the fault is applied.
      */
-    fn apply_fault_before_processing(&self, injected_fault: &mut InjectedFault) -> bool {
+    fn apply_fault_before_processing(&mut self, injected_fault: &mut InjectedFault) -> bool {
         
         // Select the component to be modified
         // 1 - access the layer
         // 2 - inject the fault
         // 3 - check if the bit in the component stays unchanged
-        let layer = self.get_layer(injected_fault.layer_index);
-        let mut layer = layer.lock().unwrap();
+        let layer = self.get_layer_mut(injected_fault.layer_index);
         let bit_unchanged = layer.apply_fault_in_component(injected_fault);
 
         bit_unchanged
@@ -178,62 +214,176 @@ impl < N: Neuron + Clone + Send + 'static > SNN < N >
 
 }
 
-impl <N: Neuron+ Clone + Send + 'static> Layer<N> {
-
-    fn apply_fault_in_component(&mut self, fault_info: &mut InjectedFault) -> bool{
+impl <N: Neuron + Clone + Send + Sync + 'static> Layer<N> {
 
-        let i_extra_weight = fault_info.component_index / self.get_extra_weights()[0].len();
-        let j_extra_weight = fault_info.component_index % self.get_extra_weights()[0].len();
-        let i_intra_weight = fault_info.component_index / self.get_intra_weights()[0].len();
-        let j_intra_weight = fault_info.component_index % self.get_intra_weights()[0].len();
+    pub(crate) fn apply_fault_in_component(&mut self, fault_info: &mut InjectedFault) -> bool{
 
         // Access the variable representing the component
-        // 1 - save the reference to the component in a variable
-        let component = match fault_info.component_type {
-            ComponentType::Extra => &mut self.extra_weights[i_extra_weight][j_extra_weight],
-            ComponentType::Intra => &mut self.intra_weights[i_intra_weight][j_intra_weight],
-            _ => self.neurons[fault_info.component_index].get_parameter_to_fault(fault_info.component_type),
+        // 1 - save the reference to the component and its storage format in a variable
+        let (component, format): (&mut dyn StoredValue, StorageFormat) = match fault_info.component_type {
+            ComponentType::Extra => {
+                let format = self.get_weight_format();
+                let extra_len = self.get_extra_weights()[0].len();
+                match self.get_sparse_extra_weights_mut() {
+                    Some(sparse_extra_weights) => (&mut sparse_extra_weights.values[fault_info.component_index], format),
+                    None => (&mut self.get_weights_mut().extra_weights[fault_info.component_index / extra_len][fault_info.component_index % extra_len], format),
+                }
+            },
+            ComponentType::Intra => {
+                let format = self.get_weight_format();
+                let intra_len = self.get_intra_weights()[0].len();
+                match self.get_sparse_intra_weights_mut() {
+                    Some(sparse_intra_weights) => (&mut sparse_intra_weights.values[fault_info.component_index], format),
+                    None => (&mut self.get_weights_mut().intra_weights[fault_info.component_index / intra_len][fault_info.component_index % intra_len], format),
+                }
+            },
+            ComponentType::KernelWeight => {
+                let format = self.get_weight_format();
+                match self.get_convolution_mut() {
+                    Some(convolution) => (&mut convolution.kernel_weights[fault_info.component_index], format),
+                    None => panic!("Error: the kernel weights can be faulted only in a convolutional layer"),
+                }
+            },
+            ComponentType::SkipWeight => {
+                let format = self.get_weight_format();
+                (self.get_skip_weight_mut(fault_info.component_index), format)
+            },
+            _ => {
+                // only the neurons of the layer are copied (if shared with other copies of the layer), not its weights
+                let neuron = &mut self.get_neurons_mut()[fault_info.component_index];
+                let format = neuron.get_storage_format();
+                (neuron.get_parameter_to_fault(fault_info.component_type), format)
+            }
         };
 
-        let bit_unchanged = false;
-        let bit_index = fault_info.bit_index.unwrap();
-        let var_in_bits = (*component).to_bits();
+        // 2 - inject the fault
+        inject_static_fault(component, format, fault_info)
+    }
 
-        //#to_do remove log
-        println!("component before: {}", *component);
+    /**
+     * Components that can be faulted in the layer: the connections, stored in the weight format of the layer,
+     * the pooling units of a pooling layer, the arbitration logic of a winner-take-all layer and the components published by the neurons 
+     * (each component type appears once).
+     */
+    pub fn get_fault_surface(&self) -> Vec<FaultableComponent> {
+        let mut surface = self.get_connections_surface();
+        if let Some(pooling_unit) = self.get_pooling_unit_component() {
+            surface.push(pooling_unit);
+        }
+        if self.get_winner_take_all().is_some() {
+            surface.push(Self::get_arbiter_component());
+        }
+        for neuron in self.get_neurons().iter() {
+            merge_fault_surface(&mut surface, neuron.get_fault_surface());
+        }
+        surface
+    }
 
-        // Inject the fault
-        match fault_info.fault_type {
-            FaultType::StuckAt0 => {
-                if var_in_bits & (1 << bit_index) == 0 {
+    // The extra weights change during the simulation if the layer learns them with the STDP rule,
+    // they are replaced by the shared kernel weights in a convolutional layer and a pooling layer has none.
+    // The skip weights can be faulted only if the layer has some skip connection.
+    // The delay buffers can be faulted only if some connection of the layer has a delay
+    // (one bit for each time step a spike can be in flight)
+    fn get_connections_surface(&self) -> Vec<FaultableComponent> {
+        let width = self.get_weight_format().get_width();
+        let mut surface = match (self.get_convolution(), self.get_pooling()) {
+            (Some(_), _) => vec![FaultableComponent::new(ComponentType::KernelWeight, "Kernel weights", width, true)],
+            (_, Some(_)) => vec![],
+            _ => vec![FaultableComponent::new(ComponentType::Extra, "Extra weights", width, self.get_stdp().is_none())],
+        };
+        surface.push(FaultableComponent::new(ComponentType::Intra, "Intra weights", width, true));
+        if !self.get_skip_connections().is_empty() {
+            surface.push(FaultableComponent::new(ComponentType::SkipWeight, "Skip weights", width, true));
+        }
+        if self.get_max_delay() > 0 {
+            surface.push(FaultableComponent::new(ComponentType::DelayBuffer, "Delay buffers", (self.get_max_delay() + 1) as usize, false));
+        }
+        surface
+    }
 
-                    //#to_do remove log
-                    println!("component unchanged");
+    // Each neuron of a pooling layer is fed by its own pooling unit
+    fn get_pooling_unit_component(&self) -> Option<FaultableComponent> {
+        let width = self.get_pooling()?.get_output_width(self.get_weight_format());
+        Some(FaultableComponent::new(ComponentType::PoolingUnit, "Pooling unit", width, false))
+    }
 
-                    return true; // The bit is already 0 -> the fault doesn't need to be applied
-                }
-                else {
-                    *component = (*fault_info).apply_fault(*component, 0);
-                }
+    // The arbiter of a winner-take-all layer has a single-bit grant signal for each neuron
+    fn get_arbiter_component() -> FaultableComponent {
+        FaultableComponent::new(ComponentType::WtaArbiter, "WTA arbiter", 1, false)
+    }
+
+    /**
+     * Descriptor of the component of the given type and index in the layer
+     * (None if the neuron doesn't have a component of the given type).
+     */
+    pub fn get_faultable_component(&self, component_type: ComponentType, component_index: usize) -> Option<FaultableComponent> {
+        if component_type == ComponentType::PoolingUnit {
+            return self.get_pooling_unit_component().filter(|_| component_index < self.get_neurons().len());
+        }
+        match component_type.get_category() {
+            ComponentCategory::Connection => self.get_connections_surface().into_iter().find(|c| c.component_type == component_type),
+            ComponentCategory::Arbitration => match self.get_winner_take_all() {
+                Some(_) if component_index < self.get_neurons().len() => Some(Self::get_arbiter_component()),
+                _ => None,
             },
-            FaultType::StuckAt1 => {
-                if var_in_bits & (1 << bit_index) != 0 {
+            _ => self.get_neurons().get(component_index)?.get_fault_surface().into_iter().find(|c| c.component_type == component_type),
+        }
+    }
 
-                    //#to_do remove log
-                    println!("component unchanged");
+    /**
+     * Indices among which the component of the given type can be selected for a fault:
+     * all the connections of the given type, or the neurons of the layer that have the component
+     */
+    pub(crate) fn get_faultable_indices(&self, component_type: ComponentType) -> Vec<usize> {
+        match component_type.get_category() {
+            ComponentCategory::Connection => (0..self.get_num_components_from_type(&component_type)).collect(),
+            _ => (0..self.get_neurons().len())
+                .filter(|i| self.get_faultable_component(component_type, *i).is_some())
+                .collect(),
+        }
+    }
+}
 
-                    return true; // The bit is already 1 -> the fault doesn't need to be applied
-                }
-                else {
-                    *component = (*fault_info).apply_fault(*component, 0);
-                }
-            },
-            _ => panic!("Only static faults can be injected before the processing phase."),
+// Add to the fault surface the components whose type is not already in it,
+// keeping the order of the component types
+fn merge_fault_surface(surface: &mut Vec<FaultableComponent>, components: Vec<FaultableComponent>) {
+    for component in components {
+        if !surface.iter().any(|c| c.component_type == component.component_type) {
+            surface.push(component);
         }
+    }
+    surface.sort_by_key(|c| c.component_type);
+}
 
-        // #to_do remove log
-        println!("component after: {}", *component);
+/**
+ * Inject a static fault in the encoding of the value stored in a component.
+ * @return true if the bit in the component is unchanged after the fault is applied.
+ */
+fn inject_static_fault(component: &mut dyn StoredValue, format: StorageFormat, fault_info: &InjectedFault) -> bool {
 
-        bit_unchanged
+    let bit_index = fault_info.bit_index.unwrap();
+    let var_in_bits = component.get_bits(format);
+
+    // The bit doesn't exist in the encoding of the component (e.g. values stored in less than 64 bits)
+    if bit_index >= format.get_width() {
+        return true;
     }
-}
\ No newline at end of file
+
+    // Inject the fault
+    match fault_info.fault_type {
+        FaultType::StuckAt0 => {
+            if var_in_bits & (1 << bit_index) == 0 {
+                return true; // The bit is already 0 -> the fault doesn't need to be applied
+            }
+        },
+        FaultType::StuckAt1 => {
+            if var_in_bits & (1 << bit_index) != 0 {
+                return true; // The bit is already 1 -> the fault doesn't need to be applied
+            }
+        },
+        _ => panic!("Only static faults can be injected before the processing phase."),
+    }
+    component.set_bits(fault_info.apply_fault(var_in_bits, 0), format);
+
+    false
+}
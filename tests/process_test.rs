@@ -1,6 +1,26 @@
 use std::vec;
+use std::sync::Arc;
 use group02::network::builder::SNNBuilder;
-use group02::network::neuron::lif::Lif;
+use group02::network::layer::Layer;
+use group02::network::snn::SNN;
+use group02::network::neuron::lif::{Lif, ResetMode};
+use group02::network::neuron::cuba_lif::CubaLif;
+use group02::network::neuron::fixed_lif::FixedLif;
+use group02::network::neuron::stochastic_lif::StochasticLif;
+use group02::network::neuron::adaptive_lif::AdaptiveLif;
+use group02::network::neuron::leaky_integrator::LeakyIntegrator;
+use group02::network::fixed_point::{Fixed, QFormat};
+use group02::network::storage_format::StorageFormat;
+use group02::network::stdp::StdpParameters;
+use group02::network::convolution::Convolution;
+use group02::network::pooling::{Pooling, PoolingMode};
+use group02::network::pruning::PruningCriterion;
+use group02::network::decoder::{Decoder, Decision};
+use group02::network::neuron::izhikevich::Izhikevich;
+use group02::network::neuron::adex::{AdEx, AdExParameters};
+use group02::network::neuron::neuron::Neuron;
+use group02::network::neuron::neuron_model::NeuronModel;
+use group02::network::config::{build_network_from_setup, NetworkSetup, LifLayerParameters, LayerParameter};
 
 #[test]
 fn test_add_layers() {
@@ -66,9 +86,9 @@ fn test_snn_layers() {
     assert_eq!(snn.get_layers().get(0).is_some(), true);
     assert_eq!(snn.get_layers().get(1).is_some(), true);
     assert_eq!(snn.get_layers().get(2).is_some(), false);
-    assert_eq!(snn.get_layers().get(0).unwrap().lock().unwrap().get_neurons().len(), 2);
-    assert_eq!(snn.get_layers().get(1).unwrap().lock().unwrap().get_neurons().len(), 1);
-    assert_eq!(snn.get_layers().get(0).unwrap().lock().unwrap().get_extra_weights().len(), 2);
+    assert_eq!(snn.get_layers().get(0).unwrap().get_num_neurons(), 2);
+    assert_eq!(snn.get_layers().get(1).unwrap().get_num_neurons(), 1);
+    assert_eq!(snn.get_layers().get(0).unwrap().downcast_ref::<Layer<Lif>>().unwrap().get_extra_weights().len(), 2);
 }
 
 #[test]
@@ -273,4 +293,1062 @@ fn test_snn_wrong_input_spikes() {
         ]).build();
 
     let _output_spikes = snn.process_input(&vec![vec![0,4],vec![0,1]], None);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_process_snn_izhikevich() {
+    let snn = SNNBuilder::new(2)
+        .add_layer(vec![
+            Izhikevich::new(0.02, 0.2, -65.0, 8.0, 30.0, 1.0),
+            Izhikevich::new(0.02, 0.2, -65.0, 8.0, 30.0, 1.0)], vec![
+            vec![40.0, 0.0],
+            vec![5.0, 5.0]], vec![
+            vec![0.0, 0.0],
+            vec![0.0, 0.0]
+        ])
+        .build();
+
+    let output_spikes = snn.process_input(&vec![vec![1,1,0,0],vec![1,1,0,1]], None);
+    let output_expected: Vec<Vec<u8>> = vec![vec![0,1,0,0],vec![0,0,0,0]];
+    assert_eq!(output_spikes, output_expected);
+}
+
+#[test]
+fn test_process_snn_izhikevich_and_lif() {
+    // the neuron model can be selected at runtime through the NeuronModel wrapper
+    let snn = SNNBuilder::new(2)
+        .add_layer(vec![
+            NeuronModel::from(Izhikevich::new(0.02, 0.2, -65.0, 8.0, 30.0, 1.0)),
+            NeuronModel::from(Lif::new(0.1, 0.05, 0.3, 1.0, 1.0))], vec![
+            vec![40.0, 0.0],
+            vec![0.3, 0.4]], vec![
+            vec![0.0, 0.0],
+            vec![0.0, 0.0]
+        ])
+        .build();
+
+    let output_spikes = snn.process_input(&vec![vec![1,1,0],vec![0,0,1]], None);
+    let output_expected: Vec<Vec<u8>> = vec![vec![0,1,0],vec![1,1,1]];
+    assert_eq!(output_spikes, output_expected);
+}
+
+#[test]
+fn test_process_snn_adex() {
+    let p = AdExParameters {
+        reset_potential: -58.0, resting_potential: -70.0, threshold: -50.0, peak_potential: 0.0, slope_factor: 2.0,
+        tau: 10.0, adaptation_coupling: 0.1, adaptation_increment: 2.7, tau_adaptation: 144.0, dt: 1.0
+    };
+    let snn = SNNBuilder::new(2)
+        .add_layer(vec![
+            AdEx::new(p),
+            AdEx::new(p)], vec![
+            vec![60.0, 0.0],
+            vec![5.0, 5.0]], vec![
+            vec![0.0, 0.0],
+            vec![0.0, 0.0]
+        ])
+        .build();
+
+    let output_spikes = snn.process_input(&vec![vec![1,1,0,0],vec![1,1,0,1]], None);
+    let output_expected: Vec<Vec<u8>> = vec![vec![0,1,0,0],vec![0,0,0,0]];
+    assert_eq!(output_spikes, output_expected);
+}
+
+#[test]
+fn test_adex_spike_triggered_adaptation() {
+    let mut neuron = AdEx::new(AdExParameters {
+        reset_potential: -58.0, resting_potential: -70.0, threshold: -50.0, peak_potential: 0.0, slope_factor: 2.0,
+        tau: 10.0, adaptation_coupling: 0.1, adaptation_increment: 2.7, tau_adaptation: 144.0, dt: 1.0
+    });
+
+    // the spike resets the membrane potential and increments the adaptation current by b
+    assert_eq!(neuron.process_input(0, 80.0, None), 1);
+    assert_eq!(neuron.get_v_mem(), -58.0);
+    assert_eq!(neuron.get_w(), 2.7);
+
+    // the adaptation current decays while it pulls down the membrane potential
+    assert_eq!(neuron.process_input(1, 0.0, None), 0);
+    assert!(neuron.get_w() < 2.7);
+    assert!(neuron.get_v_mem() < -58.0);
+}
+
+#[test]
+fn test_process_snn_lif_refractory_period() {
+    let mut lif = Lif::new(0.0, 0.0, 0.5, 1.0, 1.0);
+    lif.set_refractory_period(2);
+    let snn = SNNBuilder::new(1)
+        .add_layer(vec![
+            Lif::new(0.0, 0.0, 0.5, 1.0, 1.0),
+            lif], vec![
+            vec![1.0],
+            vec![1.0]], vec![
+            vec![0.0, 0.0],
+            vec![0.0, 0.0]
+        ])
+        .build();
+
+    // after each spike the second neuron ignores its input for 2 time steps
+    let output_spikes = snn.process_input(&vec![vec![1,1,1,1,1,0,1]], None);
+    let output_expected: Vec<Vec<u8>> = vec![vec![1,1,1,1,1,0,1],vec![1,0,0,1,0,0,1]];
+    assert_eq!(output_spikes, output_expected);
+}
+
+#[test]
+fn test_process_snn_lif_reset_modes() {
+    // infinite tau => no leakage, so the membrane potential only depends on the input and the reset mechanism
+    let lif = |reset_mode: ResetMode| {
+        let mut lif = Lif::new(0.0, 0.0, 1.0, f64::INFINITY, 1.0);
+        lif.set_reset_mode(reset_mode);
+        lif
+    };
+    let snn = SNNBuilder::new(1)
+        .add_layer(vec![
+            lif(ResetMode::ToValue),
+            lif(ResetMode::Subtract),
+            lif(ResetMode::None)], vec![
+            vec![0.75],
+            vec![0.75],
+            vec![0.75]], vec![
+            vec![0.0, 0.0, 0.0],
+            vec![0.0, 0.0, 0.0],
+            vec![0.0, 0.0, 0.0]
+        ])
+        .build();
+
+    // - value: the membrane potential is set to the reset potential after each spike
+    // - subtract: the residual potential above the threshold is kept for the following time steps
+    // - none: once the threshold is reached the neuron keeps spiking
+    let output_spikes = snn.process_input(&vec![vec![1,1,1,1,1,1]], None);
+    let output_expected: Vec<Vec<u8>> = vec![vec![0,1,0,1,0,1],vec![0,1,1,0,1,1],vec![0,1,1,1,1,1]];
+    assert_eq!(output_spikes, output_expected);
+}
+
+#[test]
+fn test_lif_refractory_hold() {
+    // value: during the refractory period the membrane potential is held at the reset potential
+    let mut neuron = Lif::new(0.25, 0.0, 0.5, f64::INFINITY, 1.0);
+    neuron.set_refractory_period(1);
+    assert_eq!(neuron.process_input(0, 1.0, None), 1);
+    assert_eq!(neuron.get_v_mem(), 0.25);
+    assert_eq!(neuron.process_input(1, 1.0, None), 0);
+    assert_eq!(neuron.get_v_mem(), 0.25);
+    assert_eq!(neuron.process_input(2, 0.0, None), 0);
+
+    // subtract: the membrane potential is held at its value after the spike
+    // and the threshold is subtracted at the end of the refractory period
+    let mut neuron = Lif::new(0.25, 0.0, 0.5, f64::INFINITY, 1.0);
+    neuron.set_refractory_period(1);
+    neuron.set_reset_mode(ResetMode::Subtract);
+    assert_eq!(neuron.process_input(0, 1.0, None), 1);
+    assert_eq!(neuron.get_v_mem(), 1.0);
+    assert_eq!(neuron.process_input(1, 1.0, None), 0);
+    assert_eq!(neuron.get_v_mem(), 1.0);
+    assert_eq!(neuron.process_input(2, 0.0, None), 0);
+    assert_eq!(neuron.get_v_mem(), 0.5);
+}
+
+#[test]
+fn test_process_snn_cuba_lif() {
+    // alpha = 0.5 and infinite tau => the membrane integrates the decaying synaptic current without leakage
+    let cuba = CubaLif::new(Lif::new(0.0, 0.0, 2.5, f64::INFINITY, 1.0), 1.0 / 2f64.ln());
+    let snn = SNNBuilder::new(1)
+        .add_layer(vec![
+            NeuronModel::from(Lif::new(0.0, 0.0, 2.5, f64::INFINITY, 1.0)),
+            NeuronModel::from(cuba)], vec![
+            vec![1.0],
+            vec![1.0]], vec![
+            vec![0.0, 0.0],
+            vec![0.0, 0.0]
+        ])
+        .build();
+
+    // - Lif: v_mem = 1, 1, 1, 2 => the threshold is never reached
+    // - CubaLif: i_syn = 1, 0.5, 0.25, 1.125 and v_mem = 1, 1.5, 1.75, 2.875 => spike at the last instant,
+    //   even if the layer processes the neuron only when it receives an input spike
+    let output_spikes = snn.process_input(&vec![vec![1,0,0,1]], None);
+    let output_expected: Vec<Vec<u8>> = vec![vec![0,0,0,0],vec![0,0,0,1]];
+    assert_eq!(output_spikes, output_expected);
+}
+
+#[test]
+fn test_process_snn_stochastic_lif() {
+    // neurons with infinite tau: v_mem = 1, 2 => the Lif spikes when v_mem > 1.5
+    let stochastic = |temperature: f64, seed: u64| StochasticLif::new(0.0, 0.0, 1.5, f64::INFINITY, 1.0, temperature, seed);
+    let snn_lif = SNNBuilder::new(1)
+        .add_layer(vec![Lif::new(0.0, 0.0, 1.5, f64::INFINITY, 1.0)], vec![vec![1.0]], vec![vec![0.0]])
+        .build();
+    let snn_cold = SNNBuilder::new(1)
+        .add_layer(vec![stochastic(1e-9, 7)], vec![vec![1.0]], vec![vec![0.0]])
+        .build();
+
+    // a temperature close to 0 gives a deterministic firing, as the Lif
+    let input_spikes = vec![vec![1,1,1,1,1,1]];
+    assert_eq!(snn_cold.process_input(&input_spikes, None), snn_lif.process_input(&input_spikes, None));
+
+    // with a high temperature the output is random, but the same seed gives the same output
+    // (also in different inferences with the same network, since the generator is reseeded)
+    let snn_hot = SNNBuilder::new(1)
+        .add_layer(vec![stochastic(10.0, 7)], vec![vec![1.0]], vec![vec![0.0]])
+        .build();
+    let snn_hot_copy = SNNBuilder::new(1)
+        .add_layer(vec![stochastic(10.0, 7)], vec![vec![1.0]], vec![vec![0.0]])
+        .build();
+    let input_spikes = vec![vec![1; 50]];
+    let output_spikes = snn_hot.process_input(&input_spikes, None);
+    assert_eq!(snn_hot.process_input(&input_spikes, None), output_spikes);
+    assert_eq!(snn_hot_copy.process_input(&input_spikes, None), output_spikes);
+    assert_ne!(output_spikes, snn_lif.process_input(&input_spikes, None));
+}
+
+#[test]
+#[should_panic]
+fn test_non_positive_temperature() { // this test should panic
+    StochasticLif::new(0.0, 0.0, 1.5, 1.0, 1.0, 0.0, 7);
+}
+
+// Network 1-2-2 with per-layer LIF parameters, whose files are written in a temporary directory
+fn get_heterogeneous_setup(dir_name: &str, thresholds: &str) -> NetworkSetup {
+    let dir = std::env::temp_dir().join(dir_name);
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = |name: &str, content: &str| {
+        let path = dir.join(name);
+        std::fs::write(&path, content).unwrap();
+        path.to_str().unwrap().to_string()
+    };
+    let extra1 = file("weights1.txt", "1.0\n1.0\n");
+    let extra2 = file("weights2.txt", "1.0 0.0\n0.0 1.0\n");
+    let input = file("input.txt", "# New slice\n1\n1\n1\n1\n");
+    let targets = file("targets.txt", "0\n");
+    let thresholds = file("thresholds.txt", thresholds);
+
+    NetworkSetup {
+        input_layer: 1,
+        hidden_layers: vec![2],
+        output_length: 2,
+        winner_take_all: None,
+        extra_weights: vec![format!("'{}'", extra1), format!("'{}'", extra2)],
+        intra_weights: Vec::new(),
+        delays: Vec::new(),
+        resting_potential: 0.0,
+        reset_potential: 0.0,
+        threshold: 1.0,
+        beta: 1.0,
+        tau: f64::INFINITY,
+        dt: 1.0,
+        refractory_period: 0,
+        reset_mode: ResetMode::ToValue,
+        spike_length: 4,
+        batch_size: 1,
+        input_spike_train: format!("'{}'", input),
+        target_file: format!("'{}'", targets),
+        neuron_model: String::from("LIF"),
+        izhikevich_parameters: None,
+        adex_parameters: None,
+        synaptic_tau: None,
+        stochastic_parameters: None,
+        stdp_parameters: None,
+        storage_format: StorageFormat::F64,
+        lif_layer_parameters: vec![
+            LifLayerParameters { threshold: Some(LayerParameter::File(thresholds)), ..Default::default() },
+            LifLayerParameters { threshold: Some(LayerParameter::Scalar(0.5)), ..Default::default() }
+        ]
+    }
+}
+
+#[test]
+#[should_panic(expected = "support only the f64 storage format")]
+fn test_build_network_from_setup_unsupported_storage_format() {
+    let mut setup = get_heterogeneous_setup("group02_unsupported_storage_format", "0.5\n1.5\n");
+    setup.neuron_model = String::from("AdEx");
+    setup.adex_parameters = Some(AdExParameters {
+        reset_potential: -58.0, resting_potential: -70.0, threshold: -50.0, peak_potential: 0.0, slope_factor: 2.0,
+        tau: 10.0, adaptation_coupling: 0.1, adaptation_increment: 2.7, tau_adaptation: 144.0, dt: 1.0
+    });
+    setup.storage_format = StorageFormat::F16;
+    build_network_from_setup(setup);
+}
+
+#[test]
+fn test_process_snn_lif_layer_parameters() {
+    let (snn, input_spikes, _targets) = build_network_from_setup(get_heterogeneous_setup("group02_layer_parameters", "0.5\n1.5\n"));
+
+    // hidden layer: thresholds 0.5 and 1.5 read from file, output layer: threshold 0.5 for all the neurons
+    let output_spikes = snn.process_input(&input_spikes[0], None);
+    let output_expected: Vec<Vec<u8>> = vec![vec![1,1,1,1],vec![0,1,0,1]];
+    assert_eq!(output_spikes, output_expected);
+}
+
+#[test]
+#[should_panic(expected = "contains 3 values, but the layer has 2 neurons")]
+fn test_lif_layer_parameters_wrong_length() {
+    build_network_from_setup(get_heterogeneous_setup("group02_layer_parameters_wrong_length", "0.5\n1.5\n1.0\n"));
+}
+
+#[test]
+fn test_fixed_point_arithmetic() {
+    let q = QFormat::from_name("Q8.8").unwrap();
+    assert_eq!(q.get_width(), 16);
+    assert!(QFormat::from_name("Q30.8").is_err());
+
+    // rounding to the nearest representable value
+    assert_eq!(Fixed::from_f64(0.3, q).to_f64(), 77.0 / 256.0);
+    // saturation to the maximum/minimum representable value
+    assert_eq!(Fixed::from_f64(300.0, q).to_f64(), 127.99609375);
+    assert_eq!(Fixed::from_f64(100.0, q).saturating_add(Fixed::from_f64(100.0, q)).to_f64(), 127.99609375);
+    assert_eq!(Fixed::from_f64(-100.0, q).saturating_sub(Fixed::from_f64(100.0, q)).to_f64(), -128.0);
+    assert_eq!(Fixed::from_f64(1.5, q).saturating_mul(Fixed::from_f64(-2.25, q)).to_f64(), -3.375);
+    // two's complement encoding on 16 bits
+    assert_eq!(Fixed::from_f64(-1.0, q).get_bits(), 0xFF00);
+    assert_eq!(Fixed::from_bits(0xFF00, q).to_f64(), -1.0);
+}
+
+#[test]
+fn test_process_snn_fixed_lif() {
+    // Q4.4: the weight 0.3 is stored as 0.3125 and the threshold 0.9 as 0.875
+    let q = QFormat::new(4, 4);
+    let snn_fixed = SNNBuilder::new(1)
+        .with_weight_format(StorageFormat::Fixed(q))
+        .add_layer(vec![FixedLif::new(0.0, 0.0, 0.9, 1.0, q)], vec![vec![0.3]], vec![vec![0.0]])
+        .build();
+    let snn_float = SNNBuilder::new(1)
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.9, f64::INFINITY, 1.0)], vec![vec![0.3]], vec![vec![0.0]])
+        .build();
+
+    // the quantization error makes the fixed-point neuron spike after three input spikes
+    let input_spikes = vec![vec![1,1,1]];
+    assert_eq!(snn_fixed.process_input(&input_spikes, None), vec![vec![0,0,1]]);
+    assert_eq!(snn_float.process_input(&input_spikes, None), vec![vec![0,0,0]]);
+}
+
+#[test]
+fn test_process_snn_fixed_point_saturation() {
+    // Q4.4: the weighted sum 5.0 + 5.0 saturates to 7.9375, below the threshold 7.95 (stored as 7.9375)
+    let q = QFormat::new(4, 4);
+    let snn = SNNBuilder::new(2)
+        .with_weight_format(StorageFormat::Fixed(q))
+        .add_layer(vec![Lif::new(0.0, 0.0, 7.9375, 1.0, 1.0)], vec![vec![5.0, 5.0]], vec![vec![0.0]])
+        .build();
+
+    let output_spikes = snn.process_input(&vec![vec![1,1],vec![1,0]], None);
+    assert_eq!(output_spikes, vec![vec![0,0]]);
+}
+
+#[test]
+fn test_storage_format_encoding() {
+    assert_eq!(StorageFormat::from_name("f32").unwrap().get_width(), 32);
+    assert_eq!(StorageFormat::from_name("Q16.16").unwrap(), StorageFormat::Fixed(QFormat::new(16, 16)));
+    assert!(StorageFormat::from_name("f8").is_err());
+
+    // encoding of 1.0 in the different formats
+    assert_eq!(StorageFormat::F32.encode(1.0), 0x3F80_0000);
+    assert_eq!(StorageFormat::F16.encode(1.0), 0x3C00);
+    assert_eq!(StorageFormat::BF16.encode(1.0), 0x3F80);
+    // rounding to the nearest representable value
+    assert_eq!(StorageFormat::F16.quantize(0.1), 0.0999755859375);
+    assert_eq!(StorageFormat::BF16.quantize(0.1), 0.10009765625);
+    assert_eq!(StorageFormat::F64.quantize(0.1), 0.1);
+}
+
+#[test]
+fn test_process_snn_reduced_precision() {
+    let snn = |format: StorageFormat| {
+        let mut lif = Lif::new(0.0, 0.0, 0.3, f64::INFINITY, 1.0);
+        lif.set_storage_format(format);
+        SNNBuilder::new(1)
+            .with_weight_format(format)
+            .add_layer(vec![lif], vec![vec![0.1]], vec![vec![0.0]])
+            .build()
+    };
+
+    // f64: 0.1 + 0.1 + 0.1 > 0.3
+    // f16: the weight is stored as 0.09997..., while the threshold is stored as 0.30004...
+    let input_spikes = vec![vec![1,1,1]];
+    assert_eq!(snn(StorageFormat::F64).process_input(&input_spikes, None), vec![vec![0,0,1]]);
+    assert_eq!(snn(StorageFormat::F16).process_input(&input_spikes, None), vec![vec![0,0,0]]);
+}
+
+#[test]
+fn test_process_snn_synaptic_delays() {
+    let snn = SNNBuilder::new(1)
+        .add_layer(vec![
+            Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0),
+            Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0)], vec![
+            vec![1.0],
+            vec![1.0]], vec![
+            vec![0.0, 0.0],
+            vec![0.0, 0.0]])
+        .with_delays(vec![vec![0], vec![2]])
+        .add_layer(vec![
+            Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0)], vec![
+            vec![0.0, 1.0]], vec![
+            vec![0.0]])
+        .with_delays(vec![vec![0, 1]])
+        .build();
+
+    // the spike of the input reaches the second neuron of the first layer after 2 time steps
+    // and the output neuron after 3 time steps
+    assert_eq!(snn.process_input(&vec![vec![1,0,0,0,0]], None), vec![vec![0,0,0,1,0]]);
+    // the spikes arriving after the end of the input are discarded
+    assert_eq!(snn.process_input(&vec![vec![0,0,0,1]], None), vec![vec![0,0,0,0]]);
+
+    let snn = SNNBuilder::new(1)
+        .add_layer(vec![
+            Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0),
+            Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0)], vec![
+            vec![1.0],
+            vec![1.0]], vec![
+            vec![0.0, 0.0],
+            vec![0.0, 0.0]])
+        .with_delays(vec![vec![0], vec![2]])
+        .build();
+    assert_eq!(snn.process_input(&vec![vec![1,0,0,1,0]], None), vec![vec![1,0,0,1,0], vec![0,0,1,0,0]]);
+}
+
+#[test]
+#[should_panic(expected = "The delay of a connection should be at most 63 time steps")]
+fn test_synaptic_delay_too_long() {
+    SNNBuilder::new(1)
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0)], vec![vec![1.0]], vec![vec![0.0]])
+        .with_delays(vec![vec![64]]);
+}
+
+#[test]
+fn test_process_snn_delays_from_file() {
+    let delays = std::env::temp_dir().join("group02_layer_parameters_delays.txt");
+    std::fs::write(&delays, "1 0\n0 1\n").unwrap();
+    let mut setup = get_heterogeneous_setup("group02_layer_parameters_delays", "0.5\n1.5\n");
+    setup.delays = vec![None, Some(LayerParameter::File(delays.to_str().unwrap().to_string()))];
+    let (snn, input_spikes, _targets) = build_network_from_setup(setup);
+
+    // the output of the hidden layer ([1,1,1,1] and [0,1,0,1]) reaches the output layer after 1 time step
+    let output_spikes = snn.process_input(&input_spikes[0], None);
+    let output_expected: Vec<Vec<u8>> = vec![vec![0,1,1,1],vec![0,0,1,0]];
+    assert_eq!(output_spikes, output_expected);
+}
+
+#[test]
+fn test_process_snn_winner_take_all() {
+    let snn = |k: usize| {
+        SNNBuilder::new(1)
+            .add_layer(vec![
+                Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0),
+                Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0),
+                Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0)], vec![
+                vec![1.0],
+                vec![3.0],
+                vec![3.0]], vec![
+                vec![0.0, 0.0, 0.0],
+                vec![0.0, 0.0, 0.0],
+                vec![0.0, 0.0, 0.0]])
+            .with_winner_take_all(k)
+            .build()
+    };
+
+    // all the neurons reach the threshold: the ones with the largest input win (the lowest index in case of ties)
+    let input_spikes = vec![vec![1,0,1]];
+    assert_eq!(snn(1).process_input(&input_spikes, None), vec![vec![0,0,0], vec![1,0,1], vec![0,0,0]]);
+    assert_eq!(snn(2).process_input(&input_spikes, None), vec![vec![0,0,0], vec![1,0,1], vec![1,0,1]]);
+    assert_eq!(snn(3).process_input(&input_spikes, None), vec![vec![1,0,1], vec![1,0,1], vec![1,0,1]]);
+}
+
+#[test]
+fn test_process_snn_winner_take_all_inhibited_neuron() {
+    let snn = SNNBuilder::new(2)
+        .add_layer(vec![
+            Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0),
+            Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0)], vec![
+            vec![1.0, 0.0],
+            vec![0.6, 0.3]], vec![
+            vec![0.0, 0.0],
+            vec![0.0, 0.0]])
+        .with_winner_take_all(1)
+        .build();
+
+    // the second neuron loses the arbitration at t=0 without being reset (0.6),
+    // so the input at t=1 brings it above the threshold (0.9) and it wins
+    let output_spikes = snn.process_input(&vec![vec![1,0], vec![0,1]], None);
+    assert_eq!(output_spikes, vec![vec![1,0], vec![0,1]]);
+    assert_eq!(snn.process_input_readout(&vec![vec![1], vec![0]], None), vec![0.0, 0.6]);
+}
+
+#[test]
+#[should_panic(expected = "The number of winners should be between 1 and the number of neurons of the layer")]
+fn test_winner_take_all_too_many_winners() {
+    SNNBuilder::new(1)
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0)], vec![vec![1.0]], vec![vec![0.0]])
+        .with_winner_take_all(2);
+}
+
+#[test]
+fn test_process_snn_stdp() {
+    let snn = SNNBuilder::new(2)
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0)], vec![vec![0.6, 0.2]], vec![vec![0.0]])
+        .with_stdp(StdpParameters::new(0.1, 0.1, 1.0, 1.0, 0.0, 1.0))
+        .build();
+
+    // t = 0: the first input makes the neuron spike => potentiation of the first weight (+0.1)
+    // t = 1: the second input arrives after the spike of the neuron => depression of the second weight (-0.1 * e^-1)
+    assert_eq!(snn.process_input(&vec![vec![1,0,0], vec![0,1,0]], None), vec![vec![1,0,0]]);
+    let weights = snn.get_learned_extra_weights(0);
+    assert!((weights[0][0] - 0.7).abs() < 1e-12);
+    assert!((weights[0][1] - (0.2 - 0.1 * (-1f64).exp())).abs() < 1e-12);
+
+    // the learned weights are kept for the next inference, and bounded in [w_min, w_max]
+    for _ in 0..5 {
+        snn.process_input(&vec![vec![1,0,0], vec![0,1,0]], None);
+    }
+    let weights = snn.get_learned_extra_weights(0);
+    assert_eq!(weights[0][0], 1.0);
+    assert_eq!(weights[0][1], 0.0);
+}
+
+#[test]
+fn test_process_snn_adaptive_lif() {
+    // the offset of the threshold is halved in each time step
+    let adaptive = AdaptiveLif::new(Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0), 1.0, 1.0 / 2f64.ln());
+    let snn_adaptive = SNNBuilder::new(1)
+        .add_layer(vec![adaptive], vec![vec![0.9]], vec![vec![0.0]])
+        .build();
+    let snn_lif = SNNBuilder::new(1)
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0)], vec![vec![0.9]], vec![vec![0.0]])
+        .build();
+
+    // after each spike the threshold is raised to 1.5 and then decays to 1.0, 0.75...
+    let input_spikes = vec![vec![1,1,1,1,1]];
+    assert_eq!(snn_adaptive.process_input(&input_spikes, None), vec![vec![1,0,1,0,1]]);
+    assert_eq!(snn_lif.process_input(&input_spikes, None), vec![vec![1,1,1,1,1]]);
+}
+
+#[test]
+fn test_adaptive_lif_layer_parameters() {
+    let mut setup = get_heterogeneous_setup("group02_layer_parameters_adaptive", "0.5\n1.5\n");
+    setup.lif_layer_parameters[1].threshold_increment = Some(LayerParameter::Scalar(1.0));
+    setup.lif_layer_parameters[1].threshold_tau = Some(LayerParameter::Scalar(1.0 / 2f64.ln()));
+    let (snn, _input_spikes, _targets) = build_network_from_setup(setup);
+
+    // only the output layer is made of adaptive threshold neurons
+    assert!(snn.get_layer(0).downcast_ref::<Layer<NeuronModel>>().unwrap().get_neurons().iter().all(|n| matches!(n, NeuronModel::Lif(_))));
+    assert!(snn.get_layer(1).downcast_ref::<Layer<NeuronModel>>().unwrap().get_neurons().iter().all(|n| matches!(n, NeuronModel::AdaptiveLif(_))));
+}
+
+#[test]
+fn test_process_snn_convolution() {
+    // 1x3x3 input, two 2x2 kernels (diagonal and anti-diagonal) => 2x2x2 output
+    let convolution = Convolution::new((1, 3, 3), vec![
+        vec![vec![vec![1.0, 0.0], vec![0.0, 1.0]]],
+        vec![vec![vec![0.0, 1.0], vec![1.0, 0.0]]]], 1, 0);
+    assert_eq!(convolution.get_output_shape(), (2, 2, 2));
+    assert_eq!(convolution.get_kernel_weight(1, 0, 0, 1), 1.0);
+
+    let snn = SNNBuilder::new(9)
+        .add_conv_layer(vec![Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0); 8], convolution, vec![vec![0.0; 8]; 8])
+        .build();
+
+    // spikes of the pixels (0,0) and (1,1) of the input
+    let input_spikes = vec![vec![1], vec![0], vec![0], vec![0], vec![1], vec![0], vec![0], vec![0], vec![0]];
+    assert_eq!(snn.process_input(&input_spikes, None), vec![
+        vec![1], vec![0], vec![0], vec![1],
+        vec![0], vec![1], vec![1], vec![0]]);
+}
+
+#[test]
+fn test_process_snn_convolution_stride_and_padding() {
+    // 1x3x3 input padded with zeros, 2x2 kernel with stride 2 => 1x2x2 output
+    let convolution = Convolution::new((1, 3, 3), vec![vec![vec![vec![1.0, 1.0], vec![1.0, 1.0]]]], 2, 1);
+    assert_eq!(convolution.get_output_shape(), (1, 2, 2));
+
+    let snn = SNNBuilder::new(9)
+        .add_conv_layer(vec![Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0); 4], convolution, vec![vec![0.0; 4]; 4])
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0)], vec![vec![1.0, 0.0, 0.0, 0.0]], vec![vec![0.0]])
+        .build();
+
+    // the pixel (0,0) is seen only by the first position, the pixel (2,2) only by the last one
+    let mut input_spikes = vec![vec![0, 0]; 9];
+    input_spikes[0] = vec![1, 0];
+    input_spikes[8] = vec![0, 1];
+    assert_eq!(snn.process_input(&input_spikes, None), vec![vec![1, 0]]);
+}
+
+#[test]
+#[should_panic(expected = "The size of the input volume of the layer should be equal to the dimension of the input layer")]
+fn test_convolution_wrong_input_length() {
+    let convolution = Convolution::new((1, 3, 3), vec![vec![vec![vec![1.0, 1.0], vec![1.0, 1.0]]]], 1, 0);
+    SNNBuilder::new(8)
+        .add_conv_layer(vec![Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0); 4], convolution, vec![vec![0.0; 4]; 4]);
+}
+
+#[test]
+fn test_process_snn_pooling() {
+    // 1x4x4 input, 2x2 windows with stride 2 => 1x2x2 output
+    let snn = |mode: PoolingMode, threshold: f64| {
+        SNNBuilder::new(16)
+            .add_pool_layer(vec![Lif::new(0.0, 0.0, threshold, f64::INFINITY, 1.0); 4], Pooling::new((1, 4, 4), 2, 2, mode))
+            .build()
+    };
+
+    // two spikes in the first window, one in the second, none in the third, four in the fourth
+    let mut input_spikes = vec![vec![0]; 16];
+    for i in [0, 5, 2, 10, 11, 14, 15] {
+        input_spikes[i] = vec![1];
+    }
+
+    // OR of the spikes in the window
+    assert_eq!(snn(PoolingMode::Max, 0.5).process_input(&input_spikes, None), vec![vec![1], vec![1], vec![0], vec![1]]);
+    // number of spikes in the window
+    assert_eq!(snn(PoolingMode::Sum, 1.5).process_input(&input_spikes, None), vec![vec![1], vec![0], vec![0], vec![1]]);
+    // fraction of the window that spiked
+    assert_eq!(snn(PoolingMode::Average, 0.75).process_input(&input_spikes, None), vec![vec![0], vec![0], vec![0], vec![1]]);
+}
+
+#[test]
+fn test_process_snn_convolution_and_pooling() {
+    // 1x3x3 input, 2x2 kernel => 1x2x2 feature map, pooled by a 2x2 window => 1 neuron
+    let convolution = Convolution::new((1, 3, 3), vec![vec![vec![vec![1.0, 0.0], vec![0.0, 0.0]]]], 1, 0);
+    let snn = SNNBuilder::new(9)
+        .add_conv_layer(vec![Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0); 4], convolution, vec![vec![0.0; 4]; 4])
+        .add_pool_layer(vec![Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0)], Pooling::new((1, 2, 2), 2, 1, PoolingMode::Max))
+        .build();
+
+    // the pixel (2,2) is not seen by the top-left corner of the kernel
+    let mut input_spikes = vec![vec![0, 0]; 9];
+    input_spikes[4] = vec![1, 0];
+    input_spikes[8] = vec![0, 1];
+    assert_eq!(snn.process_input(&input_spikes, None), vec![vec![1, 0]]);
+}
+
+#[test]
+fn test_process_heterogeneous_snn() {
+    // pooling layer of LIF neurons followed by a dense layer of Izhikevich neurons
+    let pooling_layers = SNNBuilder::new(4)
+        .add_pool_layer(vec![Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0)], Pooling::new((1, 2, 2), 2, 1, PoolingMode::Max))
+        .build_layers();
+    let izhikevich_layers = || SNNBuilder::new(1)
+        .add_layer(vec![Izhikevich::new(0.02, 0.2, -65.0, 8.0, 30.0, 1.0)], vec![vec![60.0]], vec![vec![0.0]])
+        .build_layers();
+    let snn = SNN::new(pooling_layers.into_iter().chain(izhikevich_layers()).collect());
+    assert_eq!(snn.get_num_layers(), 2);
+    assert!(snn.get_layer(0).downcast_ref::<Layer<Lif>>().is_some());
+    assert!(snn.get_layer(1).downcast_ref::<Layer<Izhikevich>>().is_some());
+
+    // the Izhikevich layer receives the OR of the input spikes
+    let input_spikes = vec![vec![1, 0, 0, 1, 0, 1], vec![0, 0, 0, 1, 0, 1], vec![1, 0, 0, 0, 0, 1], vec![0, 0, 0, 0, 0, 1]];
+    let or_spikes = vec![vec![1, 0, 0, 1, 0, 1]];
+    assert_eq!(snn.process_input(&input_spikes, None), SNN::new(izhikevich_layers()).process_input(&or_spikes, None));
+}
+
+#[test]
+#[should_panic(expected = "The input of the layer 1 is not consistent with the number of neurons of the previous layer")]
+fn test_heterogeneous_snn_inconsistent_layers() {
+    let lif_layers = SNNBuilder::new(1)
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0)], vec![vec![1.0]], vec![vec![0.0]])
+        .build_layers();
+    let izhikevich_layers = SNNBuilder::new(2)
+        .add_layer(vec![Izhikevich::new(0.02, 0.2, -65.0, 8.0, 30.0, 1.0)], vec![vec![1.0, 1.0]], vec![vec![0.0]])
+        .build_layers();
+    SNN::new(lif_layers.into_iter().chain(izhikevich_layers).collect());
+}
+
+#[test]
+fn test_process_snn_skip_connections() {
+    // the output neuron fires only if the spikes of the two inputs arrive in the same time instant:
+    // the first input through the hidden layers, the second one through the skip connection with the first layer
+    let relay = || Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0);
+    let snn = SNNBuilder::new(2)
+        .add_layer(vec![relay(), relay()], vec![vec![1.0, 0.0], vec![0.0, 1.0]], vec![vec![0.0, 0.0], vec![0.0, 0.0]])
+        .add_layer(vec![relay()], vec![vec![1.0, 0.0]], vec![vec![0.0]])
+        .add_layer(vec![Lif::new(0.0, 0.0, 1.0, 1e-3, 1.0)], vec![vec![0.6]], vec![vec![0.0]])
+        .with_skip_connection(0, vec![vec![0.0, 0.6]])
+        .build();
+    assert_eq!(snn.get_layer(2).get_skip_sources(), vec![(0, 2)]);
+
+    let input_spikes = vec![vec![1, 1, 0, 0, 1], vec![1, 0, 1, 0, 1]];
+    assert_eq!(snn.process_input(&input_spikes, None), vec![vec![1, 0, 0, 0, 1]]);
+}
+
+#[test]
+#[should_panic(expected = "The source of a skip connection should be a layer before the previous one")]
+fn test_skip_connection_from_previous_layer() {
+    SNNBuilder::new(1)
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0)], vec![vec![1.0]], vec![vec![0.0]])
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0)], vec![vec![1.0]], vec![vec![0.0]])
+        .with_skip_connection(0, vec![vec![1.0]]);
+}
+
+#[test]
+fn test_process_snn_sparse_weights() {
+    // the same network with dense and sparse weights: half of the weights are zero
+    let builder = SNNBuilder::new(4)
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.8, 1.0, 1.0); 3], vec![
+            vec![0.0, 0.9, 0.0, 0.3],
+            vec![0.5, 0.0, 0.0, 0.0],
+            vec![0.0, 0.4, 0.7, 0.0]], vec![
+                vec![0.0, -0.3, 0.0],
+                vec![0.0, 0.0, -0.6],
+                vec![0.0, 0.0, 0.0]])
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.5, 1.0, 1.0); 2], vec![
+            vec![0.6, 0.0, 0.0],
+            vec![0.0, 0.0, 0.8]], vec![
+                vec![0.0, -0.2],
+                vec![0.0, 0.0]]);
+    let dense_snn = builder.clone().build();
+    let sparse_snn = builder.with_sparse_weights().build();
+
+    {
+        let layer = sparse_snn.get_layer(0);
+        let layer = layer.downcast_ref::<Layer<Lif>>().unwrap();
+        assert_eq!(layer.get_input_length(), 4);
+        assert_eq!(layer.get_sparse_extra_weights().unwrap().get_num_values(), 5);
+        assert_eq!(layer.get_sparse_intra_weights().unwrap().get_num_values(), 2);
+        assert_eq!(&layer.get_sparse_extra_weights().unwrap().to_dense(), dense_snn.get_layer(0).downcast_ref::<Layer<Lif>>().unwrap().get_extra_weights());
+    }
+
+    let input_spikes = vec![
+        vec![1, 0, 1, 1, 0, 1, 1, 0],
+        vec![0, 1, 1, 0, 1, 1, 0, 1],
+        vec![1, 1, 0, 0, 1, 0, 1, 1],
+        vec![0, 0, 1, 1, 1, 0, 0, 1]];
+    assert_eq!(sparse_snn.process_input(&input_spikes, None), dense_snn.process_input(&input_spikes, None));
+}
+
+#[test]
+#[should_panic(expected = "The weights of a layer learning with the STDP rule should be stored as dense matrices")]
+fn test_sparse_weights_with_stdp() {
+    SNNBuilder::new(1)
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.5, 1.0, 1.0)], vec![vec![1.0]], vec![vec![0.0]])
+        .with_stdp(StdpParameters::new(0.1, 0.1, 1.0, 1.0, 0.0, 1.0))
+        .with_sparse_weights();
+}
+
+#[test]
+fn test_prune_weights_by_magnitude() {
+    let snn = SNNBuilder::new(3)
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.5, 1.0, 1.0); 2], vec![
+            vec![0.05, -0.8, 0.2],
+            vec![-0.1, 0.0, 0.9]], vec![
+                vec![0.0, -0.05],
+                vec![-0.3, 0.0]])
+        .with_pruning(PruningCriterion::Magnitude(0.15))
+        .build();
+    let layer = snn.get_layer(0);
+    assert_eq!(layer.get_dense_extra_weights(), vec![vec![0.0, -0.8, 0.2], vec![0.0, 0.0, 0.9]]);
+    assert_eq!(layer.get_dense_intra_weights(), vec![vec![0.0, 0.0], vec![-0.3, 0.0]]);
+}
+
+#[test]
+fn test_prune_weights_by_sparsity() {
+    // half of the weights of each matrix are removed: the weights already 0 count for the sparsity
+    let mut snn = SNNBuilder::new(4)
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.5, 1.0, 1.0); 2], vec![
+            vec![0.4, 0.0, -0.1, 0.7],
+            vec![0.3, -0.9, 0.2, 0.0]], vec![
+                vec![0.0, -0.2],
+                vec![-0.4, 0.0]])
+        .with_sparse_weights()
+        .build();
+    assert_eq!(snn.get_num_weights(), 12);
+    assert_eq!(snn.get_num_synapses(), 8);
+    assert_eq!(snn.prune(PruningCriterion::Sparsity(0.5)), 2);
+    assert_eq!(snn.get_num_synapses(), 6);
+
+    // the pruned synapses are no longer stored
+    let layer = snn.get_layer(0);
+    assert_eq!(layer.get_dense_extra_weights(), vec![vec![0.4, 0.0, 0.0, 0.7], vec![0.3, -0.9, 0.0, 0.0]]);
+    assert_eq!(layer.downcast_ref::<Layer<Lif>>().unwrap().get_sparse_extra_weights().unwrap().get_num_values(), 4);
+    assert_eq!(layer.get_dense_intra_weights(), vec![vec![0.0, -0.2], vec![-0.4, 0.0]]);
+}
+
+#[test]
+fn test_prune_with_report_and_save_weights() {
+    // the first output neuron fires only for the first input alone, the second one needs both the inputs:
+    // pruning the small weights silences the second neuron
+    let mut snn = SNNBuilder::new(2)
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.5, 1e-3, 1.0); 2], vec![
+            vec![0.6, -1.0],
+            vec![0.3, 0.3]], vec![
+                vec![0.0, 0.0],
+                vec![0.0, 0.0]])
+        .build();
+    let input_spike_train = vec![
+        vec![vec![1, 0], vec![0, 0]],
+        vec![vec![1, 1], vec![1, 1]]];
+    let targets = vec![0, 1];
+
+    let report = snn.prune_with_report(PruningCriterion::Magnitude(0.5), &input_spike_train, &targets);
+    assert_eq!((report.accuracy_before, report.accuracy_after), (100.0, 50.0));
+    assert_eq!((report.num_weights, report.num_synapses_before, report.num_synapses_after), (8, 4, 2));
+    assert_eq!(report.get_sparsity(), 0.75);
+
+    // the pruned weights are saved in the format of the weight files
+    let directory = std::env::temp_dir().join("test_prune_with_report_and_save_weights");
+    std::fs::create_dir_all(&directory).unwrap();
+    let filenames = snn.save_weights(directory.to_str().unwrap());
+    assert_eq!(filenames.len(), 1);
+    assert!(filenames[0].0.ends_with("weightsFile1.txt"));
+    assert!(filenames[0].1.ends_with("intraWeightsFile1.txt"));
+    assert_eq!(std::fs::read_to_string(&filenames[0].0).unwrap(), "0.6 -1\n0 0\n");
+    assert_eq!(std::fs::read_to_string(&filenames[0].1).unwrap(), "0 0\n0 0\n");
+}
+
+#[test]
+fn test_save_weights_and_load_from_setup() {
+    let snn = SNNBuilder::new(1)
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0); 2], vec![
+            vec![1.0],
+            vec![0.5]], vec![
+                vec![0.0, -0.2],
+                vec![-0.4, 0.0]])
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0); 2], vec![
+            vec![1.0, 0.0],
+            vec![0.25, 1.0]], vec![
+                vec![0.0, -0.5],
+                vec![-0.25, 0.0]])
+        .build();
+
+    // the saved extra and intra weights are loaded back as the weight files of the configuration
+    let directory = std::env::temp_dir().join("test_save_weights_and_load_from_setup");
+    std::fs::create_dir_all(&directory).unwrap();
+    let filenames = snn.save_weights(directory.to_str().unwrap());
+    let mut setup = get_heterogeneous_setup("group02_save_weights_and_load_from_setup", "0.5\n0.5\n");
+    setup.extra_weights = filenames.iter().map(|(extra, _)| format!("'{}'", extra)).collect();
+    setup.intra_weights = filenames.iter().map(|(_, intra)| format!("'{}'", intra)).collect();
+    let (loaded_snn, _input_spikes, _targets) = build_network_from_setup(setup);
+
+    for (layer, loaded_layer) in snn.get_layers().iter().zip(loaded_snn.get_layers().iter()) {
+        let (layer, loaded_layer) = (layer, loaded_layer);
+        assert_eq!(loaded_layer.get_dense_extra_weights(), layer.get_dense_extra_weights());
+        assert_eq!(loaded_layer.get_dense_intra_weights(), layer.get_dense_intra_weights());
+    }
+}
+
+#[test]
+#[should_panic(expected = "Only the weights of the fully connected layers can be saved in the weight files")]
+fn test_save_weights_convolutional_layer() {
+    let convolution = Convolution::new((1, 3, 3), vec![vec![vec![vec![1.0, 1.0], vec![1.0, 1.0]]]], 2, 1);
+    let snn = SNNBuilder::new(9)
+        .add_conv_layer(vec![Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0); 4], convolution, vec![vec![0.0; 4]; 4])
+        .build();
+    snn.save_weights(std::env::temp_dir().to_str().unwrap());
+}
+
+#[test]
+fn test_decoders() {
+    let output_spikes = vec![
+        vec![0, 0, 1, 1],
+        vec![0, 1, 0, 0],
+        vec![0, 0, 1, 1],
+        vec![0, 0, 0, 0]];
+    let membrane_potentials = vec![0.2, 0.7, -0.1, 0.4];
+
+    // the first and the third neurons have the same number of spikes
+    assert_eq!(Decoder::SpikeCount.decode(&output_spikes, &membrane_potentials), Decision::NoDecision);
+    assert_eq!(Decoder::FirstSpike.decode(&output_spikes, &membrane_potentials), Decision::Class(1));
+    assert_eq!(Decoder::MembranePotential.decode(&output_spikes, &membrane_potentials), Decision::Class(1));
+    assert_eq!(Decoder::Population(2).decode(&output_spikes, &membrane_potentials), Decision::Class(0));
+
+    // no spikes => no decision
+    let silent_spikes = vec![vec![0; 4]; 4];
+    assert_eq!(Decoder::SpikeCount.decode(&silent_spikes, &membrane_potentials), Decision::NoDecision);
+    assert_eq!(Decoder::FirstSpike.decode(&silent_spikes, &membrane_potentials), Decision::NoDecision);
+    assert_eq!(Decoder::Population(2).decode(&silent_spikes, &membrane_potentials), Decision::NoDecision);
+    assert_eq!(Decoder::MembranePotential.decode(&silent_spikes, &[0.5, 0.5, 0.1, 0.0]), Decision::NoDecision);
+}
+
+#[test]
+#[should_panic(expected = "The number of output neurons should be a multiple of the size of the populations")]
+fn test_population_decoder_wrong_size() {
+    Decoder::Population(3).decode(&vec![vec![1]; 4], &[0.0; 4]);
+}
+
+#[test]
+fn test_decoded_accuracy() {
+    // the two output neurons never spike: only the membrane potential readout can decide
+    let snn = SNNBuilder::new(2)
+        .add_layer(vec![Lif::new(0.0, 0.0, 10.0, f64::INFINITY, 1.0); 2], vec![
+            vec![1.0, 0.0],
+            vec![0.0, 1.0]], vec![
+                vec![0.0, 0.0],
+                vec![0.0, 0.0]])
+        .build();
+    let input_spike_train = vec![
+        vec![vec![1, 1, 0], vec![0, 0, 1]],
+        vec![vec![0, 0, 0], vec![1, 0, 1]]];
+    let targets = vec![0, 1];
+
+    assert_eq!(snn.decode(&input_spike_train[0], None, Decoder::MembranePotential), Decision::Class(0));
+    assert_eq!(snn.get_output_membrane_potentials(), vec![2.0, 1.0]);
+    assert_eq!(snn.get_decoded_accuracy(&input_spike_train, &targets, Decoder::MembranePotential), 100.0);
+    assert_eq!(snn.get_decoded_accuracy(&input_spike_train, &targets, Decoder::SpikeCount), 0.0);
+}
+
+#[test]
+fn test_process_snn_readout_layer() {
+    // hidden layer of LIF neurons followed by a non-spiking readout layer
+    let lif_layers = SNNBuilder::new(1)
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0)], vec![vec![1.0]], vec![vec![0.0]])
+        .build_layers();
+    let readout_layers = SNNBuilder::new(1)
+        .add_layer(vec![LeakyIntegrator::new(0.5); 2], vec![vec![1.0], vec![-2.0]], vec![vec![0.0, 0.0], vec![0.0, 0.0]])
+        .build_layers();
+    let snn = SNN::new(lif_layers.into_iter().chain(readout_layers).collect());
+
+    // acc[t] = 0.5^(t - ts) * acc[ts] + weighted_sum[t], up to the last time instant of the input
+    let input_spikes = vec![vec![1, 0, 1, 0]];
+    assert_eq!(snn.process_input_readout(&input_spikes, None), vec![0.625, -1.25]);
+    assert_eq!(snn.process_input(&input_spikes, None), vec![vec![0; 4]; 2]);
+    assert_eq!(snn.decode(&input_spikes, None, Decoder::MembranePotential), Decision::Class(0));
+
+    // the readout layer is reset for each inference
+    assert_eq!(snn.process_input_readout(&vec![vec![0, 0, 0, 1]], None), vec![1.0, -2.0]);
+
+    // the potentials are returned with the output spikes of the same input, also when the inferences are concurrent
+    std::thread::scope(|scope| {
+        for i in 0..8 {
+            let snn = &snn;
+            scope.spawn(move || {
+                let (input_spikes, expected) = if i % 2 == 0 { (vec![vec![1, 0, 1, 0]], vec![0.625, -1.25]) } else { (vec![vec![0, 0, 0, 1]], vec![1.0, -2.0]) };
+                for _ in 0..10 {
+                    assert_eq!(snn.process_input_with_potentials(&input_spikes, None), (vec![vec![0; 4]; 2], expected.clone()));
+                }
+            });
+        }
+    });
+}
+
+#[test]
+fn test_process_batch() {
+    let snn = SNNBuilder::new(2)
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0); 2], vec![
+            vec![1.0, 0.0],
+            vec![0.0, 1.0]], vec![
+                vec![0.0, 0.0],
+                vec![0.0, 0.0]])
+        .build();
+    let input_spike_train = vec![
+        vec![vec![1, 1, 0], vec![0, 0, 1]],
+        vec![vec![0, 0, 0], vec![1, 0, 1]],
+        vec![vec![0, 0, 0], vec![0, 0, 0]],
+        vec![vec![1, 0, 0], vec![0, 1, 0]],
+        vec![vec![0, 1, 1], vec![1, 0, 0]]];
+    let targets = vec![0, 1, 1, 0, 0];
+
+    // the batch gives the same output spikes as the inputs processed one at a time, in the order of the inputs
+    let output_spikes = input_spike_train.iter().map(|input_spikes| snn.process_input(input_spikes, None)).collect::<Vec<Vec<Vec<u8>>>>();
+    for num_workers in 1..=6 {
+        let batch = snn.process_batch_with_workers(&input_spike_train, &targets, None, None, num_workers);
+        assert_eq!(batch.output_spikes, output_spikes);
+        assert_eq!(batch.predictions, vec![Decision::Class(0), Decision::Class(1), Decision::Class(0), Decision::Class(0), Decision::Class(0)]);
+        assert_eq!(batch.accuracy, 80.0);
+    }
+
+    // with a decoder the inputs without a decision count as errors
+    let batch = snn.process_batch(&input_spike_train, &targets, None, Some(Decoder::SpikeCount));
+    assert_eq!(batch.predictions, vec![Decision::Class(0), Decision::Class(1), Decision::NoDecision, Decision::NoDecision, Decision::Class(0)]);
+    assert_eq!(batch.accuracy, 60.0);
+    assert_eq!(snn.get_decoded_accuracy(&input_spike_train, &targets, Decoder::SpikeCount), 60.0);
+}
+
+#[test]
+fn test_process_batch_with_stdp() {
+    let snn = SNNBuilder::new(2)
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.5, 1e-3, 1.0)], vec![vec![0.6, 0.47]], vec![vec![0.0]])
+        .with_stdp(StdpParameters::new(0.1, 0.1, 1.0, 1.0, 0.0, 1.0))
+        .build();
+    let input_spike_train = vec![
+        vec![vec![0, 1], vec![1, 0]],
+        vec![vec![0, 0], vec![1, 0]],
+        vec![vec![0, 1], vec![1, 0]],
+        vec![vec![0, 0], vec![1, 0]]];
+    let targets = vec![0, 0, 0, 0];
+
+    // the weights learned on an input would change the output of the following inputs of the same worker
+    let learning_snn = snn.clone();
+    let learned_output_spikes = input_spike_train.iter().map(|input_spikes| learning_snn.process_input(input_spikes, None)).collect::<Vec<Vec<Vec<u8>>>>();
+
+    // each input is processed with the weights of the network, so the output doesn't depend on the number of workers
+    let output_spikes = input_spike_train.iter().map(|input_spikes| snn.clone().process_input(input_spikes, None)).collect::<Vec<Vec<Vec<u8>>>>();
+    assert_ne!(output_spikes, learned_output_spikes);
+    for num_workers in 1..=4 {
+        assert_eq!(snn.process_batch_with_workers(&input_spike_train, &targets, None, None, num_workers).output_spikes, output_spikes);
+    }
+    assert_eq!(snn.get_layer(0).get_dense_extra_weights(), vec![vec![0.6, 0.47]]);
+}
+
+#[test]
+#[should_panic(expected = "Error: The number of targets is not consistent with the number of inputs of the batch")]
+fn test_process_batch_wrong_targets() {
+    let snn = SNNBuilder::new(1)
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0)], vec![vec![1.0]], vec![vec![0.0]])
+        .build();
+    snn.process_batch(&vec![vec![vec![1, 0]]], &vec![0, 0], None, None);
+}
+
+#[test]
+fn test_clone_shares_parameters() {
+    let snn = SNNBuilder::new(2)
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0); 2], vec![
+            vec![1.0, 0.0],
+            vec![0.0, 1.0]], vec![
+                vec![0.0, 0.0],
+                vec![0.0, 0.0]])
+        .build();
+    let clone = snn.clone();
+    let shared = |a: &SNN, b: &SNN| {
+        let a = a.get_layer(0).downcast_ref::<Layer<Lif>>().unwrap();
+        let b = b.get_layer(0).downcast_ref::<Layer<Lif>>().unwrap();
+        Arc::ptr_eq(a.get_weights(), b.get_weights()) && Arc::ptr_eq(a.get_neurons(), b.get_neurons())
+    };
+
+    // the inferences of the clone use the same weights, with their own state
+    assert!(shared(&snn, &clone));
+    assert_eq!(clone.process_input(&vec![vec![1, 0, 1], vec![0, 0, 0]], None), vec![vec![1, 0, 1], vec![0, 0, 0]]);
+    assert!(shared(&snn, &clone));
+    assert_eq!(clone.get_output_membrane_potentials(), vec![0.0, 0.0]);
+    assert_eq!(snn.process_input(&vec![vec![0, 0, 0], vec![0, 1, 0]], None), vec![vec![0, 0, 0], vec![0, 1, 0]]);
+
+    // the learning modifies only the weights learned by the inferences of the clone, the weights of the layer stay shared
+    let stdp_snn = SNNBuilder::new(1)
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0)], vec![vec![1.0]], vec![vec![0.0]])
+        .with_stdp(StdpParameters::new(0.1, 0.1, 1.0, 1.0, 0.0, 2.0))
+        .build();
+    let stdp_clone = stdp_snn.clone();
+    stdp_clone.process_input(&vec![vec![1, 1]], None);
+    assert!(Arc::ptr_eq(stdp_snn.get_layer(0).downcast_ref::<Layer<Lif>>().unwrap().get_weights(), stdp_clone.get_layer(0).downcast_ref::<Layer<Lif>>().unwrap().get_weights()));
+    assert_eq!(stdp_snn.get_learned_extra_weights(0), vec![vec![1.0]]);
+    assert_ne!(stdp_clone.get_learned_extra_weights(0), vec![vec![1.0]]);
+    assert_eq!(stdp_clone.get_layer(0).get_dense_extra_weights(), vec![vec![1.0]]);
+}
+
+#[test]
+fn test_process_input_with_state() {
+    let snn = SNNBuilder::new(2)
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0)], vec![vec![0.6, 0.2]], vec![vec![0.0]])
+        .with_stdp(StdpParameters::new(0.1, 0.1, 1.0, 1.0, 0.0, 1.0))
+        .build();
+    let input_spikes = vec![vec![1,0,0], vec![0,1,0]];
+    let expected = snn.clone().process_input(&input_spikes, None);
+
+    // the threads share the network without copying it, each inference with its own state
+    let states = std::thread::scope(|scope| {
+        let handles = (0..8).map(|_| scope.spawn(|| {
+            let mut state = snn.new_inference_state();
+            assert_eq!(snn.process_input_with_state(&mut state, &input_spikes, None), expected);
+            state
+        })).collect::<Vec<_>>();
+        handles.into_iter().map(|handle| handle.join().unwrap()).collect::<Vec<_>>()
+    });
+    for state in states.iter() {
+        let learned_weights = state.get_layer(0).get_learned_extra_weights().unwrap();
+        assert!((learned_weights[0][0] - 0.7).abs() < 1e-12);
+    }
+
+    // the weights learned in a state are kept for the next inferences with the same state,
+    // while the network and the state of its inferences are not modified
+    let mut state = states[0].clone();
+    for _ in 0..5 {
+        snn.process_input_with_state(&mut state, &input_spikes, None);
+    }
+    assert_eq!(state.get_layer(0).get_learned_extra_weights().unwrap(), &vec![vec![1.0, 0.0]]);
+    assert!((states[1].get_layer(0).get_learned_extra_weights().unwrap()[0][0] - 0.7).abs() < 1e-12);
+    assert_eq!(snn.get_learned_extra_weights(0), vec![vec![0.6, 0.2]]);
+    assert_eq!(snn.get_layer(0).get_dense_extra_weights(), vec![vec![0.6, 0.2]]);
+}
@@ -1,6 +1,28 @@
+use std::sync::Arc;
 use group02::network::config::{build_network_from_setup, compute_accuracy, compute_max_output_spike, network_setup_from_file};
 use group02::resilience::components::{ComponentType, ComponentCategory};
 use group02::resilience::fault_models::{FaultType, InjectedFault};
+use group02::resilience::simulation::UserSelection;
+use group02::network::builder::SNNBuilder;
+use group02::network::layer::Layer;
+use group02::network::snn::SNN;
+use group02::network::neuron::lif::{Lif, ResetMode};
+use group02::network::neuron::izhikevich::Izhikevich;
+use group02::network::neuron::neuron::Neuron;
+use group02::network::neuron::neuron_model::NeuronModel;
+use group02::network::neuron::cuba_lif::CubaLif;
+use group02::network::neuron::fixed_lif::FixedLif;
+use group02::network::neuron::stochastic_lif::StochasticLif;
+use group02::network::neuron::adaptive_lif::AdaptiveLif;
+use group02::network::neuron::adex::{AdEx, AdExParameters};
+use group02::network::neuron::leaky_integrator::LeakyIntegrator;
+use group02::network::fixed_point::QFormat;
+use group02::network::storage_format::StorageFormat;
+use group02::network::stdp::StdpParameters;
+use group02::network::convolution::Convolution;
+use group02::network::pooling::{Pooling, PoolingMode};
+use group02::network::pruning::PruningCriterion;
+use group02::network::decoder::{Decoder, Decision};
 
 /* THE RESULT OF THESE TESTS REFERS TO THE ORIGINAL IMPORTED NETWORK */
 
@@ -343,13 +365,10 @@ fn test_extra_weight_fault_injection() {
     - bit at index 0 from 0 to 1
     - time step from 0 to 2
 
-    Setting the time step to a value greater than the actual processing time, will result in a panic due to
-    an 'attempt to subtract with overflow' in the function 'process_input' of the neuron.rs file. In fact,
-    the time step is a u64 and can't support negative values.
-    - the program will panic
+    Setting the time step to a value greater than the actual processing time means that no time has elapsed
+    since the last update of the neuron: the membrane potential doesn't decay, but the program doesn't panic.
 */
 #[test]
-#[should_panic]
 fn test_positive_ts_fault_injection() {
 
   let n = network_setup_from_file();
@@ -385,3 +404,935 @@ fn test_positive_ts_fault_injection() {
   println!(""); // empty line
 
 }
+
+/**
+    This test injects a fault in the refractory counter of a LIF neuron with a refractory period of 1 time step.
+    - bit at index 63 from 0 to 1
+    - refractory counter from 0 to 2^63
+
+    The fault is active from the first time instant, so the neuron is refractory from the beginning of the input
+    and the counter never expires: we expect the neuron to **never fire**
+ */
+#[test]
+fn test_refractory_counter_fault_injection() {
+
+    let mut lif = Lif::new(0.0, 0.0, 0.5, 1.0, 1.0);
+    lif.set_refractory_period(1);
+    let snn = SNNBuilder::new(1)
+        .add_layer(vec![lif], vec![vec![1.0]], vec![vec![0.0]])
+        .build();
+
+    let input_spikes = vec![vec![1,1,1,1,1]];
+    assert_eq!(snn.process_input(&input_spikes, None), vec![vec![1,0,1,0,1]]);
+
+    // MANUAL FAULT INJECTION
+    //***************************************************************************
+    let fault = InjectedFault::new(FaultType::StuckAt1, None, 0, ComponentType::RefractoryCounter, ComponentCategory::MemoryArea, 0, Some(63));
+    //***************************************************************************
+
+    assert_eq!(snn.process_input(&input_spikes, Some(fault)), vec![vec![0,0,0,0,0]]);
+}
+
+/**
+    This test injects a fault in the reset flag of a LIF neuron with the reset by subtraction.
+    - stuck-at-0: the threshold is never subtracted after a spike, so we expect the neuron to **keep firing**
+    - stuck-at-1: the threshold is subtracted at every time step, so we expect the neuron to **never fire**
+ */
+#[test]
+fn test_reset_flag_fault_injection() {
+
+    let mut lif = Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0);
+    lif.set_reset_mode(ResetMode::Subtract);
+    let snn = SNNBuilder::new(1)
+        .add_layer(vec![lif], vec![vec![0.3]], vec![vec![0.0]])
+        .build();
+
+    let input_spikes = vec![vec![1,1,1,1,1,1]];
+    assert_eq!(snn.process_input(&input_spikes, None), vec![vec![0,1,0,1,0,1]]);
+
+    // MANUAL FAULT INJECTION
+    //***************************************************************************
+    let fault = InjectedFault::new(FaultType::StuckAt0, None, 0, ComponentType::ResetFlag, ComponentCategory::MemoryArea, 0, Some(0));
+    //***************************************************************************
+    assert_eq!(snn.process_input(&input_spikes, Some(fault)), vec![vec![0,1,1,1,1,1]]);
+
+    let fault = InjectedFault::new(FaultType::StuckAt1, None, 0, ComponentType::ResetFlag, ComponentCategory::MemoryArea, 0, Some(0));
+    assert_eq!(snn.process_input(&input_spikes, Some(fault)), vec![vec![0,0,0,0,0,0]]);
+}
+
+/**
+    This test injects a fault in the synaptic current of a CuBa LIF neuron.
+    - bit at index 62 stuck at 1
+    - the synaptic current after the first input spike is stored as 3.0 instead of 1.0
+
+    The charge given by the synaptic current in the following time steps is larger,
+    so we expect the neuron to **fire** when the threshold (3.0) was never reached in the original network
+ */
+#[test]
+fn test_synaptic_current_fault_injection() {
+
+    let cuba = CubaLif::new(Lif::new(0.0, 0.0, 3.0, f64::INFINITY, 1.0), 1.0 / 2f64.ln());
+    let snn = SNNBuilder::new(1)
+        .add_layer(vec![cuba], vec![vec![1.0]], vec![vec![0.0]])
+        .build();
+
+    let input_spikes = vec![vec![1,0,0,1]];
+    assert_eq!(snn.process_input(&input_spikes, None), vec![vec![0,0,0,0]]);
+
+    // MANUAL FAULT INJECTION
+    //***************************************************************************
+    let fault = InjectedFault::new(FaultType::StuckAt1, None, 0, ComponentType::SynapticCurrent, ComponentCategory::MemoryArea, 0, Some(62));
+    //***************************************************************************
+
+    assert_eq!(snn.process_input(&input_spikes, Some(fault)), vec![vec![0,0,0,1]]);
+}
+
+/**
+    This test injects a fault in the threshold of a fixed-point LIF neuron (Q8.8).
+    - bit at index 15 (sign bit of the 16-bit encoding) from 0 to 1
+    - threshold from 0.75 (0x00C0) to -127.25 (0x80C0)
+
+    The bit indices refer to the hardware bits, so we expect the neuron to **fire at each input spike**,
+    while a bit index beyond the width of the format doesn't exist and leaves the threshold unchanged
+ */
+#[test]
+fn test_fixed_point_threshold_fault_injection() {
+
+    let q = QFormat::from_name("Q8.8").unwrap();
+    let snn = SNNBuilder::new(1)
+        .add_layer(vec![FixedLif::new(0.0, 0.0, 0.75, 1.0, q)], vec![vec![0.5]], vec![vec![0.0]])
+        .build();
+
+    let input_spikes = vec![vec![1,0,1,0]];
+    assert_eq!(snn.process_input(&input_spikes, None), vec![vec![0,0,1,0]]);
+
+    // MANUAL FAULT INJECTION
+    //***************************************************************************
+    let fault = InjectedFault::new(FaultType::StuckAt1, None, 0, ComponentType::Threshold, ComponentCategory::MemoryArea, 0, Some(15));
+    let missing_bit_fault = InjectedFault::new(FaultType::StuckAt1, None, 0, ComponentType::Threshold, ComponentCategory::MemoryArea, 0, Some(40));
+    //***************************************************************************
+
+    assert_eq!(snn.process_input(&input_spikes, Some(fault)), vec![vec![1,0,1,0]]);
+    assert_eq!(snn.process_input(&input_spikes, Some(missing_bit_fault)), vec![vec![0,0,1,0]]);
+}
+
+/**
+    This test injects a fault in an extra weight stored in fixed point (Q8.8).
+    - bit at index 7 from 1 to 0
+    - weight from 0.5 (0x0080) to 0.0
+
+    The same bit of the f64 encoding of 0.5 is already 0, while in the integer encoding
+    it is the only bit set, so we expect the neuron to **never fire**
+ */
+#[test]
+fn test_fixed_point_extra_weight_fault_injection() {
+
+    let snn = SNNBuilder::new(1)
+        .with_weight_format(StorageFormat::Fixed(QFormat::new(8, 8)))
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.4, 1.0, 1.0)], vec![vec![0.5]], vec![vec![0.0]])
+        .build();
+
+    let input_spikes = vec![vec![1,1]];
+    assert_eq!(snn.process_input(&input_spikes, None), vec![vec![1,1]]);
+
+    // MANUAL FAULT INJECTION
+    //***************************************************************************
+    let fault = InjectedFault::new(FaultType::StuckAt0, None, 0, ComponentType::Extra, ComponentCategory::Connection, 0, Some(7));
+    //***************************************************************************
+
+    assert_eq!(snn.process_input(&input_spikes, Some(fault)), vec![vec![0,0]]);
+}
+
+/**
+    This test injects a fault in the threshold of a LIF neuron stored in f16.
+    - bit at index 15 (sign bit of the f16 encoding) from 0 to 1
+    - threshold from 0.75 (0x3A00) to -0.75 (0xBA00)
+
+    We expect the neuron to **fire at each input spike**, while the bit 63 (sign bit of the f64 encoding)
+    doesn't exist in f16 and leaves the threshold unchanged
+ */
+#[test]
+fn test_reduced_precision_threshold_fault_injection() {
+
+    let mut lif = Lif::new(0.0, 0.0, 0.75, 1.0, 1.0);
+    lif.set_storage_format(StorageFormat::F16);
+    let snn = SNNBuilder::new(1)
+        .with_weight_format(StorageFormat::F16)
+        .add_layer(vec![lif], vec![vec![0.5]], vec![vec![0.0]])
+        .build();
+
+    let input_spikes = vec![vec![1,0,1,0]];
+    assert_eq!(snn.process_input(&input_spikes, None), vec![vec![0,0,0,0]]);
+
+    // MANUAL FAULT INJECTION
+    //***************************************************************************
+    let fault = InjectedFault::new(FaultType::StuckAt1, None, 0, ComponentType::Threshold, ComponentCategory::MemoryArea, 0, Some(15));
+    let missing_bit_fault = InjectedFault::new(FaultType::StuckAt1, None, 0, ComponentType::Threshold, ComponentCategory::MemoryArea, 0, Some(63));
+    //***************************************************************************
+
+    assert_eq!(snn.process_input(&input_spikes, Some(fault)), vec![vec![1,0,1,0]]);
+    assert_eq!(snn.process_input(&input_spikes, Some(missing_bit_fault)), vec![vec![0,0,0,0]]);
+}
+
+/**
+    This test checks the width of the components used to select the bit index of the faults:
+    the weights and the memory areas follow their storage format, while the integer memory areas are 64-bit
+ */
+#[test]
+fn test_component_width() {
+
+    let mut lif = Lif::new(0.0, 0.0, 0.75, 1.0, 1.0);
+    lif.set_storage_format(StorageFormat::BF16);
+    let snn = SNNBuilder::new(1)
+        .with_weight_format(StorageFormat::F32)
+        .add_layer(vec![lif], vec![vec![0.5]], vec![vec![0.0]])
+        .build();
+
+    let layer = snn.get_layer(0);
+    assert_eq!(layer.get_component_width(ComponentType::Extra, 0), 32);
+    assert_eq!(layer.get_component_width(ComponentType::Threshold, 0), 16);
+    assert_eq!(layer.get_component_width(ComponentType::Adder, 0), 16);
+    assert_eq!(layer.get_component_width(ComponentType::Ts, 0), 64);
+}
+
+/**
+    This test injects a fault in the temperature of a stochastic LIF neuron.
+    - bit at index 62 from 0 to 1
+    - temperature from 1e-9 to about 1e299
+
+    The membrane potential (0.5) is always below the threshold (1.5) and the original neuron never fires,
+    while with a huge temperature the firing probability is 0.5, so we expect the neuron to **fire randomly**
+    (in the same instants in each inference, since the generator of the neuron is reseeded)
+ */
+#[test]
+fn test_temperature_fault_injection() {
+
+    let snn = SNNBuilder::new(1)
+        .add_layer(vec![StochasticLif::new(0.0, 0.0, 1.5, 0.1, 1.0, 1e-9, 3)], vec![vec![0.5]], vec![vec![0.0]])
+        .build();
+
+    let input_spikes = vec![vec![1; 20]];
+    assert_eq!(snn.process_input(&input_spikes, None), vec![vec![0; 20]]);
+
+    // MANUAL FAULT INJECTION
+    //***************************************************************************
+    let fault = InjectedFault::new(FaultType::StuckAt1, None, 0, ComponentType::Temperature, ComponentCategory::MemoryArea, 0, Some(62));
+    //***************************************************************************
+
+    let output_spikes = snn.process_input(&input_spikes, Some(fault));
+    assert!(output_spikes[0].contains(&1));
+    assert_eq!(snn.process_input(&input_spikes, Some(fault)), output_spikes);
+}
+
+/**
+    This test injects a fault in a high bit of the time step (u64) of the neurons that read it as a subtrahend.
+    - bit at index 40 from 0 to 1, so the last update time is far after the current time instant
+    - no time is considered elapsed, so the potential doesn't decay between the two input spikes
+    - the LIF neuron spikes at the second input spike, while the fault-free one doesn't
+*/
+#[test]
+fn test_high_bit_ts_fault_injection() {
+
+    let input_spikes: Vec<Vec<u8>> = vec![vec![1, 0, 0, 1]];
+    let snn = SNNBuilder::new(1)
+        .add_layer(vec![NeuronModel::from(Lif::new(0.0, 0.0, 1.0, 1.0, 1.0))], vec![vec![0.6]], vec![vec![0.0]])
+        .build();
+
+    // MANUAL FAULT INJECTION
+    //***************************************************************************
+    let fault = InjectedFault::new(FaultType::StuckAt1, None, 0, ComponentType::Ts, ComponentCategory::MemoryArea, 0, Some(40));
+    //***************************************************************************
+
+    assert_eq!(snn.process_input(&input_spikes, None), vec![vec![0, 0, 0, 0]]);
+    assert_eq!(snn.process_input(&input_spikes, Some(fault)), vec![vec![0, 0, 0, 1]]);
+
+    // the other models subtracting ts from the time instant don't panic either
+    assert_eq!(Lif::new(0.0, 0.0, 1.0, 1.0, 1.0).process_input(3, 0.5, Some(fault)), 0);
+    assert_eq!(AdaptiveLif::new(Lif::new(0.0, 0.0, 1.0, 1.0, 1.0), 0.5, 10.0).process_input(3, 0.5, Some(fault)), 0);
+    StochasticLif::new(0.0, 0.0, 1.0, 1.0, 1.0, 0.1, 42).process_input(3, 0.5, Some(fault));
+}
+
+/**
+    This test checks the fault surface published by the neuron models:
+    - the LIF neuron publishes Ts as a dynamic 64-bit memory area, which can also be accessed to be faulted
+    - the Izhikevich neuron doesn't have a tau, so a layer mixing the two models can fault it only in the LIF neuron
+ */
+#[test]
+fn test_fault_surface() {
+
+    let mut lif = Lif::new(0.0, 0.0, 0.75, 1.0, 1.0);
+    let ts = lif.get_fault_surface().into_iter().find(|c| c.component_type == ComponentType::Ts).unwrap();
+    assert_eq!((ts.bit_width, ts.is_static), (64, false));
+    assert_eq!(lif.get_parameter_to_fault(ComponentType::Ts).get_bits(StorageFormat::F64), 0);
+
+    let snn = SNNBuilder::new(1)
+        .add_layer(vec![
+            NeuronModel::from(Izhikevich::new(0.02, 0.2, -65.0, 8.0, 30.0, 1.0)),
+            NeuronModel::from(lif)], vec![
+            vec![1.0],
+            vec![1.0]], vec![
+            vec![0.0, 0.0],
+            vec![0.0, 0.0]
+        ])
+        .build();
+    {
+        let layer = snn.get_layer(0);
+        assert!(layer.get_faultable_component(ComponentType::Tau, 0).is_none());
+        assert!(layer.get_faultable_component(ComponentType::Tau, 1).unwrap().is_static);
+        assert!(layer.get_faultable_component(ComponentType::RecoveryVariable, 0).is_some());
+    }
+    let surface = snn.get_fault_surface();
+    assert_eq!(surface[0].component_type, ComponentType::Extra);
+    assert!(surface.iter().any(|c| c.component_type == ComponentType::Tau));
+    assert!(surface.iter().any(|c| c.component_type == ComponentType::RecoveryVariable));
+
+    // the random faults in tau are injected only in the LIF neuron
+    let user_selection = UserSelection::new(vec![ComponentType::Tau], FaultType::StuckAt1, 10, vec![vec![vec![1,0,1]]]);
+    let results = snn.run_simulation(user_selection, vec![1], 100.0);
+    assert_eq!(results.len(), 10);
+    assert!(results.iter().all(|(_, fault)| fault.component_index == 1 && fault.bit_index.unwrap() < 64));
+}
+
+/**
+    This test injects faults in the delay buffer of the connection between the input and the second neuron (delay 2).
+    - slot 2 stuck at 0: the spikes entering the buffer are lost, so we expect the neuron to **never fire**
+    - slot 1 flipped at time step 3: the spike entering the buffer at time step 3 is duplicated in flight,
+      so we expect the neuron to **fire one time step earlier** too
+ */
+#[test]
+fn test_delay_buffer_fault_injection() {
+
+    let snn = SNNBuilder::new(1)
+        .add_layer(vec![
+            Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0),
+            Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0)], vec![
+            vec![1.0],
+            vec![1.0]], vec![
+            vec![0.0, 0.0],
+            vec![0.0, 0.0]])
+        .with_delays(vec![vec![0], vec![2]])
+        .build();
+    {
+        let layer = snn.get_layer(0);
+        let delay_buffer = layer.get_faultable_component(ComponentType::DelayBuffer, 1).unwrap();
+        assert_eq!((delay_buffer.bit_width, delay_buffer.is_static), (3, false));
+        assert_eq!(delay_buffer.component_type.get_category(), ComponentCategory::Connection);
+    }
+
+    let input_spikes = vec![vec![1,0,0,1,0,0,0]];
+    assert_eq!(snn.process_input(&input_spikes, None), vec![vec![1,0,0,1,0,0,0], vec![0,0,1,0,0,1,0]]);
+
+    // MANUAL FAULT INJECTION
+    //***************************************************************************
+    let stuck_at_0 = InjectedFault::new(FaultType::StuckAt0, None, 0, ComponentType::DelayBuffer, ComponentCategory::Connection, 1, Some(2));
+    let bit_flip = InjectedFault::new(FaultType::TransientBitFlip, Some(3), 0, ComponentType::DelayBuffer, ComponentCategory::Connection, 1, Some(1));
+    //***************************************************************************
+
+    assert_eq!(snn.process_input(&input_spikes, Some(stuck_at_0)), vec![vec![1,0,0,1,0,0,0], vec![0,0,0,0,0,0,0]]);
+    assert_eq!(snn.process_input(&input_spikes, Some(bit_flip)), vec![vec![1,0,0,1,0,0,0], vec![0,0,1,0,1,1,0]]);
+}
+
+/**
+    This test injects faults in the arbiter of a 1-winner-take-all layer, where the second neuron always wins.
+    - grant of the first neuron stuck at 1: we expect the first neuron to **fire** even if it loses the arbitration
+    - grant of the second neuron stuck at 0: we expect **no neuron to fire**, since the winner is inhibited
+    - grant of the second neuron flipped at time step 2: we expect the winner to **miss one spike**
+ */
+#[test]
+fn test_wta_arbiter_fault_injection() {
+
+    let snn = SNNBuilder::new(1)
+        .add_layer(vec![
+            Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0),
+            Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0)], vec![
+            vec![1.0],
+            vec![2.0]], vec![
+            vec![0.0, 0.0],
+            vec![0.0, 0.0]])
+        .with_winner_take_all(1)
+        .build();
+    assert!(snn.get_fault_surface().iter().any(|c| c.component_type == ComponentType::WtaArbiter && c.bit_width == 1));
+
+    let input_spikes = vec![vec![1,1,1]];
+    assert_eq!(snn.process_input(&input_spikes, None), vec![vec![0,0,0], vec![1,1,1]]);
+
+    // MANUAL FAULT INJECTION
+    //***************************************************************************
+    let stuck_at_1 = InjectedFault::new(FaultType::StuckAt1, None, 0, ComponentType::WtaArbiter, ComponentCategory::Arbitration, 0, None);
+    let stuck_at_0 = InjectedFault::new(FaultType::StuckAt0, None, 0, ComponentType::WtaArbiter, ComponentCategory::Arbitration, 1, None);
+    let bit_flip = InjectedFault::new(FaultType::TransientBitFlip, Some(2), 0, ComponentType::WtaArbiter, ComponentCategory::Arbitration, 1, None);
+    //***************************************************************************
+
+    assert_eq!(snn.process_input(&input_spikes, Some(stuck_at_1)), vec![vec![1,1,1], vec![1,1,1]]);
+    assert_eq!(snn.process_input(&input_spikes, Some(stuck_at_0)), vec![vec![0,0,0], vec![0,0,0]]);
+    assert_eq!(snn.process_input(&input_spikes, Some(bit_flip)), vec![vec![0,0,0], vec![1,1,0]]);
+
+    // the random faults in the arbiter are injected in one of the neurons of the layer
+    let user_selection = UserSelection::new(vec![ComponentType::WtaArbiter], FaultType::StuckAt0, 5, vec![input_spikes]);
+    let results = snn.run_simulation(user_selection, vec![1], 100.0);
+    assert!(results.iter().all(|(_, fault)| fault.component_index < 2 && fault.bit_index.is_none()));
+}
+
+/**
+    This test injects a permanent fault in an extra weight of a layer that learns with the STDP rule.
+    - bit at index 63 (sign bit) stuck at 1
+    - weight from 0.6 to -0.6
+
+    The weight changes during the simulation, so the fault is not static: it is applied when the weight is read
+    and when the learned weight is written back, so we expect the neuron to **never fire** and the stored weight to **stay negative**
+ */
+#[test]
+fn test_stdp_extra_weight_fault_injection() {
+
+    let snn = || SNNBuilder::new(1)
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0)], vec![vec![0.6]], vec![vec![0.0]])
+        .with_stdp(StdpParameters::new(0.1, 0.1, 1.0, 1.0, -1.0, 1.0))
+        .build();
+    let extra = snn().get_fault_surface().into_iter().find(|c| c.component_type == ComponentType::Extra).unwrap();
+    assert!(!extra.is_static);
+
+    let input_spikes = vec![vec![1,1,1]];
+    let snn_no_fault = snn();
+    assert_eq!(snn_no_fault.process_input(&input_spikes, None), vec![vec![1,1,1]]);
+    assert!(snn_no_fault.get_learned_extra_weights(0)[0][0] > 0.6);
+
+    // MANUAL FAULT INJECTION
+    //***************************************************************************
+    let fault = InjectedFault::new(FaultType::StuckAt1, None, 0, ComponentType::Extra, ComponentCategory::Connection, 0, Some(63));
+    //***************************************************************************
+
+    let snn_fault = snn();
+    assert_eq!(snn_fault.process_input(&input_spikes, Some(fault)), vec![vec![0,0,0]]);
+    assert!(snn_fault.get_learned_extra_weights(0)[0][0] < 0.0);
+}
+
+/**
+    This test injects a stuck-at-1 fault in the threshold comparator of the hidden neuron, that spikes in each time step
+    instead of one time step out of two.
+    - output LIF neuron: it copies the spikes of the hidden neuron, so we expect it to **fire twice as much**
+    - output adaptive threshold neuron: the threshold raised by each spike limits its firing rate,
+      so we expect it to **fire as much as without the fault**
+ */
+#[test]
+fn test_adaptive_threshold_limits_threshold_comparator_fault() {
+
+    let snn = |output: NeuronModel| SNNBuilder::new(1)
+        .add_layer(vec![NeuronModel::from(Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0))], vec![vec![0.3]], vec![vec![0.0]])
+        .add_layer(vec![output], vec![vec![0.9]], vec![vec![0.0]])
+        .build();
+    let snn_lif = snn(NeuronModel::from(Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0)));
+    let snn_adaptive = snn(NeuronModel::from(AdaptiveLif::new(Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0), 1.0, 1.0 / 2f64.ln())));
+
+    let input_spikes = vec![vec![1,1,1,1,1,1]];
+    assert_eq!(snn_lif.process_input(&input_spikes, None), vec![vec![0,1,0,1,0,1]]);
+    assert_eq!(snn_adaptive.process_input(&input_spikes, None), vec![vec![0,1,0,1,0,1]]);
+
+    // MANUAL FAULT INJECTION
+    //***************************************************************************
+    let fault = InjectedFault::new(FaultType::StuckAt1, None, 0, ComponentType::ThresholdComparator, ComponentCategory::InternalProcessingBlock, 0, None);
+    //***************************************************************************
+
+    assert_eq!(snn_lif.process_input(&input_spikes, Some(fault)), vec![vec![1,1,1,1,1,1]]);
+    assert_eq!(snn_adaptive.process_input(&input_spikes, Some(fault)), vec![vec![1,0,1,0,1,0]]);
+}
+
+/**
+    This test injects a fault in the threshold offset of an adaptive threshold neuron.
+    - bit at index 63 (sign bit) stuck at 1
+    - the offset after each spike is stored as -1.0 instead of 1.0
+
+    The threshold is lowered after each spike instead of raised, so we expect the neuron to **fire at each input spike**
+ */
+#[test]
+fn test_threshold_offset_fault_injection() {
+
+    let adaptive = AdaptiveLif::new(Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0), 1.0, 1.0 / 2f64.ln());
+    let offset = adaptive.get_fault_surface().into_iter().find(|c| c.component_type == ComponentType::ThresholdOffset).unwrap();
+    assert!(!offset.is_static);
+    let snn = SNNBuilder::new(1)
+        .add_layer(vec![adaptive], vec![vec![0.9]], vec![vec![0.0]])
+        .build();
+
+    let input_spikes = vec![vec![1,1,1,1,1]];
+    assert_eq!(snn.process_input(&input_spikes, None), vec![vec![1,0,1,0,1]]);
+
+    // MANUAL FAULT INJECTION
+    //***************************************************************************
+    let fault = InjectedFault::new(FaultType::StuckAt1, None, 0, ComponentType::ThresholdOffset, ComponentCategory::MemoryArea, 0, Some(63));
+    //***************************************************************************
+
+    assert_eq!(snn.process_input(&input_spikes, Some(fault)), vec![vec![1,1,1,1,1]]);
+}
+
+/**
+    This test injects a fault in a shared weight of the kernel of the first output channel of a convolutional layer.
+    - sign bit of the first kernel weight stuck at 1: 0.5 -> -0.5
+    - we expect the fault to **spread to all the output positions** of the first channel, which don't fire anymore,
+      while the second channel (with its own kernel) is not affected
+ */
+#[test]
+fn test_kernel_weight_fault_injection() {
+
+    let kernel = vec![vec![vec![0.5, 0.5], vec![0.5, 0.5]]];
+    let convolution = Convolution::new((1, 3, 3), vec![kernel.clone(), kernel], 1, 0);
+    let snn = SNNBuilder::new(9)
+        .add_conv_layer(vec![Lif::new(0.0, 0.0, 1.5, f64::INFINITY, 1.0); 8], convolution, vec![vec![0.0; 8]; 8])
+        .build();
+    {
+        let layer = snn.get_layer(0);
+        let kernel_weight = layer.get_faultable_component(ComponentType::KernelWeight, 0).unwrap();
+        assert_eq!((kernel_weight.bit_width, kernel_weight.is_static), (64, true));
+        assert_eq!(kernel_weight.component_type.get_category(), ComponentCategory::Connection);
+        assert!(layer.get_faultable_component(ComponentType::Extra, 0).is_none());
+        assert_eq!(layer.get_faultable_indices(ComponentType::KernelWeight).len(), 8);
+    }
+
+    let input_spikes = vec![vec![1]; 9];
+    assert_eq!(snn.process_input(&input_spikes, None), vec![vec![1]; 8]);
+
+    // MANUAL FAULT INJECTION
+    //***************************************************************************
+    let fault = InjectedFault::new(FaultType::StuckAt1, None, 0, ComponentType::KernelWeight, ComponentCategory::Connection, 0, Some(63));
+    //***************************************************************************
+
+    assert_eq!(snn.process_input(&input_spikes, Some(fault)), vec![
+        vec![0], vec![0], vec![0], vec![0],
+        vec![1], vec![1], vec![1], vec![1]]);
+}
+
+/**
+    This test injects faults in the pooling units of a pooling layer (one 2x2 window for each of the two channels),
+    where only the first channel receives spikes.
+    - OR output of the second unit stuck at 1: we expect the second neuron to **fire** even without input spikes
+    - sum of the first unit with the sign bit stuck at 1: we expect the first neuron to **never fire**
+ */
+#[test]
+fn test_pooling_unit_fault_injection() {
+
+    let snn = |mode: PoolingMode| {
+        SNNBuilder::new(8)
+            .add_pool_layer(vec![Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0); 2], Pooling::new((2, 2, 2), 2, 2, mode))
+            .build()
+    };
+    let snn_max = snn(PoolingMode::Max);
+    let snn_sum = snn(PoolingMode::Sum);
+    {
+        let layer = snn_max.get_layer(0);
+        let pooling_unit = layer.get_faultable_component(ComponentType::PoolingUnit, 1).unwrap();
+        assert_eq!((pooling_unit.bit_width, pooling_unit.is_static), (1, false));
+        assert_eq!(pooling_unit.component_type.get_category(), ComponentCategory::InternalProcessingBlock);
+        assert!(layer.get_faultable_component(ComponentType::Extra, 0).is_none());
+        assert_eq!(snn_sum.get_layer(0).get_component_width(ComponentType::PoolingUnit, 0), 64);
+    }
+
+    let mut input_spikes = vec![vec![0, 0, 0]; 8];
+    input_spikes[0] = vec![1, 0, 1];
+    input_spikes[3] = vec![0, 1, 1];
+    assert_eq!(snn_max.process_input(&input_spikes, None), vec![vec![1, 1, 1], vec![0, 0, 0]]);
+    assert_eq!(snn_sum.process_input(&input_spikes, None), vec![vec![1, 1, 1], vec![0, 0, 0]]);
+
+    // MANUAL FAULT INJECTION
+    //***************************************************************************
+    let or_stuck_at_1 = InjectedFault::new(FaultType::StuckAt1, None, 0, ComponentType::PoolingUnit, ComponentCategory::InternalProcessingBlock, 1, None);
+    let sum_stuck_at_1 = InjectedFault::new(FaultType::StuckAt1, None, 0, ComponentType::PoolingUnit, ComponentCategory::InternalProcessingBlock, 0, Some(63));
+    //***************************************************************************
+
+    assert_eq!(snn_max.process_input(&input_spikes, Some(or_stuck_at_1)), vec![vec![1, 1, 1], vec![1, 1, 1]]);
+    assert_eq!(snn_sum.process_input(&input_spikes, Some(sum_stuck_at_1)), vec![vec![0, 0, 0], vec![0, 0, 0]]);
+}
+
+/**
+    This test checks the random fault injection in a network mixing a pooling layer of LIF neurons
+    and a dense layer of Izhikevich neurons: each fault is injected in the layer that has the selected component.
+ */
+#[test]
+fn test_heterogeneous_snn_fault_injection() {
+
+    let pooling_layers = SNNBuilder::new(4)
+        .add_pool_layer(vec![Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0)], Pooling::new((1, 2, 2), 2, 1, PoolingMode::Sum))
+        .build_layers();
+    let izhikevich_layers = SNNBuilder::new(1)
+        .add_layer(vec![Izhikevich::new(0.02, 0.2, -65.0, 8.0, 30.0, 1.0)], vec![vec![60.0]], vec![vec![0.0]])
+        .build_layers();
+    let snn = SNN::new(pooling_layers.into_iter().chain(izhikevich_layers).collect());
+
+    let surface = snn.get_fault_surface();
+    for component_type in [ComponentType::Extra, ComponentType::Tau, ComponentType::RecoveryVariable, ComponentType::PoolingUnit] {
+        assert!(surface.iter().any(|c| c.component_type == component_type));
+    }
+
+    let input_sequence = vec![vec![vec![1,0,1]; 4]];
+    for (component_type, layer_index) in [(ComponentType::PoolingUnit, 0), (ComponentType::Tau, 0), (ComponentType::RecoveryVariable, 1), (ComponentType::Extra, 1)] {
+        let user_selection = UserSelection::new(vec![component_type], FaultType::TransientBitFlip, 5, input_sequence.clone());
+        let results = snn.run_simulation(user_selection, vec![0], 100.0);
+        assert_eq!(results.len(), 5);
+        assert!(results.iter().all(|(_, fault)| fault.layer_index == layer_index && fault.component_index == 0));
+    }
+}
+
+/**
+    This test injects a fault in the weights of a specific edge of a network with skip connections:
+    the output layer receives the spikes of the third layer and, through two skip connections, of the first and second layers.
+    - sign bit of the weight of the skip connection with the second layer stuck at 1: 1.0 -> -1.0
+    - we expect the output neuron to **never fire**, since it needs the spikes of all the three incoming edges
+ */
+#[test]
+fn test_skip_weight_fault_injection() {
+
+    let relay = || Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0);
+    let mut snn = SNNBuilder::new(1)
+        .add_layer(vec![relay()], vec![vec![1.0]], vec![vec![0.0]])
+        .add_layer(vec![relay()], vec![vec![1.0]], vec![vec![0.0]])
+        .add_layer(vec![relay()], vec![vec![1.0]], vec![vec![0.0]])
+        .add_layer(vec![Lif::new(0.0, 0.0, 2.5, 1e-3, 1.0)], vec![vec![1.0]], vec![vec![0.0]])
+        .with_skip_connection(1, vec![vec![1.0]])
+        .with_skip_connection(0, vec![vec![1.0]])
+        .build();
+
+    let index;
+    {
+        let layer = snn.get_layer(3);
+        let skip_weight = layer.get_faultable_component(ComponentType::SkipWeight, 0).unwrap();
+        assert_eq!((skip_weight.bit_width, skip_weight.is_static), (64, true));
+        assert_eq!(skip_weight.component_type.get_category(), ComponentCategory::Connection);
+        assert_eq!(layer.get_faultable_indices(ComponentType::SkipWeight), vec![0, 1]);
+        assert!(snn.get_layer(2).get_faultable_component(ComponentType::SkipWeight, 0).is_none());
+        index = layer.downcast_ref::<Layer<Lif>>().unwrap().get_skip_weight_index(1, 0, 0);
+    }
+    assert_eq!(index, 1);
+
+    let input_spikes = vec![vec![1,0,1]];
+    assert_eq!(snn.process_input(&input_spikes, None), vec![vec![1,0,1]]);
+
+    // MANUAL FAULT INJECTION
+    //***************************************************************************
+    let mut fault = InjectedFault::new(FaultType::StuckAt1, None, 3, ComponentType::SkipWeight, ComponentCategory::Connection, index, Some(63));
+    //***************************************************************************
+
+    assert_eq!(snn.process_input(&input_spikes, Some(fault)), vec![vec![0,0,0]]);
+
+    // the static fault applied before the processing corrupts only the weight of the selected edge
+    assert!(!snn.get_layer_mut(3).apply_fault_in_component(&mut fault));
+    {
+        let layer = snn.get_layer(3);
+        let skip_connections = layer.downcast_ref::<Layer<Lif>>().unwrap().get_skip_connections();
+        assert_eq!((skip_connections[0].get_weights()[0][0], skip_connections[1].get_weights()[0][0]), (1.0, -1.0));
+    }
+    assert_eq!(snn.process_input(&input_spikes, None), vec![vec![0,0,0]]);
+}
+
+/**
+    This test injects a fault in the extra weights of a layer with sparse weights:
+    only the non-zero weights are stored, so the faults can target only the existing synapses.
+    - the second input is connected only to the second neuron (third stored weight)
+    - sign bit of the weight stuck at 1: 1.0 -> -1.0
+    - we expect the second neuron to **never fire**
+ */
+#[test]
+fn test_sparse_extra_weight_fault_injection() {
+
+    let relay = || Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0);
+    let mut snn = SNNBuilder::new(2)
+        .add_layer(vec![relay(), relay()], vec![vec![1.0, 0.0], vec![1.0, 1.0]], vec![vec![0.0, 0.0], vec![0.0, 0.0]])
+        .with_sparse_weights()
+        .build();
+
+    {
+        let layer = snn.get_layer(0);
+        assert_eq!(layer.get_faultable_indices(ComponentType::Extra), vec![0, 1, 2]);
+        assert!(layer.get_faultable_indices(ComponentType::Intra).is_empty());
+    }
+
+    let input_spikes = vec![vec![0,0,1], vec![1,0,1]];
+    assert_eq!(snn.process_input(&input_spikes, None), vec![vec![0,0,1], vec![1,0,1]]);
+
+    // MANUAL FAULT INJECTION
+    //***************************************************************************
+    let mut fault = InjectedFault::new(FaultType::StuckAt1, None, 0, ComponentType::Extra, ComponentCategory::Connection, 2, Some(63));
+    //***************************************************************************
+
+    assert_eq!(snn.process_input(&input_spikes, Some(fault)), vec![vec![0,0,1], vec![0,0,0]]);
+
+    // the static fault applied before the processing corrupts the stored weight of the synapse
+    assert!(!snn.get_layer_mut(0).apply_fault_in_component(&mut fault));
+    {
+        let layer = snn.get_layer(0);
+        let weights = layer.downcast_ref::<Layer<Lif>>().unwrap().get_sparse_extra_weights().unwrap().to_dense();
+        assert_eq!(weights, vec![vec![1.0, 0.0], vec![1.0, -1.0]]);
+    }
+    assert_eq!(snn.process_input(&input_spikes, None), vec![vec![0,0,1], vec![0,0,0]]);
+}
+
+/**
+    This test compares the fault sites of a network before and after the pruning:
+    with sparse weights the pruned synapses are no longer stored, so they can't be selected for a fault
+ */
+#[test]
+fn test_pruned_network_fault_sites() {
+
+    let builder = SNNBuilder::new(3)
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.5, 1.0, 1.0); 2], vec![vec![0.9, 0.05, -0.7], vec![0.1, 0.8, 0.02]], vec![vec![0.0, -0.01], vec![-0.6, 0.0]])
+        .with_sparse_weights();
+    let snn = builder.clone().build();
+    let pruned_snn = builder.with_pruning(PruningCriterion::Magnitude(0.2)).build();
+
+    let num_sites = |snn: &SNN, component_type| snn.get_layer(0).get_faultable_indices(component_type).len();
+    assert_eq!((num_sites(&snn, ComponentType::Extra), num_sites(&snn, ComponentType::Intra)), (6, 2));
+    assert_eq!((num_sites(&pruned_snn, ComponentType::Extra), num_sites(&pruned_snn, ComponentType::Intra)), (3, 1));
+}
+
+/**
+    This test shows how the decoder changes which faults count as failures:
+    - sign bit of the extra weight of the first output neuron stuck at 1: 1.0 -> -1.0
+    - the output layer is silenced: compute_max_output_spike returns the class 0, which matches the target,
+      while the spike count decoder returns no decision and the fault is counted as a failure
+ */
+#[test]
+fn test_decoder_no_decision_fault_injection() {
+
+    let relay = || Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0);
+    let snn = SNNBuilder::new(1)
+        .add_layer(vec![relay(), relay()], vec![vec![1.0], vec![0.0]], vec![vec![0.0, 0.0], vec![0.0, 0.0]])
+        .build();
+    let input_spike_train = vec![vec![vec![1, 0, 1]]];
+    let targets = vec![0];
+
+    assert_eq!(snn.decode(&input_spike_train[0], None, Decoder::SpikeCount), Decision::Class(0));
+
+    // MANUAL FAULT INJECTION
+    //***************************************************************************
+    let fault = InjectedFault::new(FaultType::StuckAt1, None, 0, ComponentType::Extra, ComponentCategory::Connection, 0, Some(63));
+    //***************************************************************************
+
+    let output_spikes = snn.process_input(&input_spike_train[0], Some(fault));
+    assert_eq!(compute_accuracy(vec![compute_max_output_spike(output_spikes)], &targets), 100.0);
+    assert_eq!(snn.decode(&input_spike_train[0], Some(fault), Decoder::SpikeCount), Decision::NoDecision);
+
+    // the campaign with the decoder counts every fault in the extra weight as a failure
+    let user_selection = UserSelection::new(vec![ComponentType::Extra], FaultType::StuckAt1, 4, input_spike_train)
+        .with_decoder(Decoder::SpikeCount);
+    let results = snn.run_simulation(user_selection, targets, 100.0);
+    assert_eq!(results.len(), 4);
+    for (accuracy, fault) in results {
+        assert_eq!(fault.component_type, ComponentType::Extra);
+        if fault.component_index == 0 && fault.bit_index == Some(63) {
+            assert_eq!(accuracy, 0.0);
+        }
+    }
+}
+
+/**
+    This test injects a fault in the accumulator of a neuron of a non-spiking readout layer:
+    - sign bit of the accumulator of the first readout neuron stuck at 1
+    - the logit of the first class becomes negative, and the network decides for the second class
+ */
+#[test]
+fn test_readout_accumulator_fault_injection() {
+
+    let lif_layers = SNNBuilder::new(1)
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0)], vec![vec![1.0]], vec![vec![0.0]])
+        .build_layers();
+    let readout_layers = SNNBuilder::new(1)
+        .add_layer(vec![LeakyIntegrator::new(0.5); 2], vec![vec![1.0], vec![0.5]], vec![vec![0.0, 0.0], vec![0.0, 0.0]])
+        .build_layers();
+    let snn = SNN::new(lif_layers.into_iter().chain(readout_layers).collect());
+
+    {
+        let layer = snn.get_layer(1);
+        let surface = layer.get_fault_surface().into_iter().map(|c| c.component_type).collect::<Vec<ComponentType>>();
+        assert!(surface.contains(&ComponentType::Accumulator) && surface.contains(&ComponentType::Decay));
+        assert!(!surface.contains(&ComponentType::ThresholdComparator));
+        assert!(!layer.get_faultable_component(ComponentType::Accumulator, 0).unwrap().is_static);
+        assert!(layer.get_faultable_component(ComponentType::Decay, 0).unwrap().is_static);
+        assert_eq!(ComponentType::Decay.get_category(), ComponentCategory::MemoryArea);
+    }
+
+    let input_spikes = vec![vec![1, 0, 1, 0]];
+    assert_eq!(snn.process_input_readout(&input_spikes, None), vec![0.625, 0.3125]);
+    assert_eq!(snn.decode(&input_spikes, None, Decoder::MembranePotential), Decision::Class(0));
+
+    // MANUAL FAULT INJECTION
+    //***************************************************************************
+    let fault = InjectedFault::new(FaultType::StuckAt1, None, 1, ComponentType::Accumulator, ComponentCategory::MemoryArea, 0, Some(63));
+    //***************************************************************************
+
+    assert_eq!(snn.process_input_readout(&input_spikes, Some(fault)), vec![-0.375, 0.3125]);
+    assert_eq!(snn.decode(&input_spikes, Some(fault), Decoder::MembranePotential), Decision::Class(1));
+}
+
+/**
+    This test processes a batch of inputs with a fault injected in each inference:
+    - sign bit of the first extra weight stuck at 1
+    - the batch gives the same output spikes as the inputs processed one at a time with the fault
+    - the network is not modified by the batch
+ */
+#[test]
+fn test_process_batch_fault_injection() {
+
+    let relay = || Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0);
+    let snn = SNNBuilder::new(2)
+        .add_layer(vec![relay(), relay()], vec![vec![1.0, 0.0], vec![0.0, 1.0]], vec![vec![0.0, 0.0], vec![0.0, 0.0]])
+        .build();
+    let input_spike_train = vec![
+        vec![vec![1, 1, 0], vec![0, 0, 1]],
+        vec![vec![0, 0, 0], vec![1, 0, 1]],
+        vec![vec![1, 0, 1], vec![0, 0, 0]]];
+    let targets = vec![0, 1, 0];
+
+    // MANUAL FAULT INJECTION
+    //***************************************************************************
+    let fault = InjectedFault::new(FaultType::StuckAt1, None, 0, ComponentType::Extra, ComponentCategory::Connection, 0, Some(63));
+    //***************************************************************************
+
+    let output_spikes = input_spike_train.iter().map(|input_spikes| snn.process_input(input_spikes, Some(fault))).collect::<Vec<Vec<Vec<u8>>>>();
+    let batch = snn.process_batch_with_workers(&input_spike_train, &targets, Some(fault), Some(Decoder::SpikeCount), 2);
+    assert_eq!(batch.output_spikes, output_spikes);
+    assert_eq!(batch.predictions, vec![Decision::Class(1), Decision::Class(1), Decision::NoDecision]);
+    assert_eq!(batch.accuracy, 33.0);
+
+    assert_eq!(snn.process_batch(&input_spike_train, &targets, None, Some(Decoder::SpikeCount)).accuracy, 100.0);
+    assert_eq!(snn.get_layer(0).get_dense_extra_weights(), vec![vec![1.0, 0.0], vec![0.0, 1.0]]);
+}
+
+/**
+    This test runs a campaign of static faults (applied to the network before the processing)
+    and checks that each fault is isolated in its own copy of the network:
+    - the golden network is bit-identical after the campaign
+    - the accuracy of each fault is the one of the fault injected alone during the processing
+ */
+#[test]
+fn test_golden_network_unchanged_after_campaign() {
+
+    let snn = SNNBuilder::new(2)
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0); 2], vec![vec![1.0, 0.0], vec![0.0, 1.0]], vec![vec![0.0, -0.5], vec![-0.5, 0.0]])
+        .build();
+    let input_spike_train = vec![
+        vec![vec![1, 1, 0], vec![0, 0, 1]],
+        vec![vec![0, 0, 0], vec![1, 0, 1]],
+        vec![vec![1, 0, 1], vec![0, 1, 1]]];
+    let targets = vec![0, 1, 0];
+    let golden = format!("{:?}", snn);
+    let golden_bits = snn.get_layer(0).get_dense_extra_weights().iter().flatten().map(|w| w.to_bits()).collect::<Vec<u64>>();
+    let accuracy = snn.process_batch(&input_spike_train, &targets, None, None).accuracy;
+
+    let components = vec![ComponentType::Extra, ComponentType::Intra, ComponentType::Threshold, ComponentType::ResetPotential];
+    let user_selection = UserSelection::new(components, FaultType::StuckAt1, 40, input_spike_train.clone());
+    let results = snn.run_simulation(user_selection, targets.clone(), accuracy);
+    assert_eq!(results.len(), 40);
+
+    assert_eq!(format!("{:?}", snn), golden);
+    assert_eq!(snn.get_layer(0).get_dense_extra_weights().iter().flatten().map(|w| w.to_bits()).collect::<Vec<u64>>(), golden_bits);
+    assert_eq!(snn.process_batch(&input_spike_train, &targets, None, None).accuracy, accuracy);
+
+    for (fault_accuracy, fault) in results {
+        assert_eq!(snn.process_batch(&input_spike_train, &targets, Some(fault), None).accuracy, fault_accuracy);
+    }
+}
+
+/**
+    This test injects a static fault in a clone of the network:
+    - sign bit of the first extra weight stuck at 1
+    - only the faulted clone copies the weights of the layer, the other clones keep sharing them with the original network
+    - a fault in a neuron (threshold stuck at 1) copies only the neurons of the layer, the weights stay shared
+ */
+#[test]
+fn test_static_fault_copies_shared_parameters() {
+
+    let snn = SNNBuilder::new(2)
+        .add_layer(vec![Lif::new(0.0, 0.0, 0.5, f64::INFINITY, 1.0); 2], vec![vec![1.0, 0.0], vec![0.0, 1.0]], vec![vec![0.0, 0.0], vec![0.0, 0.0]])
+        .build();
+    let mut faulty = snn.clone();
+    let healthy = snn.clone();
+    let mut faulty_neuron = snn.clone();
+    let layer = |snn: &SNN| snn.get_layer(0).downcast_ref::<Layer<Lif>>().unwrap().clone();
+    let shared_weights = |a: &SNN, b: &SNN| Arc::ptr_eq(layer(a).get_weights(), layer(b).get_weights());
+    let shared_neurons = |a: &SNN, b: &SNN| Arc::ptr_eq(layer(a).get_neurons(), layer(b).get_neurons());
+
+    // MANUAL FAULT INJECTION
+    //***************************************************************************
+    let mut fault = InjectedFault::new(FaultType::StuckAt1, None, 0, ComponentType::Extra, ComponentCategory::Connection, 0, Some(63));
+    //***************************************************************************
+
+    assert!(!faulty.get_layer_mut(0).apply_fault_in_component(&mut fault));
+    assert!(!shared_weights(&snn, &faulty));
+    assert!(shared_neurons(&snn, &faulty));
+    assert!(shared_weights(&snn, &healthy));
+    assert_eq!(faulty.get_layer(0).get_dense_extra_weights(), vec![vec![-1.0, 0.0], vec![0.0, 1.0]]);
+    assert_eq!(snn.get_layer(0).get_dense_extra_weights(), vec![vec![1.0, 0.0], vec![0.0, 1.0]]);
+
+    let input_spikes = vec![vec![1, 0, 1], vec![0, 1, 0]];
+    assert_eq!(faulty.process_input(&input_spikes, None), vec![vec![0, 0, 0], vec![0, 1, 0]]);
+    assert_eq!(healthy.process_input(&input_spikes, None), input_spikes);
+
+    // MANUAL FAULT INJECTION
+    //***************************************************************************
+    let mut fault = InjectedFault::new(FaultType::StuckAt1, None, 0, ComponentType::Threshold, ComponentCategory::MemoryArea, 1, Some(62));
+    //***************************************************************************
+
+    assert!(!faulty_neuron.get_layer_mut(0).apply_fault_in_component(&mut fault));
+    assert!(shared_weights(&snn, &faulty_neuron));
+    assert!(!shared_neurons(&snn, &faulty_neuron));
+    assert_eq!(faulty_neuron.process_input(&input_spikes, None), vec![vec![1, 0, 1], vec![0, 0, 0]]);
+    assert_eq!(snn.process_input(&input_spikes, None), input_spikes);
+}
+
+/**
+    This test injects a fault in the adaptation current of an AdEx neuron.
+    - bit at index 61 (exponent) stuck at 1
+    - the adaptation current after the first spike (2.7) becomes about 2.7 * 2^512
+
+    The adaptation current pulls the membrane potential far below the resting potential,
+    so we expect the neuron to **fire only at the first input spike**
+ */
+#[test]
+fn test_adaptation_current_fault_injection() {
+
+    let adex = AdEx::new(AdExParameters {
+        reset_potential: -58.0, resting_potential: -70.0, threshold: -50.0, peak_potential: 0.0, slope_factor: 2.0,
+        tau: 10.0, adaptation_coupling: 0.1, adaptation_increment: 2.7, tau_adaptation: 144.0, dt: 1.0
+    });
+    let adaptation_current = adex.get_fault_surface().into_iter().find(|c| c.component_type == ComponentType::AdaptationCurrent).unwrap();
+    assert!(!adaptation_current.is_static);
+    let snn = SNNBuilder::new(1)
+        .add_layer(vec![adex], vec![vec![80.0]], vec![vec![0.0]])
+        .build();
+
+    let input_spikes = vec![vec![1,1,1,1,1]];
+    assert_eq!(snn.process_input(&input_spikes, None), vec![vec![1,1,1,1,1]]);
+
+    // MANUAL FAULT INJECTION
+    //***************************************************************************
+    let fault = InjectedFault::new(FaultType::StuckAt1, None, 0, ComponentType::AdaptationCurrent, ComponentCategory::MemoryArea, 0, Some(61));
+    //***************************************************************************
+
+    assert_eq!(snn.process_input(&input_spikes, Some(fault)), vec![vec![1,0,0,0,0]]);
+}
+
+/**
+    This test injects a fault in the recovery reset (d) of an Izhikevich neuron (regular spiking).
+    - bit at index 63 (sign) stuck at 1
+    - the recovery variable after each spike is decreased by 8 instead of increased
+
+    Without the fault the recovery variable raised by each spike prevents the next input from reaching the peak,
+    while the faulted one makes the neuron more excitable, so we expect the neuron to **fire at each input spike**
+ */
+#[test]
+fn test_recovery_reset_fault_injection() {
+
+    let izhikevich = Izhikevich::new(0.02, 0.2, -65.0, 8.0, 30.0, 1.0);
+    let recovery_reset = izhikevich.get_fault_surface().into_iter().find(|c| c.component_type == ComponentType::RecoveryReset).unwrap();
+    assert!(recovery_reset.is_static);
+    let snn = SNNBuilder::new(1)
+        .add_layer(vec![izhikevich], vec![vec![100.0]], vec![vec![0.0]])
+        .build();
+
+    let input_spikes = vec![vec![1,1,1,1,1,1]];
+    assert_eq!(snn.process_input(&input_spikes, None), vec![vec![1,0,1,0,1,0]]);
+
+    // MANUAL FAULT INJECTION
+    //***************************************************************************
+    let fault = InjectedFault::new(FaultType::StuckAt1, None, 0, ComponentType::RecoveryReset, ComponentCategory::MemoryArea, 0, Some(63));
+    //***************************************************************************
+
+    assert_eq!(snn.process_input(&input_spikes, Some(fault)), vec![vec![1,1,1,1,1,1]]);
+}